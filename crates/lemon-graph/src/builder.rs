@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+
+use petgraph::{graph::NodeIndex, Direction};
+use thiserror::Error;
+
+use crate::{
+    nodes::{AsyncNode, StoreWrapper, SyncNode},
+    Graph, GraphEdge, GraphNode, Value,
+};
+
+#[derive(Debug, Error)]
+pub enum GraphBuildError {
+    #[error("Data index {index} is used more than once by node {node:?}")]
+    DuplicateDataIndex { node: NodeIndex, index: usize },
+}
+
+/// Builds a [`Graph`], checking its data connections once finished.
+#[derive(Default)]
+pub struct GraphBuilder {
+    graph: Graph,
+}
+
+impl GraphBuilder {
+    /// Continues building an existing graph.
+    pub fn from_graph(graph: Graph) -> Self {
+        Self { graph }
+    }
+
+    /// Returns the graph being built, such as to add nodes using their wrappers.
+    pub fn graph_mut(&mut self) -> &mut Graph {
+        &mut self.graph
+    }
+
+    pub fn store(&mut self, value: Value) -> StoreWrapper {
+        StoreWrapper(self.graph.add_node(GraphNode::Store(value)))
+    }
+
+    /// Adds a store whose value is never changed by execution.
+    /// See [`GraphNode::Constant`].
+    pub fn constant(&mut self, value: Value) -> StoreWrapper {
+        StoreWrapper(self.graph.add_node(GraphNode::Constant(value)))
+    }
+
+    pub fn sync_node(&mut self, weight: impl SyncNode + 'static) -> NodeIndex {
+        self.graph.add_node(GraphNode::SyncNode(Box::new(weight)))
+    }
+
+    pub fn async_node(&mut self, weight: impl AsyncNode + 'static) -> NodeIndex {
+        self.graph.add_node(GraphNode::AsyncNode(Box::new(weight)))
+    }
+
+    /// Maps data between a node and a store, in either direction.
+    pub fn connect_data(
+        &mut self,
+        from: impl Into<NodeIndex>,
+        to: impl Into<NodeIndex>,
+        index: usize,
+    ) -> &mut Self {
+        self.graph
+            .add_edge(from.into(), to.into(), GraphEdge::DataMap(index));
+        self
+    }
+
+    /// Flows data from one store to another.
+    pub fn connect_stores(&mut self, from: StoreWrapper, to: StoreWrapper) -> &mut Self {
+        self.graph.add_edge(from.0, to.0, GraphEdge::DataFlow);
+        self
+    }
+
+    /// Runs `to` after `from`.
+    pub fn connect_flow(
+        &mut self,
+        from: impl Into<NodeIndex>,
+        to: impl Into<NodeIndex>,
+    ) -> &mut Self {
+        self.graph
+            .add_edge(from.into(), to.into(), GraphEdge::ExecutionFlow);
+        self
+    }
+
+    /// Returns the graph, checking that no node uses the same data index
+    /// more than once for its inputs or its outputs.
+    pub fn build(self) -> Result<Graph, GraphBuildError> {
+        for node in self.graph.node_indices() {
+            for direction in [Direction::Incoming, Direction::Outgoing] {
+                let mut seen = HashSet::new();
+
+                for edge in self.graph.edges_directed(node, direction) {
+                    if let GraphEdge::DataMap(index) = edge.weight() {
+                        if !seen.insert(*index) {
+                            return Err(GraphBuildError::DuplicateDataIndex {
+                                node,
+                                index: *index,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(self.graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nodes::{NodeError, SyncNode};
+
+    use super::*;
+
+    struct TestSync;
+
+    impl SyncNode for TestSync {
+        fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+            Ok(inputs)
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let mut builder = GraphBuilder::default();
+
+        let input = builder.store(Value::USize(1));
+        let output = builder.store(Value::USize(0));
+        let a = builder.sync_node(TestSync);
+        let b = builder.sync_node(TestSync);
+
+        builder
+            .connect_data(input, a, 0)
+            .connect_data(a, output, 0)
+            .connect_flow(a, b);
+
+        let graph = builder.build().unwrap();
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 3);
+    }
+
+    #[test]
+    fn test_duplicate_index() {
+        let mut builder = GraphBuilder::default();
+
+        let a = builder.store(Value::USize(1));
+        let b = builder.store(Value::USize(2));
+        let node = builder.sync_node(TestSync);
+
+        builder.connect_data(a, node, 0).connect_data(b, node, 0);
+
+        assert!(matches!(
+            builder.build(),
+            Err(GraphBuildError::DuplicateDataIndex { node: n, index: 0 }) if n == node
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction};
+
+use crate::{nodes::NodeError, Graph, GraphEdge, Value};
+
+/// Condition of a [`GraphEdge::ConditionalFlow`],
+/// tested against the first output store of the source node.
+///
+/// Comparisons between mismatched types are never true.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Condition {
+    /// The value equals the given value.
+    Equals(Value),
+    /// The value is `true`, a non-zero number, or non-empty.
+    /// [`Value::Null`] is never truthy.
+    Truthy,
+    /// The value is a number greater than the given number.
+    GreaterThan(f64),
+    /// The value is a string containing the given string.
+    Contains(String),
+}
+
+impl Condition {
+    pub fn evaluate(&self, value: &Value) -> bool {
+        match self {
+            Self::Equals(expected) => value == expected,
+            Self::Truthy => match value {
+                Value::Bool(value) => *value,
+                Value::Bytes(value) => !value.is_empty(),
+                Value::F32(value) => *value != 0.0,
+                Value::ISize(value) => *value != 0,
+                Value::Map(value) => !value.is_empty(),
+                Value::Null => false,
+                Value::String(value) => !value.is_empty(),
+                Value::USize(value) => *value != 0,
+                Value::Vec(value) => !value.is_empty(),
+            },
+            Self::GreaterThan(threshold) => as_f64(value).is_some_and(|value| value > *threshold),
+            Self::Contains(needle) => match value {
+                Value::String(value) => value.contains(needle.as_str()),
+                _ => false,
+            },
+        }
+    }
+
+    /// Whether the value's type can be tested by the condition.
+    /// Values that are not accepted never pass.
+    pub fn accepts(&self, value: &Value) -> bool {
+        match self {
+            Self::Equals(_) | Self::Truthy => true,
+            Self::GreaterThan(_) => as_f64(value).is_some(),
+            Self::Contains(_) => matches!(value, Value::String(_)),
+        }
+    }
+
+    /// Evaluates the condition against the first output store of `node`.
+    /// Nodes without an output store never pass.
+    pub(crate) fn evaluate_output(&self, graph: &Graph, node: NodeIndex) -> bool {
+        graph
+            .edges_directed(node, Direction::Outgoing)
+            .find(|edge| matches!(edge.weight(), GraphEdge::DataMap(0)))
+            .and_then(|edge| graph[edge.target()].value())
+            .map(|value| self.evaluate(value))
+            .unwrap_or_default()
+    }
+}
+
+/// Stores the condition as a single entry [`Value::Map`], such as `{"contains": "yes"}`.
+/// Thresholds are stored as strings, as [`Value`] has no `f64`.
+impl From<&Condition> for Value {
+    fn from(value: &Condition) -> Self {
+        let (key, value) = match value {
+            Condition::Equals(expected) => ("equals", expected.clone()),
+            Condition::Truthy => ("truthy", Value::Null),
+            Condition::GreaterThan(threshold) => {
+                ("greater_than", Value::String(threshold.to_string()))
+            }
+            Condition::Contains(needle) => ("contains", Value::String(needle.clone())),
+        };
+
+        Value::Map(BTreeMap::from([(key.to_string(), value)]))
+    }
+}
+
+impl TryFrom<&Value> for Condition {
+    type Error = NodeError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let entry = match value {
+            Value::Map(map) if map.len() == 1 => map.iter().next(),
+            _ => None,
+        };
+
+        match entry.map(|(key, value)| (key.as_str(), value)) {
+            Some(("equals", expected)) => Ok(Self::Equals(expected.clone())),
+            Some(("truthy", Value::Null)) => Ok(Self::Truthy),
+            Some(("greater_than", Value::String(threshold))) => threshold
+                .parse()
+                .map(Self::GreaterThan)
+                .map_err(|_| NodeError::ConversionError(value.clone())),
+            Some(("greater_than", threshold)) => Ok(Self::GreaterThan(threshold.as_f64()?)),
+            Some(("contains", Value::String(needle))) => Ok(Self::Contains(needle.clone())),
+            _ => Err(NodeError::ConversionError(value.clone())),
+        }
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::F32(value) => Some(*value as f64),
+        Value::ISize(value) => Some(*value as f64),
+        Value::USize(value) => Some(*value as f64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        nodes::{CallbackNode, NodeWrapper},
+        Executor,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        let equals = Condition::Equals(Value::USize(3));
+        assert!(equals.evaluate(&Value::USize(3)));
+        assert!(!equals.evaluate(&Value::USize(4)));
+        assert!(!equals.evaluate(&Value::String("3".to_string())));
+
+        assert!(Condition::Truthy.evaluate(&Value::Bool(true)));
+        assert!(Condition::Truthy.evaluate(&Value::String("yes".to_string())));
+        assert!(!Condition::Truthy.evaluate(&Value::String(String::new())));
+        assert!(!Condition::Truthy.evaluate(&Value::USize(0)));
+        assert!(!Condition::Truthy.evaluate(&Value::Null));
+
+        let greater = Condition::GreaterThan(0.5);
+        assert!(greater.evaluate(&Value::F32(0.75)));
+        assert!(greater.evaluate(&Value::USize(1)));
+        assert!(!greater.evaluate(&Value::ISize(-1)));
+        assert!(!greater.evaluate(&Value::String("1".to_string())));
+
+        let contains = Condition::Contains("yes".to_string());
+        assert!(contains.evaluate(&Value::String("I think yes.".to_string())));
+        assert!(!contains.evaluate(&Value::String("No.".to_string())));
+        assert!(!contains.evaluate(&Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_value_round_trip() {
+        for condition in [
+            Condition::Equals(Value::Vec(vec![Value::USize(1)])),
+            Condition::Truthy,
+            Condition::GreaterThan(0.1),
+            Condition::Contains("yes".to_string()),
+        ] {
+            let value = Value::from(&condition);
+            assert_eq!(Condition::try_from(&value).unwrap(), condition);
+        }
+
+        assert!(Condition::try_from(&Value::String("truthy".to_string())).is_err());
+    }
+
+    /// Runs a source node outputting `value`, with a branch for each condition.
+    /// Returns the indices of the branches that ran.
+    async fn branches(value: Value, conditions: Vec<Condition>) -> Vec<usize> {
+        let mut graph = Graph::default();
+        let ran = Rc::new(RefCell::new(Vec::new()));
+
+        let source = CallbackNode::new(&mut graph, move |_| value.clone());
+
+        for (i, condition) in conditions.into_iter().enumerate() {
+            let ran = ran.clone();
+            let branch = CallbackNode::new(&mut graph, move |input| {
+                ran.borrow_mut().push(i);
+                input
+            });
+            branch.run_after_if(&mut graph, source.0, condition);
+        }
+
+        Executor::execute(&mut graph, source.0).await.unwrap();
+
+        let mut ran = ran.borrow().clone();
+        ran.sort();
+        ran
+    }
+
+    #[tokio::test]
+    async fn test_conditional_flow() {
+        let conditions = || {
+            vec![
+                Condition::Equals(Value::String("Yes, it is.".to_string())),
+                Condition::Truthy,
+                Condition::GreaterThan(1.0),
+                Condition::Contains("Yes".to_string()),
+            ]
+        };
+
+        assert_eq!(
+            branches(Value::String("Yes, it is.".to_string()), conditions()).await,
+            vec![0, 1, 3]
+        );
+        assert_eq!(branches(Value::USize(2), conditions()).await, vec![1, 2]);
+        assert_eq!(
+            branches(Value::String(String::new()), conditions()).await,
+            Vec::<usize>::new()
+        );
+    }
+}
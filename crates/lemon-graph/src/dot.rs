@@ -0,0 +1,125 @@
+use std::fmt::Write;
+
+use petgraph::visit::EdgeRef;
+
+use crate::{Graph, GraphEdge, GraphLabels, GraphNode};
+
+/// Renders the graph in Graphviz DOT format, for debugging.
+///
+/// Stores are labeled with their current value, and executable nodes with
+/// their type name. Nodes with a label, see [`GraphLabels`], are prefixed with it.
+/// The output can be rendered with `dot -Tpng`.
+pub fn to_dot(graph: &Graph) -> String {
+    let mut dot = String::from("digraph {\n");
+
+    for index in graph.node_indices() {
+        let name = |name: String| match graph.label(index) {
+            Some(label) => format!("{}: {}", label, name),
+            None => name,
+        };
+
+        let attributes = match &graph[index] {
+            GraphNode::AsyncNode(node) => format!(
+                "label = {:?}, shape = box",
+                name(short_name(node.type_name()).to_string())
+            ),
+            GraphNode::SyncNode(node) => format!(
+                "label = {:?}, shape = box, style = rounded",
+                name(short_name(node.type_name()).to_string())
+            ),
+            GraphNode::Store(value) => {
+                format!("label = {:?}, shape = ellipse", name(value.to_string()))
+            }
+            GraphNode::Constant(value) => {
+                format!("label = {:?}, shape = plaintext", name(value.to_string()))
+            }
+        };
+
+        // Writing to a string cannot fail.
+        let _ = writeln!(dot, "    {} [ {} ]", index.index(), attributes);
+    }
+
+    for edge in graph.edge_references() {
+        let attributes = match edge.weight() {
+            GraphEdge::ExecutionFlow => "color = black, style = bold".to_string(),
+            GraphEdge::ConditionalFlow(condition) => format!(
+                "label = {:?}, color = black, style = \"bold,dashed\"",
+                format!("{:?}", condition)
+            ),
+            GraphEdge::ErrorFlow => "color = red, style = bold".to_string(),
+            GraphEdge::DataFlow => "color = blue, style = dashed".to_string(),
+            GraphEdge::DataMap(index) => format!("label = \"{}\", color = blue", index),
+        };
+
+        let _ = writeln!(
+            dot,
+            "    {} -> {} [ {} ]",
+            edge.source().index(),
+            edge.target().index(),
+            attributes
+        );
+    }
+
+    dot.push('}');
+    dot
+}
+
+/// Removes the module path and generic parameters from a type name.
+fn short_name(name: &str) -> &str {
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nodes::{LogNode, NodeWrapper};
+
+    use super::*;
+
+    #[test]
+    fn test_short_name() {
+        assert_eq!(short_name("a::b::Weight"), "Weight");
+        assert_eq!(short_name("a::Weight<b::Backend>"), "Weight");
+        assert_eq!(short_name("Weight"), "Weight");
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = Graph::default();
+
+        let a = LogNode::new(&mut graph);
+        let b = LogNode::new(&mut graph);
+        b.run_after(&mut graph, a.0);
+
+        let message = a.message(&graph).unwrap();
+        message.set_value(&mut graph, "Hello \"world\"".to_string().into());
+        b.message(&graph)
+            .unwrap()
+            .set_input(&mut graph, Some(message));
+
+        let dot = to_dot(&graph);
+
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("label = \"lemon.log\""));
+        assert!(dot.contains("label = \"Hello \\\"world\\\"\""));
+        assert!(dot.contains(&format!(
+            "{} -> {} [ color = black",
+            a.0.index(),
+            b.0.index()
+        )));
+        assert!(dot.contains("color = blue, style = dashed"));
+        assert!(dot.contains("[ label = \"0\", color = blue ]"));
+    }
+
+    #[test]
+    fn test_to_dot_label() {
+        let mut graph = Graph::default();
+        let log = LogNode::new(&mut graph);
+        graph.set_label(log.0, "greeter");
+
+        let dot = to_dot(&graph);
+
+        assert!(dot.contains("label = \"greeter: lemon.log\""));
+        assert!(!dot.contains("\"greeter\""));
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphNode, Value};
+
+use super::ExecutionStepError;
+
+/// State of an execution between two waves of steps,
+/// from which it can be continued with [`Executor::resume`](super::Executor::resume).
+///
+/// Checkpoints are only taken once every step in a wave has finished,
+/// so a node that was running when execution stopped is run again on resume.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Checkpoint {
+    /// Nodes that have finished running.
+    pub completed: BTreeSet<NodeIndex>,
+    /// Steps that will run in the next wave.
+    pub pending: Vec<NodeIndex>,
+    /// Number of completed incoming execution flows for each waiting join node.
+    pub arrivals: BTreeMap<NodeIndex, usize>,
+    /// Number of incoming execution flows that will not be taken for each waiting
+    /// join node, such as from a node that errored.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub skipped: BTreeMap<NodeIndex, usize>,
+    /// Values of every store in the graph.
+    pub stores: BTreeMap<NodeIndex, Value>,
+}
+
+impl Checkpoint {
+    pub(crate) fn stores(graph: &Graph) -> BTreeMap<NodeIndex, Value> {
+        graph
+            .node_indices()
+            .filter_map(|node| match &graph[node] {
+                GraphNode::Store(value) => Some((node, value.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Writes the saved store values back into the graph.
+    pub(crate) fn restore_stores(&self, graph: &mut Graph) -> Result<(), ExecutionStepError> {
+        for (node, value) in &self.stores {
+            match graph.node_weight_mut(*node) {
+                Some(GraphNode::Store(store)) => *store = value.clone(),
+                Some(_) => return Err(ExecutionStepError::InvalidWeight),
+                None => return Err(ExecutionStepError::NoWeight),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        nodes::{CallbackNode, NodeError, NodeWrapper, SyncNode},
+        ExecutionObserver, Executor, GraphEdge,
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Saver(Mutex<Option<Checkpoint>>);
+
+    impl ExecutionObserver for Saver {
+        fn checkpoint(&self, checkpoint: &Checkpoint) {
+            *self.0.lock().unwrap() = Some(checkpoint.clone());
+        }
+    }
+
+    /// Passes its input through, failing while `fail` is set.
+    struct Flaky {
+        fail: Rc<Cell<bool>>,
+        runs: Rc<Cell<usize>>,
+    }
+
+    impl SyncNode for Flaky {
+        fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+            self.runs.set(self.runs.get() + 1);
+
+            if self.fail.get() {
+                return Err(NodeError::InternalError("crashed".to_string()));
+            }
+
+            Ok(inputs)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume() {
+        let mut graph = Graph::default();
+
+        let a_runs = Rc::new(Cell::new(0));
+        let a_runs_cb = a_runs.clone();
+        let a = CallbackNode::new(&mut graph, move |_| {
+            a_runs_cb.set(a_runs_cb.get() + 1);
+            "hello".to_string().into()
+        });
+
+        let fail = Rc::new(Cell::new(true));
+        let b_runs = Rc::new(Cell::new(0));
+        let b = graph.add_node(GraphNode::SyncNode(Box::new(Flaky {
+            fail: fail.clone(),
+            runs: b_runs.clone(),
+        })));
+        graph.add_edge(a.0, b, GraphEdge::ExecutionFlow);
+        let b_input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(b_input, b, GraphEdge::DataMap(0));
+        let b_output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(b, b_output, GraphEdge::DataMap(0));
+
+        let a_output = a.output(&graph).unwrap();
+        graph.add_edge(a_output.0, b_input, GraphEdge::DataFlow);
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let received_cb = received.clone();
+        let c = CallbackNode::new(&mut graph, move |input| {
+            received_cb.borrow_mut().push(input.clone());
+            input
+        });
+        c.run_after(&mut graph, b);
+        let c_input = c.input(&graph).unwrap();
+        graph.add_edge(b_output, c_input.0, GraphEdge::DataFlow);
+
+        let saver = Arc::new(Saver::default());
+        let executor = Executor::default()
+            .with_checkpoints()
+            .with_observer(saver.clone());
+
+        assert!(executor.run(&mut graph, a.0).await.is_err());
+        assert_eq!(a_runs.get(), 1);
+        assert_eq!(b_runs.get(), 1);
+
+        let checkpoint = saver.0.lock().unwrap().take().unwrap();
+        assert_eq!(checkpoint.completed, BTreeSet::from([a.0]));
+        assert_eq!(checkpoint.pending, vec![b]);
+
+        // Stores are restored from the checkpoint.
+        a_output.set_value(&mut graph, "changed".to_string().into());
+
+        fail.set(false);
+        executor.resume(&mut graph, checkpoint).await.unwrap();
+
+        assert_eq!(a_runs.get(), 1);
+        assert_eq!(b_runs.get(), 2);
+        assert_eq!(*received.borrow(), vec![Value::String("hello".to_string())]);
+
+        let checkpoint = saver.0.lock().unwrap().take().unwrap();
+        assert_eq!(checkpoint.completed, BTreeSet::from([a.0, b, c.0]));
+        assert!(checkpoint.pending.is_empty());
+    }
+
+    #[test]
+    fn test_restore_invalid() {
+        let mut graph = Graph::default();
+        let a = CallbackNode::new(&mut graph, |input| input);
+
+        let checkpoint = Checkpoint {
+            stores: BTreeMap::from([(a.0, Value::Bool(true))]),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            checkpoint.restore_stores(&mut graph),
+            Err(ExecutionStepError::InvalidWeight)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let checkpoint = Checkpoint {
+            completed: BTreeSet::from([NodeIndex::new(0)]),
+            pending: vec![NodeIndex::new(1)],
+            arrivals: BTreeMap::from([(NodeIndex::new(2), 1)]),
+            skipped: BTreeMap::from([(NodeIndex::new(2), 1)]),
+            stores: BTreeMap::from([(NodeIndex::new(3), Value::USize(4))]),
+        };
+
+        let json = serde_json::to_string(&checkpoint).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Checkpoint>(&json).unwrap(),
+            checkpoint
+        );
+    }
+}
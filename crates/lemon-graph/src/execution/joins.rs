@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, HashSet};
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction};
+
+use crate::{Graph, GraphEdge, GraphNode};
+
+use super::ExecutionStep;
+
+/// Incoming execution flows of the join nodes waiting to run.
+///
+/// A join runs once each incoming flow has either arrived or been skipped,
+/// if at least one arrived. If every flow was skipped, the join is skipped too.
+#[derive(Debug, Default)]
+pub(super) struct Joins {
+    /// Flows that were taken, by join.
+    pub arrivals: BTreeMap<NodeIndex, usize>,
+    /// Flows that will not be taken, by join.
+    pub skipped: BTreeMap<NodeIndex, usize>,
+}
+
+impl Joins {
+    /// Records a flow into `node`, queueing it if it is ready to run.
+    pub fn arrive(&mut self, graph: &Graph, node: NodeIndex, steps: &mut Vec<ExecutionStep>) {
+        if !is_join(graph, node) {
+            steps.push(ExecutionStep(node));
+            return;
+        }
+
+        *self.arrivals.entry(node).or_default() += 1;
+        self.resolve(graph, node, steps);
+    }
+
+    /// Records that flows into `targets` will not be taken.
+    ///
+    /// Nodes that are not joins are not reached, so their outgoing flows are
+    /// skipped as well, until reaching the joins waiting on them.
+    pub fn skip(
+        &mut self,
+        graph: &Graph,
+        targets: impl IntoIterator<Item = NodeIndex>,
+        steps: &mut Vec<ExecutionStep>,
+    ) {
+        let mut targets = targets.into_iter().collect::<Vec<_>>();
+        let mut unreached = HashSet::new();
+
+        while let Some(target) = targets.pop() {
+            let reached = if is_join(graph, target) {
+                *self.skipped.entry(target).or_default() += 1;
+                self.resolve(graph, target, steps) != Some(false)
+            } else {
+                false
+            };
+
+            // Each node is only followed once, so cycles end.
+            if !reached && unreached.insert(target) {
+                targets.extend(execution_targets(graph, target));
+            }
+        }
+    }
+
+    /// Queues a join once every incoming flow has arrived or been skipped.
+    /// Returns whether it was queued, or `None` if it is still waiting.
+    fn resolve(
+        &mut self,
+        graph: &Graph,
+        node: NodeIndex,
+        steps: &mut Vec<ExecutionStep>,
+    ) -> Option<bool> {
+        let arrived = self.arrivals.get(&node).copied().unwrap_or_default();
+        let skipped = self.skipped.get(&node).copied().unwrap_or_default();
+
+        let expected = graph
+            .edges_directed(node, Direction::Incoming)
+            .filter(|edge| is_execution_flow(edge.weight()))
+            .count();
+
+        if arrived + skipped < expected {
+            return None;
+        }
+
+        self.arrivals.remove(&node);
+        self.skipped.remove(&node);
+
+        if arrived == 0 {
+            return Some(false);
+        }
+
+        steps.push(ExecutionStep(node));
+        Some(true)
+    }
+}
+
+/// Returns the targets of every outgoing execution flow, whether taken or not.
+pub(super) fn execution_targets(
+    graph: &Graph,
+    node: NodeIndex,
+) -> impl Iterator<Item = NodeIndex> + '_ {
+    graph
+        .edges_directed(node, Direction::Outgoing)
+        .filter(|edge| is_execution_flow(edge.weight()))
+        .map(|edge| edge.target())
+}
+
+fn is_execution_flow(edge: &GraphEdge) -> bool {
+    matches!(
+        edge,
+        GraphEdge::ExecutionFlow | GraphEdge::ConditionalFlow(_)
+    )
+}
+
+fn is_join(graph: &Graph, node: NodeIndex) -> bool {
+    match graph.node_weight(node) {
+        Some(GraphNode::AsyncNode(node)) => node.is_join(),
+        Some(GraphNode::SyncNode(node)) => node.is_join(),
+        _ => false,
+    }
+}
@@ -1,21 +1,1271 @@
+mod checkpoint;
+mod joins;
+mod observer;
+mod plan;
+mod report;
 mod step;
+mod trace;
 
-use petgraph::graph::NodeIndex;
+use std::{
+    cell::RefCell,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+pub use checkpoint::Checkpoint;
+use futures_util::future::join_all;
+use joins::{execution_targets, Joins};
+pub use observer::ExecutionObserver;
+use petgraph::{graph::NodeIndex, Direction};
+pub use plan::{PlannedInput, PlannedStep};
+pub use report::{RunReport, ShutdownReport};
 pub use step::*;
+use tokio::sync::{broadcast, Notify, Semaphore};
+use tokio_util::sync::CancellationToken;
+pub use trace::{NodeTrace, Trace, TraceCollector};
+use tracing::{debug_span, warn, Instrument};
+
+use crate::{
+    detect_cycles, nodes::ItemError, validate, Graph, GraphEdge, GraphLabels, GraphNode,
+    GraphValidationError, Value,
+};
 
-use crate::Graph;
+/// Called with each store that changes, and its new value.
+type StoreObserver = Box<dyn Fn(NodeIndex, &Value)>;
 
-pub struct Executor;
+/// Nodes that are running, tracked for [`Executor::shutdown`].
+#[derive(Default)]
+struct ShutdownState {
+    running: Vec<NodeIndex>,
+    /// Nodes that finished after the shutdown started.
+    completed: Vec<NodeIndex>,
+}
+
+#[derive(Default)]
+pub struct Executor {
+    cancel: CancellationToken,
+    check_cycles: bool,
+    checkpoints: bool,
+    concurrency: Option<Semaphore>,
+    deadline: Option<Instant>,
+    draining: CancellationToken,
+    idle: Notify,
+    node_timeout: Option<Duration>,
+    observer: Option<Arc<dyn ExecutionObserver>>,
+    shutdown: RefCell<ShutdownState>,
+    store_observer: Option<StoreObserver>,
+    validate: bool,
+    watch: Option<broadcast::Sender<(NodeIndex, Value)>>,
+}
 
 impl Executor {
-    pub async fn execute(graph: &mut Graph, start: NodeIndex) -> Result<(), ExecutionStepError> {
-        let mut steps = vec![ExecutionStep(start)];
+    /// Executes the graph using the default executor.
+    pub async fn execute(
+        graph: &mut Graph,
+        start: NodeIndex,
+    ) -> Result<RunReport, ExecutionStepError> {
+        Self::default().run(graph, start).await
+    }
+
+    /// Stops execution once the deadline passes, returning
+    /// [`ExecutionStepError::DeadlineExceeded`].
+    ///
+    /// The deadline is checked before each node runs, and bounds the node while it runs.
+    /// Stores written before the deadline keep their values, so partial results
+    /// can still be read from the graph, and the error carries a [`RunReport`]
+    /// of the nodes that ran.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets a deadline of `timeout` from now.
+    /// See [`Executor::with_deadline`].
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Uses `token` to cancel execution, so it can be shared with other tasks,
+    /// or with nodes that support cancellation.
+    /// See [`Executor::cancel`].
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// The token used to cancel execution.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Cancels execution, returning [`ExecutionStepError::Cancelled`].
+    ///
+    /// Nodes that are running are dropped, and no further nodes are run.
+    /// Like with a deadline, stores that were already written keep their values.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Shuts down gracefully, waiting up to `grace` for running nodes to finish.
+    ///
+    /// No further nodes are started, and the execution returns
+    /// [`ExecutionStepError::Shutdown`] once the running nodes finish and their
+    /// outputs are written. Nodes still running after `grace` are cancelled,
+    /// and the execution returns [`ExecutionStepError::Cancelled`] instead.
+    /// Either way, steps that did not finish stay pending in the final
+    /// [`Checkpoint`], so the execution can be resumed.
+    ///
+    /// Ready nodes waiting for a permit from [`Executor::with_max_concurrency`]
+    /// are not started. Must be polled alongside the execution, such as with
+    /// [`tokio::join!`]. Like [`Executor::cancel`], the executor will not run
+    /// any further nodes.
+    pub async fn shutdown(&self, grace: Duration) -> ShutdownReport {
+        self.draining.cancel();
+
+        let drained = tokio::time::timeout(grace, async {
+            loop {
+                let idle = self.idle.notified();
+
+                if self.shutdown.borrow().running.is_empty() {
+                    break;
+                }
+
+                idle.await;
+            }
+        })
+        .await
+        .is_ok();
+
+        let mut state = self.shutdown.borrow_mut();
+
+        let aborted = if drained {
+            Vec::new()
+        } else {
+            self.cancel.cancel();
+            std::mem::take(&mut state.running)
+        };
+
+        ShutdownReport {
+            completed: std::mem::take(&mut state.completed),
+            aborted,
+        }
+    }
+
+    /// Checks the graph for execution cycles before running,
+    /// returning [`ExecutionStepError::InvalidGraph`] instead of running forever.
+    /// See [`detect_cycles`].
+    pub fn with_cycle_check(mut self) -> Self {
+        self.check_cycles = true;
+        self
+    }
+
+    /// Validates the graph before running, logging each warning.
+    /// See [`validate`].
+    pub fn with_validation(mut self) -> Self {
+        self.validate = true;
+        self
+    }
+
+    /// Limits how long each node may run, returning [`ExecutionStepError::Timeout`]
+    /// if exceeded. See [`ExecutionStep::execute_with`].
+    pub fn with_node_timeout(mut self, timeout: Duration) -> Self {
+        self.node_timeout = Some(timeout);
+        self
+    }
+
+    /// Passes a [`Checkpoint`] to the observer after each wave of steps,
+    /// see [`ExecutionObserver::checkpoint`].
+    pub fn with_checkpoints(mut self) -> Self {
+        self.checkpoints = true;
+        self
+    }
+
+    /// Limits how many nodes may run at the same time.
+    /// Ready nodes wait for a permit before running.
+    /// A limit of 1 runs nodes one at a time.
+    ///
+    /// Unbounded by default, or if `n` is [`usize::MAX`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn with_max_concurrency(mut self, n: usize) -> Self {
+        assert!(n > 0, "max concurrency must be greater than 0");
+        self.concurrency = (n < Semaphore::MAX_PERMITS).then(|| Semaphore::new(n));
+        self
+    }
+
+    /// Notifies the observer as each node runs.
+    pub fn with_observer(mut self, observer: Arc<dyn ExecutionObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Calls `observer` each time a store changes during execution,
+    /// whether written as a node output or updated from a [`GraphEdge::DataFlow`].
+    pub fn with_store_observer(mut self, observer: impl Fn(NodeIndex, &Value) + 'static) -> Self {
+        self.store_observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Broadcasts every store write during execution.
+    /// Each subscriber buffers up to `capacity` updates before lagging.
+    pub fn with_watch(mut self, capacity: usize) -> Self {
+        self.watch = Some(broadcast::channel(capacity).0);
+        self
+    }
+
+    /// Subscribes to store updates, if enabled with [`Executor::with_watch`].
+    pub fn subscribe(&self) -> Option<broadcast::Receiver<(NodeIndex, Value)>> {
+        self.watch.as_ref().map(|watch| watch.subscribe())
+    }
+
+    /// Runs the graph, following execution flows from `start`.
+    ///
+    /// If a node errors, execution continues along its [`GraphEdge::ErrorFlow`] edges.
+    /// Without any, the error is returned.
+    /// On success, returns a [`RunReport`] with how long each node ran.
+    ///
+    /// Steps run in waves: every step that is ready runs concurrently,
+    /// and the steps they lead to make up the next wave.
+    /// Each wave reads all of its inputs before any of its nodes run,
+    /// so nodes in the same wave never see each other's outputs.
+    /// Outputs are written once the whole wave finishes, in the order the steps were queued.
+    /// If a node errors, outputs of the steps queued before it are still written.
+    pub async fn run(
+        &self,
+        graph: &mut Graph,
+        start: NodeIndex,
+    ) -> Result<RunReport, ExecutionStepError> {
+        self.run_steps(graph, vec![start]).await
+    }
+
+    /// Runs the graph from every entry node, in index order.
+    /// Entry nodes are executable nodes without an incoming execution flow.
+    pub async fn run_all(&self, graph: &mut Graph) -> Result<RunReport, ExecutionStepError> {
+        let steps = entry_nodes(graph).collect();
+        self.run_steps(graph, steps).await
+    }
+
+    /// Continues an execution from a checkpoint, restoring its stores
+    /// and running its pending steps. Completed nodes are not run again,
+    /// unless reached by a pending step.
+    pub async fn resume(
+        &self,
+        graph: &mut Graph,
+        checkpoint: Checkpoint,
+    ) -> Result<RunReport, ExecutionStepError> {
+        checkpoint.restore_stores(graph)?;
+        self.run_from(graph, checkpoint).await
+    }
+
+    async fn run_steps(
+        &self,
+        graph: &mut Graph,
+        pending: Vec<NodeIndex>,
+    ) -> Result<RunReport, ExecutionStepError> {
+        let checkpoint = Checkpoint {
+            pending,
+            ..Default::default()
+        };
+
+        self.run_from(graph, checkpoint).await
+    }
+
+    async fn run_from(
+        &self,
+        graph: &mut Graph,
+        checkpoint: Checkpoint,
+    ) -> Result<RunReport, ExecutionStepError> {
+        if self.check_cycles {
+            detect_cycles(graph)?;
+        }
+
+        if self.validate {
+            for warning in validate(graph) {
+                warn!("{}", warning);
+            }
+        }
+
+        let start = Instant::now();
+        let mut report = RunReport::default();
+
+        let res = self.run_waves(graph, checkpoint, &mut report).await;
+
+        report.total = start.elapsed();
+
+        match res {
+            Ok(()) => Ok(report),
+            Err(error) => Err(error.with_report(report)),
+        }
+    }
+
+    /// Runs waves of steps until none are left, recording each step in `report`.
+    async fn run_waves(
+        &self,
+        graph: &mut Graph,
+        checkpoint: Checkpoint,
+        report: &mut RunReport,
+    ) -> Result<(), ExecutionStepError> {
+        let Checkpoint {
+            mut completed,
+            pending,
+            arrivals,
+            skipped,
+            ..
+        } = checkpoint;
+
+        let mut joins = Joins { arrivals, skipped };
+
+        let mut steps = pending.into_iter().map(ExecutionStep).collect::<Vec<_>>();
+
+        while !steps.is_empty() {
+            let wave = std::mem::take(&mut steps);
+
+            if self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return Err(
+                    ExecutionStepError::deadline_exceeded(wave[0].0).labeled(graph, wave[0].0)
+                );
+            }
+
+            if self.cancel.is_cancelled() {
+                return Err(ExecutionStepError::Cancelled(wave[0].0).labeled(graph, wave[0].0));
+            }
+
+            if self.draining.is_cancelled() {
+                return Err(ExecutionStepError::Shutdown(wave[0].0).labeled(graph, wave[0].0));
+            }
+
+            let inputs = wave
+                .iter()
+                .map(|step| {
+                    let res = match &self.store_observer {
+                        Some(observer) => step.read_inputs_with(graph, observer),
+                        None => step.read_inputs(graph),
+                    };
+                    res.map_err(|e| e.labeled(graph, step.0))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let shared: &Graph = graph;
+            let results = join_all(
+                wave.iter()
+                    .zip(inputs)
+                    .map(|(step, inputs)| self.run_step(shared, step, inputs)),
+            )
+            .await;
+
+            for (step, (res, duration)) in wave.iter().zip(results) {
+                // Steps stopped by a shutdown stay pending, and the outputs of the
+                // rest of the wave are still written.
+                if let Err(ExecutionStepError::Shutdown(_) | ExecutionStepError::Cancelled(_)) =
+                    &res
+                {
+                    if self.draining.is_cancelled() {
+                        steps.push(ExecutionStep(step.0));
+                        continue;
+                    }
+                }
+
+                *report.node_timings.entry(step.0).or_default() += duration;
+
+                match res {
+                    Ok(outputs) => {
+                        report.item_errors.extend(
+                            item_errors(graph, step.0, &outputs)
+                                .into_iter()
+                                .map(|error| (step.0, error)),
+                        );
+                        self.finish_step(graph, step, outputs, &mut steps, &mut joins)
+                    }
+                    // Error targets are queued directly, without waiting as joins.
+                    // The failed node's execution flows are not taken.
+                    Err(error) => {
+                        let message = error.to_string();
+                        steps.extend(step.catch(graph, error)?);
+                        joins.skip(graph, execution_targets(graph, step.0), &mut steps);
+                        report.errors.push((step.0, message));
+                    }
+                }
+
+                completed.insert(step.0);
+            }
+
+            if self.checkpoints {
+                if let Some(observer) = &self.observer {
+                    observer.checkpoint(&Checkpoint {
+                        completed: completed.clone(),
+                        pending: steps.iter().map(|step| step.0).collect(),
+                        arrivals: joins.arrivals.clone(),
+                        skipped: joins.skipped.clone(),
+                        stores: Checkpoint::stores(graph),
+                    });
+                }
+            }
+        }
 
-        while let Some(step) = steps.pop() {
-            let next_steps = step.execute(graph).await?;
-            steps.extend(next_steps);
+        // Joins still waiting on a flow would never run.
+        if let Some(join) = joins.arrivals.keys().next() {
+            return Err(ExecutionStepError::IncompleteJoin(*join).labeled(graph, *join));
         }
 
         Ok(())
     }
+
+    /// Writes the outputs of a step, queueing any steps that are now ready.
+    fn finish_step(
+        &self,
+        graph: &mut Graph,
+        step: &ExecutionStep,
+        outputs: Vec<Value>,
+        steps: &mut Vec<ExecutionStep>,
+        joins: &mut Joins,
+    ) {
+        let written = step.write_outputs(graph, outputs);
+
+        if let Some(observer) = &self.store_observer {
+            for store in &written {
+                if let GraphNode::Store(value) = &graph[*store] {
+                    observer(*store, value);
+                }
+            }
+        }
+
+        if let Some(watch) = &self.watch {
+            for store in written {
+                if let GraphNode::Store(value) = &graph[store] {
+                    // Sending only fails when there are no subscribers.
+                    let _ = watch.send((store, value.clone()));
+                }
+            }
+        }
+
+        for next in step.next_steps(graph) {
+            joins.arrive(graph, next.0, steps);
+        }
+
+        joins.skip(graph, step.untaken_targets(graph), steps);
+    }
+
+    /// Runs the node of a step, notifying the observer.
+    /// Returns the result with how long the node ran.
+    async fn run_step(
+        &self,
+        graph: &Graph,
+        step: &ExecutionStep,
+        inputs: Vec<Value>,
+    ) -> (Result<Vec<Value>, ExecutionStepError>, Duration) {
+        let _permit = match &self.concurrency {
+            // The semaphore is never closed.
+            Some(semaphore) => Some(semaphore.acquire().await.expect("semaphore closed")),
+            None => None,
+        };
+
+        if self.draining.is_cancelled() {
+            return (Err(ExecutionStepError::Shutdown(step.0)), Duration::ZERO);
+        }
+
+        self.shutdown.borrow_mut().running.push(step.0);
+
+        // The inputs are kept for the observer, as the node consumes them.
+        let observed_inputs = self.observer.as_ref().map(|observer| {
+            observer.node_started(step.0, &inputs);
+            inputs.clone()
+        });
+
+        let start = Instant::now();
+
+        let run = async {
+            match self.node_timeout {
+                Some(timeout) => step.run_with_timeout(graph, inputs, timeout).await,
+                None => step.run(graph, inputs).await,
+            }
+        };
+
+        let run = async {
+            match self.deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline.into(), run)
+                    .await
+                    .unwrap_or(Err(ExecutionStepError::deadline_exceeded(step.0))),
+                None => run.await,
+            }
+        };
+
+        // Events from inside the node are recorded with its index and label.
+        let span = debug_span!("node", index = step.0.index(), label = graph.label(step.0));
+
+        // On cancellation the node is dropped, cancelling it.
+        let res = tokio::select! {
+            res = run.instrument(span) => res,
+            _ = self.cancel.cancelled() => Err(ExecutionStepError::Cancelled(step.0)),
+        };
+
+        let duration = start.elapsed();
+
+        {
+            let mut state = self.shutdown.borrow_mut();
+
+            if let Some(i) = state.running.iter().position(|node| *node == step.0) {
+                state.running.swap_remove(i);
+            }
+
+            if self.draining.is_cancelled() && !matches!(res, Err(ExecutionStepError::Cancelled(_)))
+            {
+                state.completed.push(step.0);
+            }
+
+            if state.running.is_empty() {
+                self.idle.notify_waiters();
+            }
+        }
+
+        if let (Some(observer), Some(inputs)) = (&self.observer, observed_inputs) {
+            match &res {
+                Ok(outputs) => observer.node_finished(step.0, &inputs, outputs, duration),
+                Err(error) => observer.node_failed(step.0, &inputs, error, duration),
+            }
+        }
+
+        (res, duration)
+    }
+
+    /// Returns the nodes that would be executed from `start`, in a topological
+    /// order, along with the stores feeding each input. No nodes are run.
+    ///
+    /// Assumes every execution flow is taken, including conditional flows.
+    /// Error flows are not followed.
+    /// Cyclic graphs cannot be planned, including loops through conditional flows,
+    /// and return [`GraphValidationError::ExecutionCycle`].
+    pub fn plan(graph: &Graph, start: NodeIndex) -> Result<Vec<PlannedStep>, GraphValidationError> {
+        plan::plan(graph, start)
+    }
+}
+
+/// Returns executable nodes without an incoming execution flow.
+fn entry_nodes(graph: &Graph) -> impl Iterator<Item = NodeIndex> + '_ {
+    graph.node_indices().filter(|node| {
+        !matches!(graph[*node], GraphNode::Store(_) | GraphNode::Constant(_))
+            && !graph
+                .edges_directed(*node, Direction::Incoming)
+                .any(|edge| {
+                    matches!(
+                        edge.weight(),
+                        GraphEdge::ExecutionFlow
+                            | GraphEdge::ConditionalFlow(_)
+                            | GraphEdge::ErrorFlow
+                    )
+                })
+    })
+}
+
+/// Messages of the errors a node output, see
+/// [`AsyncNode::item_errors_output`](crate::nodes::AsyncNode::item_errors_output).
+fn item_errors(graph: &Graph, node: NodeIndex, outputs: &[Value]) -> Vec<String> {
+    let index = match graph.node_weight(node) {
+        Some(GraphNode::AsyncNode(node)) => node.item_errors_output(),
+        Some(GraphNode::SyncNode(node)) => node.item_errors_output(),
+        _ => None,
+    };
+
+    match index.and_then(|index| outputs.get(index)) {
+        Some(Value::Vec(errors)) => errors
+            .iter()
+            .map(|error| match ItemError::try_from(error.clone()) {
+                Ok(error) => error.to_string(),
+                Err(_) => error.to_string(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        future::Future,
+        rc::Rc,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use crate::nodes::{
+        AsyncNode, BranchNode, CallbackNode, DelayNode, JoinNode, LogNode, NodeError, NodeWrapper,
+        SyncNode,
+    };
+
+    use super::*;
+
+    struct TestSleep(Duration);
+
+    impl AsyncNode for TestSleep {
+        fn run(
+            &self,
+            inputs: Vec<Value>,
+        ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+            let duration = self.0;
+
+            Box::new(Box::pin(async move {
+                tokio::time::sleep(duration).await;
+                Ok(inputs)
+            }))
+        }
+    }
+
+    struct TestFail;
+
+    impl SyncNode for TestFail {
+        fn run(&self, _inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+            Err(NodeError::InternalError("failed".to_string()))
+        }
+    }
+
+    /// Tracks the peak number of nodes running at once.
+    #[derive(Default)]
+    struct Running {
+        current: AtomicUsize,
+        peak: AtomicUsize,
+        total: AtomicUsize,
+    }
+
+    struct TestRunning(Arc<Running>);
+
+    impl AsyncNode for TestRunning {
+        fn run(
+            &self,
+            inputs: Vec<Value>,
+        ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+            let running = self.0.clone();
+
+            Box::new(Box::pin(async move {
+                let current = running.current.fetch_add(1, Ordering::SeqCst) + 1;
+                running.peak.fetch_max(current, Ordering::SeqCst);
+
+                tokio::time::sleep(Duration::from_millis(1)).await;
+
+                running.current.fetch_sub(1, Ordering::SeqCst);
+                running.total.fetch_add(1, Ordering::SeqCst);
+                Ok(inputs)
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrency() {
+        let mut graph = Graph::default();
+        let running = Arc::new(Running::default());
+
+        let root = LogNode::new(&mut graph);
+
+        for _ in 0..100 {
+            let node = graph.add_node(GraphNode::AsyncNode(Box::new(TestRunning(running.clone()))));
+            graph.add_edge(root.0, node, GraphEdge::ExecutionFlow);
+        }
+
+        Executor::default()
+            .with_max_concurrency(4)
+            .run(&mut graph, root.0)
+            .await
+            .unwrap();
+
+        // More nodes are ready than the limit, so it is reached but not exceeded.
+        assert_eq!(running.total.load(Ordering::SeqCst), 100);
+        assert_eq!(running.peak.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_max_concurrency_timing() {
+        async fn run(max_concurrency: usize) -> Duration {
+            let mut graph = Graph::default();
+            let root = LogNode::new(&mut graph);
+
+            for _ in 0..4 {
+                let delay = DelayNode::new(&mut graph, Duration::from_millis(50));
+                graph.add_edge(root.0, delay.0, GraphEdge::ExecutionFlow);
+            }
+
+            // The clock is paused, so only the delays advance it.
+            let start = tokio::time::Instant::now();
+
+            Executor::default()
+                .with_max_concurrency(max_concurrency)
+                .run(&mut graph, root.0)
+                .await
+                .unwrap();
+
+            start.elapsed()
+        }
+
+        // Two waves of two delays.
+        assert_eq!(run(2).await, Duration::from_millis(100));
+
+        // One delay at a time.
+        assert_eq!(run(1).await, Duration::from_millis(200));
+
+        // Every delay at once.
+        assert_eq!(run(usize::MAX).await, Duration::from_millis(50));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_parallel_branches() {
+        let mut graph = Graph::default();
+
+        let root = LogNode::new(&mut graph);
+
+        for _ in 0..2 {
+            let sleep = graph.add_node(GraphNode::AsyncNode(Box::new(TestSleep(
+                Duration::from_millis(200),
+            ))));
+            graph.add_edge(root.0, sleep, GraphEdge::ExecutionFlow);
+        }
+
+        // The clock is paused, so only the sleeps advance it.
+        let start = tokio::time::Instant::now();
+        Executor::execute(&mut graph, root.0).await.unwrap();
+
+        // Both branches sleep at the same time.
+        assert_eq!(start.elapsed(), Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_deadline() {
+        let mut graph = Graph::default();
+
+        let callback = CallbackNode::new(&mut graph, |_| "done".to_string().into());
+        let output = callback.output(&graph).unwrap();
+
+        let sleep = graph.add_node(GraphNode::AsyncNode(Box::new(TestSleep(
+            Duration::from_secs(10),
+        ))));
+        graph.add_edge(callback.0, sleep, GraphEdge::ExecutionFlow);
+
+        let res = Executor::default()
+            .with_timeout(Duration::from_millis(50))
+            .run(&mut graph, callback.0)
+            .await;
+
+        let Err(ExecutionStepError::DeadlineExceeded { node, report }) = res else {
+            panic!("{:?}", res);
+        };
+        assert_eq!(node, sleep);
+
+        // The report covers the nodes that ran before the deadline.
+        assert!(report.node_timings.contains_key(&callback.0));
+        assert!(report.total >= Duration::from_millis(50));
+
+        // Results written before the deadline are kept.
+        match &graph[output.0] {
+            GraphNode::Store(value) => assert_eq!(value, &Value::String("done".to_string())),
+            _ => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_node_timeout() {
+        let mut graph = Graph::default();
+
+        let log = LogNode::new(&mut graph);
+        let sleep = graph.add_node(GraphNode::AsyncNode(Box::new(TestSleep(
+            Duration::from_secs(10),
+        ))));
+        graph.add_edge(log.0, sleep, GraphEdge::ExecutionFlow);
+
+        let res = Executor::default()
+            .with_node_timeout(Duration::from_millis(10))
+            .run(&mut graph, log.0)
+            .await;
+
+        assert!(matches!(res, Err(ExecutionStepError::Timeout(node)) if node == sleep));
+    }
+
+    #[tokio::test]
+    async fn test_run_report() {
+        let mut graph = Graph::default();
+
+        let log = LogNode::new(&mut graph);
+        let sleep = graph.add_node(GraphNode::AsyncNode(Box::new(TestSleep(
+            Duration::from_millis(50),
+        ))));
+        graph.add_edge(log.0, sleep, GraphEdge::ExecutionFlow);
+
+        let fail = graph.add_node(GraphNode::SyncNode(Box::new(TestFail)));
+        graph.add_edge(sleep, fail, GraphEdge::ExecutionFlow);
+        let handler = LogNode::new(&mut graph);
+        handler.run_on_error(&mut graph, fail);
+
+        let report = Executor::default().run(&mut graph, log.0).await.unwrap();
+
+        assert_eq!(report.node_timings.len(), 4);
+        assert_eq!(report.slowest().map(|(node, _)| node), Some(sleep));
+        assert!(report.node_timings[&sleep] >= Duration::from_millis(50));
+        assert!(report.total >= report.node_timings[&sleep]);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, fail);
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let mut graph = Graph::default();
+
+        let callback = CallbackNode::new(&mut graph, |_| "done".to_string().into());
+        let output = callback.output(&graph).unwrap();
+
+        let sleep = graph.add_node(GraphNode::AsyncNode(Box::new(TestSleep(
+            Duration::from_secs(10),
+        ))));
+        graph.add_edge(callback.0, sleep, GraphEdge::ExecutionFlow);
+
+        let executor = Executor::default();
+        let token = executor.cancellation_token();
+
+        let (res, _) = tokio::join!(executor.run(&mut graph, callback.0), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            token.cancel();
+        });
+
+        assert!(matches!(res, Err(ExecutionStepError::Cancelled(node)) if node == sleep));
+
+        match &graph[output.0] {
+            GraphNode::Store(value) => assert_eq!(value, &Value::String("done".to_string())),
+            _ => panic!(),
+        }
+
+        // A cancelled executor does not run any further nodes.
+        let res = executor.run(&mut graph, callback.0).await;
+        assert!(matches!(res, Err(ExecutionStepError::Cancelled(node)) if node == callback.0));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let mut graph = Graph::default();
+        let order = Rc::default();
+
+        let log = LogNode::new(&mut graph);
+
+        let fast = graph.add_node(GraphNode::AsyncNode(Box::new(TestSleep(
+            Duration::from_millis(50),
+        ))));
+        graph.add_edge(log.0, fast, GraphEdge::ExecutionFlow);
+        let after_fast = named(&mut graph, &order, "after_fast");
+        graph.add_edge(fast, after_fast, GraphEdge::ExecutionFlow);
+
+        let slow = graph.add_node(GraphNode::AsyncNode(Box::new(TestSleep(
+            Duration::from_secs(10),
+        ))));
+        graph.add_edge(log.0, slow, GraphEdge::ExecutionFlow);
+
+        let executor = Executor::default();
+        let start = Instant::now();
+
+        let (res, report) = tokio::join!(executor.run(&mut graph, log.0), async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            executor.shutdown(Duration::from_millis(100)).await
+        });
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // The fast node drains, then the slow node is cancelled.
+        assert_eq!(report.completed, vec![fast]);
+        assert_eq!(report.aborted, vec![slow]);
+        assert!(matches!(res, Err(ExecutionStepError::Cancelled(_))));
+
+        // No new steps are scheduled.
+        assert!(order.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drained() {
+        let mut graph = Graph::default();
+
+        let log = LogNode::new(&mut graph);
+
+        let sleeps = (0..2)
+            .map(|_| {
+                let sleep = graph.add_node(GraphNode::AsyncNode(Box::new(TestSleep(
+                    Duration::from_millis(50),
+                ))));
+                graph.add_edge(log.0, sleep, GraphEdge::ExecutionFlow);
+                sleep
+            })
+            .collect::<Vec<_>>();
+
+        let executor = Executor::default().with_max_concurrency(1);
+
+        let (res, report) = tokio::join!(executor.run(&mut graph, log.0), async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            executor.shutdown(Duration::from_secs(1)).await
+        });
+
+        // One sleep finishes, and the one waiting for a permit never starts.
+        assert_eq!(report.completed.len(), 1);
+        assert!(sleeps.contains(&report.completed[0]));
+        assert!(report.aborted.is_empty());
+        assert!(matches!(
+            res,
+            Err(ExecutionStepError::Shutdown(node)) if sleeps.contains(&node) && node != report.completed[0]
+        ));
+
+        let res = executor.run(&mut graph, log.0).await;
+        assert!(matches!(res, Err(ExecutionStepError::Shutdown(node)) if node == log.0));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_passed() {
+        let mut graph = Graph::default();
+        let log = LogNode::new(&mut graph);
+
+        let res = Executor::default()
+            .with_deadline(Instant::now())
+            .run(&mut graph, log.0)
+            .await;
+
+        assert!(
+            matches!(res, Err(ExecutionStepError::DeadlineExceeded { node, report })
+                if node == log.0 && report.node_timings.is_empty())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch() {
+        let mut graph = Graph::default();
+
+        let callback = CallbackNode::new(&mut graph, |input| input);
+        let input = callback.input(&graph).unwrap();
+        input.set_value(&mut graph, "Hello, world!".to_string().into());
+        let output = callback.output(&graph).unwrap();
+
+        let executor = Executor::default().with_watch(16);
+        let mut rx = executor.subscribe().unwrap();
+
+        executor.run(&mut graph, callback.0).await.unwrap();
+
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            (output.0, Value::String("Hello, world!".to_string()))
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_watch_disabled() {
+        assert!(Executor::default().subscribe().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_labeled_error() {
+        let mut graph = Graph::default();
+
+        // Log nodes fail without an input.
+        let log = LogNode::new(&mut graph);
+        let message = log.message(&graph).unwrap();
+        graph.remove_node(message.0);
+
+        graph.set_label(log.0, "summarizer");
+
+        let error = Executor::execute(&mut graph, log.0).await.unwrap_err();
+
+        assert!(matches!(
+            &error,
+            ExecutionStepError::Labeled { label, source }
+                if label == "summarizer" && matches!(**source, ExecutionStepError::NodeError(_))
+        ));
+        assert!(error.to_string().starts_with("Node `summarizer` failed"));
+    }
+
+    #[tokio::test]
+    async fn test_store_observer() {
+        let mut graph = Graph::default();
+
+        let a = CallbackNode::new(&mut graph, |input| input);
+        let a_input = a.input(&graph).unwrap();
+        a_input.set_value(&mut graph, "Hello, world!".to_string().into());
+        let a_output = a.output(&graph).unwrap();
+
+        let b = CallbackNode::new(&mut graph, |input| input);
+        let b_input = b.input(&graph).unwrap();
+        b_input.set_input(&mut graph, Some(a_output));
+        let b_output = b.output(&graph).unwrap();
+
+        graph.add_edge(a.0, b.0, GraphEdge::ExecutionFlow);
+
+        let updates = Rc::new(RefCell::new(Vec::new()));
+        let updates_clone = updates.clone();
+
+        let executor = Executor::default().with_store_observer(move |store, value| {
+            updates_clone.borrow_mut().push((store, value.clone()));
+        });
+
+        executor.run(&mut graph, a.0).await.unwrap();
+
+        let value = Value::String("Hello, world!".to_string());
+        assert_eq!(
+            *updates.borrow(),
+            vec![
+                (a_output.0, value.clone()),
+                (b_input.0, value.clone()),
+                (b_output.0, value),
+            ]
+        );
+    }
+
+    /// Creates a node that records its name when run.
+    fn named(
+        graph: &mut Graph,
+        order: &Rc<RefCell<Vec<&'static str>>>,
+        name: &'static str,
+    ) -> NodeIndex {
+        let order = order.clone();
+
+        CallbackNode::new(graph, move |input| {
+            order.borrow_mut().push(name);
+            input
+        })
+        .0
+    }
+
+    #[tokio::test]
+    async fn test_run_all_chain() {
+        let mut graph = Graph::default();
+        let order = Rc::default();
+
+        let a = named(&mut graph, &order, "a");
+        let b = named(&mut graph, &order, "b");
+        let c = named(&mut graph, &order, "c");
+        graph.add_edge(a, b, GraphEdge::ExecutionFlow);
+        graph.add_edge(b, c, GraphEdge::ExecutionFlow);
+
+        Executor::default().run_all(&mut graph).await.unwrap();
+
+        assert_eq!(*order.borrow(), vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_run_all_diamond() {
+        let mut graph = Graph::default();
+        let order = Rc::default();
+
+        let a = named(&mut graph, &order, "a");
+        let b = named(&mut graph, &order, "b");
+        let c = named(&mut graph, &order, "c");
+        graph.add_edge(a, b, GraphEdge::ExecutionFlow);
+        graph.add_edge(a, c, GraphEdge::ExecutionFlow);
+
+        let join = JoinNode::new(&mut graph, 0);
+        join.run_after(&mut graph, b);
+        join.run_after(&mut graph, c);
+
+        let d = named(&mut graph, &order, "d");
+        graph.add_edge(join.0, d, GraphEdge::ExecutionFlow);
+
+        Executor::default().run_all(&mut graph).await.unwrap();
+
+        let order = order.borrow();
+        assert_eq!(order.len(), 4);
+        assert_eq!(order[0], "a");
+        assert_eq!(order[3], "d");
+    }
+
+    #[tokio::test]
+    async fn test_run_all_entries() {
+        let mut graph = Graph::default();
+        let order = Rc::default();
+
+        named(&mut graph, &order, "a");
+        let b = named(&mut graph, &order, "b");
+        let c = named(&mut graph, &order, "c");
+        graph.add_edge(b, c, GraphEdge::ExecutionFlow);
+
+        Executor::default().run_all(&mut graph).await.unwrap();
+
+        assert_eq!(*order.borrow(), vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_cycle_check() {
+        let mut graph = Graph::default();
+
+        let a = LogNode::new(&mut graph);
+        let b = LogNode::new(&mut graph);
+        b.run_after(&mut graph, a.0);
+        a.run_after(&mut graph, b.0);
+
+        let res = Executor::default()
+            .with_cycle_check()
+            .run(&mut graph, a.0)
+            .await;
+
+        assert!(matches!(res, Err(ExecutionStepError::InvalidGraph(_))));
+    }
+
+    #[test]
+    fn test_plan_chain() {
+        let mut graph = Graph::default();
+
+        let a = LogNode::new(&mut graph);
+        let b = LogNode::new(&mut graph);
+        let c = LogNode::new(&mut graph);
+        b.run_after(&mut graph, a.0);
+        c.run_after(&mut graph, b.0);
+
+        let plan = Executor::plan(&graph, a.0).unwrap();
+        let order = plan.iter().map(|step| step.node).collect::<Vec<_>>();
+        assert_eq!(order, vec![a.0, b.0, c.0]);
+    }
+
+    #[test]
+    fn test_plan_cycle() {
+        let mut graph = Graph::default();
+
+        let a = LogNode::new(&mut graph);
+        let b = LogNode::new(&mut graph);
+        b.run_after(&mut graph, a.0);
+        a.run_after(&mut graph, b.0);
+
+        assert!(matches!(
+            Executor::plan(&graph, a.0),
+            Err(GraphValidationError::ExecutionCycle(nodes)) if nodes == vec![a.0, b.0]
+        ));
+    }
+
+    #[test]
+    fn test_plan_diamond() {
+        let mut graph = Graph::default();
+
+        let a = LogNode::new(&mut graph);
+        let b = CallbackNode::new(&mut graph, |input| input);
+        let c = LogNode::new(&mut graph);
+        let d = LogNode::new(&mut graph);
+        b.run_after(&mut graph, a.0);
+        c.run_after(&mut graph, a.0);
+        d.run_after(&mut graph, b.0);
+        d.run_after(&mut graph, c.0);
+
+        let output = b.output(&graph).unwrap();
+        let message = d.message(&graph).unwrap();
+        message.set_input(&mut graph, Some(output));
+        graph.set_label(d.0, "log");
+
+        let plan = Executor::plan(&graph, a.0).unwrap();
+        let order = plan.iter().map(|step| step.node).collect::<Vec<_>>();
+        assert_eq!(order.len(), 4);
+
+        // Every node is planned after the nodes it runs after.
+        let position = |node: NodeIndex| order.iter().position(|n| *n == node).unwrap();
+        assert_eq!(position(a.0), 0);
+        assert!(position(b.0) < position(d.0));
+        assert!(position(c.0) < position(d.0));
+        assert_eq!(position(d.0), 3);
+
+        let last = &plan[3];
+        assert_eq!(last.label.as_deref(), Some("log"));
+        assert_eq!(
+            last.inputs,
+            vec![PlannedInput {
+                index: 0,
+                store: message.0,
+                source: Some(output.0),
+            }]
+        );
+        assert_eq!(plan[0].inputs[0].source, None);
+    }
+
+    #[tokio::test]
+    async fn test_join_after_caught_error() {
+        let mut graph = Graph::default();
+        let order = Rc::default();
+
+        let start = named(&mut graph, &order, "start");
+        let fail = graph.add_node(GraphNode::SyncNode(Box::new(TestFail)));
+        let ok = named(&mut graph, &order, "ok");
+        graph.add_edge(start, fail, GraphEdge::ExecutionFlow);
+        graph.add_edge(start, ok, GraphEdge::ExecutionFlow);
+
+        // The failed branch passes through another node before the join.
+        let after_fail = named(&mut graph, &order, "after_fail");
+        graph.add_edge(fail, after_fail, GraphEdge::ExecutionFlow);
+        let handler = named(&mut graph, &order, "handler");
+        graph.add_edge(fail, handler, GraphEdge::ErrorFlow);
+
+        let join = JoinNode::new(&mut graph, 0);
+        join.run_after(&mut graph, after_fail);
+        join.run_after(&mut graph, ok);
+
+        let end = named(&mut graph, &order, "end");
+        graph.add_edge(join.0, end, GraphEdge::ExecutionFlow);
+
+        Executor::execute(&mut graph, start).await.unwrap();
+
+        let order = order.borrow();
+        assert!(order.contains(&"handler"));
+        assert!(!order.contains(&"after_fail"));
+        assert_eq!(order.last(), Some(&"end"));
+    }
+
+    #[tokio::test]
+    async fn test_join_after_branch() {
+        async fn run(value: bool) -> Vec<&'static str> {
+            let mut graph = Graph::default();
+            let order = Rc::default();
+
+            let then = named(&mut graph, &order, "then");
+            let otherwise = named(&mut graph, &order, "otherwise");
+            let branch = BranchNode::new(&mut graph, Some(then), Some(otherwise));
+            let input = branch.input(&graph).unwrap();
+            input.set_value(&mut graph, Value::Bool(value));
+
+            let join = JoinNode::new(&mut graph, 0);
+            join.run_after(&mut graph, then);
+            join.run_after(&mut graph, otherwise);
+
+            let end = named(&mut graph, &order, "end");
+            graph.add_edge(join.0, end, GraphEdge::ExecutionFlow);
+
+            Executor::execute(&mut graph, branch.0).await.unwrap();
+
+            let order = order.borrow().clone();
+            order
+        }
+
+        assert_eq!(run(true).await, ["then", "end"]);
+        assert_eq!(run(false).await, ["otherwise", "end"]);
+    }
+
+    #[tokio::test]
+    async fn test_incomplete_join() {
+        let mut graph = Graph::default();
+        let order = Rc::default();
+
+        let a = named(&mut graph, &order, "a");
+        let unreached = named(&mut graph, &order, "unreached");
+
+        let join = JoinNode::new(&mut graph, 0);
+        join.run_after(&mut graph, a);
+        join.run_after(&mut graph, unreached);
+
+        let res = Executor::execute(&mut graph, a).await;
+        assert!(matches!(res, Err(ExecutionStepError::IncompleteJoin(node)) if node == join.0));
+    }
+
+    #[tokio::test]
+    async fn test_error_flow() {
+        let mut graph = Graph::default();
+        let order = Rc::default();
+
+        let fail = graph.add_node(GraphNode::SyncNode(Box::new(TestFail)));
+        let next = named(&mut graph, &order, "next");
+        let fallback = named(&mut graph, &order, "fallback");
+        let after = named(&mut graph, &order, "after");
+        graph.add_edge(fail, next, GraphEdge::ExecutionFlow);
+        graph.add_edge(fail, fallback, GraphEdge::ErrorFlow);
+        graph.add_edge(fallback, after, GraphEdge::ExecutionFlow);
+
+        Executor::execute(&mut graph, fail).await.unwrap();
+
+        assert_eq!(*order.borrow(), vec!["fallback", "after"]);
+
+        // Nodes only reached by an error flow are not entry nodes.
+        assert_eq!(entry_nodes(&graph).collect::<Vec<_>>(), vec![fail]);
+    }
 }
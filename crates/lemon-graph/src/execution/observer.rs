@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use petgraph::graph::NodeIndex;
+
+use crate::Value;
+
+use super::{Checkpoint, ExecutionStepError};
+
+/// Receives events from the [`Executor`](super::Executor) as nodes run.
+pub trait ExecutionObserver {
+    /// Called before a node runs, with its inputs.
+    fn node_started(&self, _node: NodeIndex, _inputs: &[Value]) {}
+
+    /// Called after a node runs successfully, with the inputs it ran with and its outputs.
+    ///
+    /// The same node may run more than once at a time, so each call is given
+    /// the inputs of that run.
+    fn node_finished(
+        &self,
+        _node: NodeIndex,
+        _inputs: &[Value],
+        _outputs: &[Value],
+        _duration: Duration,
+    ) {
+    }
+
+    /// Called after a node fails, with the inputs it ran with.
+    /// The error is still returned from the executor.
+    fn node_failed(
+        &self,
+        _node: NodeIndex,
+        _inputs: &[Value],
+        _error: &ExecutionStepError,
+        _duration: Duration,
+    ) {
+    }
+
+    /// Called after each wave of steps finishes, if enabled with
+    /// [`Executor::with_checkpoints`](super::Executor::with_checkpoints).
+    fn checkpoint(&self, _checkpoint: &Checkpoint) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        nodes::{CallbackNode, LogNode, NodeWrapper},
+        Executor, Graph,
+    };
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Started(NodeIndex, Vec<Value>),
+        Finished(NodeIndex, Vec<Value>),
+        Failed(NodeIndex),
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<Event>>);
+
+    impl ExecutionObserver for Recorder {
+        fn node_started(&self, node: NodeIndex, inputs: &[Value]) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(Event::Started(node, inputs.to_vec()));
+        }
+
+        fn node_finished(
+            &self,
+            node: NodeIndex,
+            _inputs: &[Value],
+            outputs: &[Value],
+            _duration: Duration,
+        ) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(Event::Finished(node, outputs.to_vec()));
+        }
+
+        fn node_failed(
+            &self,
+            node: NodeIndex,
+            _inputs: &[Value],
+            _error: &ExecutionStepError,
+            _duration: Duration,
+        ) {
+            self.0.lock().unwrap().push(Event::Failed(node));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_observer_events() {
+        let mut graph = Graph::default();
+
+        let upper = CallbackNode::new(&mut graph, |input| {
+            Value::String(input.to_string().to_uppercase())
+        });
+        let input = upper.input(&graph).unwrap();
+        input.set_value(&mut graph, "hi".to_string().into());
+
+        let log = LogNode::new(&mut graph);
+        log.run_after(&mut graph, upper.0);
+        let message = log.message(&graph).unwrap();
+        let output = upper.output(&graph).unwrap();
+        message.set_input(&mut graph, Some(output));
+
+        let recorder = Arc::new(Recorder::default());
+
+        Executor::default()
+            .with_observer(recorder.clone())
+            .run(&mut graph, upper.0)
+            .await
+            .unwrap();
+
+        let hi = Value::String("hi".to_string());
+        let upper_hi = Value::String("HI".to_string());
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                Event::Started(upper.0, vec![hi]),
+                Event::Finished(upper.0, vec![upper_hi.clone()]),
+                Event::Started(log.0, vec![upper_hi]),
+                Event::Finished(log.0, Vec::new()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_observer_error() {
+        let mut graph = Graph::default();
+
+        // Log nodes fail without an input.
+        let log = LogNode::new(&mut graph);
+        let message = log.message(&graph).unwrap();
+        graph.remove_node(message.0);
+
+        let recorder = Arc::new(Recorder::default());
+
+        let res = Executor::default()
+            .with_observer(recorder.clone())
+            .run(&mut graph, log.0)
+            .await;
+
+        assert!(res.is_err());
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![Event::Started(log.0, Vec::new()), Event::Failed(log.0)]
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction};
+
+use crate::{Graph, GraphEdge, GraphLabels, GraphValidationError};
+
+use super::step::upstream_store;
+
+/// A node that would be run, returned by [`Executor::plan`](super::Executor::plan).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedStep {
+    pub node: NodeIndex,
+    /// The node's label, see [`GraphLabels`].
+    pub label: Option<String>,
+    /// Stores mapped to the node's inputs, ordered by data index.
+    pub inputs: Vec<PlannedInput>,
+}
+
+/// A store mapped to an input of a [`PlannedStep`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedInput {
+    /// Data index of the input.
+    pub index: usize,
+    /// The input store.
+    pub store: NodeIndex,
+    /// The store its value is read from, following [`GraphEdge::DataFlow`] edges.
+    /// `None` if nothing flows into the input store.
+    pub source: Option<NodeIndex>,
+}
+
+pub(super) fn plan(
+    graph: &Graph,
+    start: NodeIndex,
+) -> Result<Vec<PlannedStep>, GraphValidationError> {
+    let next = |node: NodeIndex| {
+        graph
+            .edges_directed(node, Direction::Outgoing)
+            .filter(|edge| {
+                matches!(
+                    edge.weight(),
+                    GraphEdge::ExecutionFlow | GraphEdge::ConditionalFlow(_)
+                )
+            })
+            .map(|edge| edge.target())
+    };
+
+    // Find every reachable node, in the order it is first reached.
+    let mut reachable = vec![start];
+    let mut visited = HashSet::from([start]);
+    let mut i = 0;
+
+    while let Some(&node) = reachable.get(i) {
+        reachable.extend(next(node).filter(|target| visited.insert(*target)));
+        i += 1;
+    }
+
+    let mut incoming = HashMap::<NodeIndex, usize>::new();
+
+    for node in &reachable {
+        for target in next(*node) {
+            *incoming.entry(target).or_default() += 1;
+        }
+    }
+
+    let mut queue = reachable
+        .iter()
+        .copied()
+        .filter(|node| !incoming.contains_key(node))
+        .collect::<VecDeque<_>>();
+    let mut order = Vec::with_capacity(reachable.len());
+
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+
+        for target in next(node) {
+            let count = incoming.get_mut(&target).expect("target is reachable");
+            *count -= 1;
+
+            if *count == 0 {
+                queue.push_back(target);
+            }
+        }
+    }
+
+    if order.len() < reachable.len() {
+        let mut cycle = incoming
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(node, _)| node)
+            .collect::<Vec<_>>();
+        cycle.sort();
+
+        return Err(GraphValidationError::ExecutionCycle(cycle));
+    }
+
+    Ok(order
+        .into_iter()
+        .map(|node| PlannedStep {
+            node,
+            label: graph.label(node).map(str::to_string),
+            inputs: planned_inputs(graph, node),
+        })
+        .collect())
+}
+
+fn planned_inputs(graph: &Graph, node: NodeIndex) -> Vec<PlannedInput> {
+    let mut inputs = graph
+        .edges_directed(node, Direction::Incoming)
+        .filter_map(|edge| match edge.weight() {
+            GraphEdge::DataMap(index) => Some(PlannedInput {
+                index: *index,
+                store: edge.source(),
+                source: upstream_store(graph, edge.source()).ok().flatten(),
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    inputs.sort_by_key(|input| input.index);
+    inputs
+}
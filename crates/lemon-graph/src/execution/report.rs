@@ -0,0 +1,46 @@
+use std::{collections::HashMap, time::Duration};
+
+use petgraph::graph::NodeIndex;
+
+/// Summary of an execution, returned by [`Executor::run`](super::Executor::run).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunReport {
+    /// Time spent running each node.
+    /// Nodes that ran multiple times, such as in a loop, hold the sum of every run.
+    pub node_timings: HashMap<NodeIndex, Duration>,
+    /// Wall-clock time of the whole execution.
+    pub total: Duration,
+    /// Errors caught by [`GraphEdge::ErrorFlow`](crate::GraphEdge::ErrorFlow) edges,
+    /// in the order they happened.
+    /// Uncaught errors stop execution and are returned instead.
+    pub errors: Vec<(NodeIndex, String)>,
+    /// Errors nodes recovered from without failing, such as failed items of a
+    /// [`ForEachNode`](crate::nodes::ForEachNode) that continues on error.
+    pub item_errors: Vec<(NodeIndex, String)>,
+}
+
+impl RunReport {
+    /// Whether any node completed with partial results, see [`RunReport::item_errors`].
+    pub fn is_partial(&self) -> bool {
+        !self.item_errors.is_empty()
+    }
+
+    /// The node that took the longest to run, if any ran.
+    pub fn slowest(&self) -> Option<(NodeIndex, Duration)> {
+        self.node_timings
+            .iter()
+            .max_by_key(|(_, duration)| **duration)
+            .map(|(node, duration)| (*node, *duration))
+    }
+}
+
+/// Nodes that were running when the executor shut down,
+/// returned by [`Executor::shutdown`](super::Executor::shutdown).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Nodes that finished within the grace period, in the order they finished.
+    /// Their outputs are written to the graph.
+    pub completed: Vec<NodeIndex>,
+    /// Nodes that were still running once the grace period ended, and were cancelled.
+    pub aborted: Vec<NodeIndex>,
+}
@@ -1,7 +1,14 @@
+use std::{collections::HashSet, time::Duration};
+
 use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction};
 use thiserror::Error;
+use tracing::warn;
+
+use crate::{
+    nodes::NodeError, Graph, GraphEdge, GraphLabels, GraphNode, GraphValidationError, Value,
+};
 
-use crate::{nodes::NodeError, Graph, GraphEdge, GraphNode};
+use super::RunReport;
 
 pub struct ExecutionStep(pub NodeIndex);
 
@@ -11,16 +18,180 @@ pub enum ExecutionStepError {
     NoWeight,
     #[error("Invalid weight")]
     InvalidWeight,
+    /// The executor's deadline passed before or while the node ran.
+    /// See [`Executor::with_deadline`](super::Executor::with_deadline).
+    #[error("Deadline exceeded at node {node:?}")]
+    DeadlineExceeded {
+        node: NodeIndex,
+        /// Report of the execution up to the deadline.
+        report: Box<RunReport>,
+    },
+    #[error("Node {0:?} timed out")]
+    Timeout(NodeIndex),
+    #[error("Cancelled at node {0:?}")]
+    Cancelled(NodeIndex),
+    /// The executor was shut down before the node could run.
+    /// See [`Executor::shutdown`](super::Executor::shutdown).
+    #[error("Shut down before node {0:?}")]
+    Shutdown(NodeIndex),
+    /// Execution finished while a join node was waiting on an incoming flow,
+    /// such as from a node that was never run.
+    #[error("Join node {0:?} never received all of its incoming flows")]
+    IncompleteJoin(NodeIndex),
     #[error(transparent)]
     NodeError(#[from] NodeError),
+    #[error(transparent)]
+    InvalidGraph(#[from] GraphValidationError),
+    /// An error from a node with a label, see [`GraphLabels`](crate::GraphLabels).
+    #[error("Node `{label}` failed: {source}")]
+    Labeled {
+        label: String,
+        source: Box<ExecutionStepError>,
+    },
+}
+
+impl ExecutionStepError {
+    /// A [`ExecutionStepError::DeadlineExceeded`] error with an empty report.
+    pub(crate) fn deadline_exceeded(node: NodeIndex) -> Self {
+        Self::DeadlineExceeded {
+            node,
+            report: Box::default(),
+        }
+    }
+
+    /// Sets the report of a [`ExecutionStepError::DeadlineExceeded`] error,
+    /// including one wrapped in [`ExecutionStepError::Labeled`].
+    pub(crate) fn with_report(self, report: RunReport) -> Self {
+        match self {
+            Self::DeadlineExceeded { node, .. } => Self::DeadlineExceeded {
+                node,
+                report: Box::new(report),
+            },
+            Self::Labeled { label, source } => Self::Labeled {
+                label,
+                source: Box::new(source.with_report(report)),
+            },
+            error => error,
+        }
+    }
+
+    /// Wraps the error in [`ExecutionStepError::Labeled`], if the node has a label.
+    pub fn labeled(self, graph: &Graph, node: NodeIndex) -> Self {
+        match graph.label(node) {
+            Some(label) => Self::Labeled {
+                label: label.to_string(),
+                source: Box::new(self),
+            },
+            None => self,
+        }
+    }
 }
 
 impl ExecutionStep {
+    /// Runs the node, returning the steps to execute after it.
+    ///
+    /// If the node errors, the error is routed along any [`GraphEdge::ErrorFlow`] edges.
+    /// See [`ExecutionStep::catch`].
     pub async fn execute<'a>(
         &self,
         graph: &'a mut Graph,
     ) -> Result<impl Iterator<Item = ExecutionStep> + 'a, ExecutionStepError> {
-        // Read inputs
+        let inputs = self.read_inputs(graph)?;
+        let res = self.run(graph, inputs).await;
+        self.finish(graph, res)
+    }
+
+    /// Like [`ExecutionStep::execute`], but returns [`ExecutionStepError::Timeout`]
+    /// if the node runs for longer than `timeout`.
+    ///
+    /// Only async nodes can time out, as sync nodes run to completion once started.
+    pub async fn execute_with<'a>(
+        &self,
+        graph: &'a mut Graph,
+        timeout: Duration,
+    ) -> Result<impl Iterator<Item = ExecutionStep> + 'a, ExecutionStepError> {
+        let inputs = self.read_inputs(graph)?;
+        let res = self.run_with_timeout(graph, inputs, timeout).await;
+        self.finish(graph, res)
+    }
+
+    fn finish(
+        &self,
+        graph: &mut Graph,
+        res: Result<Vec<Value>, ExecutionStepError>,
+    ) -> Result<std::vec::IntoIter<ExecutionStep>, ExecutionStepError> {
+        let steps = match res {
+            Ok(outputs) => {
+                self.write_outputs(graph, outputs);
+                self.next_steps(graph).collect::<Vec<_>>()
+            }
+            Err(error) => self.catch(graph, error)?,
+        };
+
+        Ok(steps.into_iter())
+    }
+
+    /// Routes an error from running the node along its [`GraphEdge::ErrorFlow`] edges,
+    /// writing the error message to the first input store of each target.
+    /// Returns the error if the node has no error flows,
+    /// or if the error is [`ExecutionStepError::DeadlineExceeded`] or [`ExecutionStepError::Cancelled`].
+    /// Returned errors are [labeled](ExecutionStepError::labeled) with the node's label.
+    pub(crate) fn catch(
+        &self,
+        graph: &mut Graph,
+        error: ExecutionStepError,
+    ) -> Result<Vec<ExecutionStep>, ExecutionStepError> {
+        if let ExecutionStepError::DeadlineExceeded { .. }
+        | ExecutionStepError::Cancelled(_)
+        | ExecutionStepError::Shutdown(_) = error
+        {
+            return Err(error.labeled(graph, self.0));
+        }
+
+        let targets = graph
+            .edges_directed(self.0, Direction::Outgoing)
+            .filter(|edge| matches!(edge.weight(), GraphEdge::ErrorFlow))
+            .map(|edge| edge.target())
+            .collect::<Vec<_>>();
+
+        if targets.is_empty() {
+            return Err(error.labeled(graph, self.0));
+        }
+
+        let message = Value::String(error.to_string());
+
+        for target in &targets {
+            let store = graph
+                .edges_directed(*target, Direction::Incoming)
+                .find(|edge| matches!(edge.weight(), GraphEdge::DataMap(0)))
+                .map(|edge| edge.source());
+
+            if let Some(store) = store {
+                if let GraphNode::Store(value) = &mut graph[store] {
+                    *value = message.clone();
+                }
+            }
+        }
+
+        Ok(targets.into_iter().map(ExecutionStep).collect())
+    }
+
+    /// Reads the node's inputs, sorted by data index.
+    ///
+    /// An input store with an incoming [`GraphEdge::DataFlow`] is first updated
+    /// to the value at the start of its chain of data flows, see [`upstream_value`].
+    /// Only the node's own input stores are updated.
+    pub(crate) fn read_inputs(&self, graph: &mut Graph) -> Result<Vec<Value>, ExecutionStepError> {
+        self.read_inputs_with(graph, |_, _| {})
+    }
+
+    /// Like [`ExecutionStep::read_inputs`], calling `on_update` for each
+    /// input store updated from a [`GraphEdge::DataFlow`].
+    pub(crate) fn read_inputs_with(
+        &self,
+        graph: &mut Graph,
+        mut on_update: impl FnMut(NodeIndex, &Value),
+    ) -> Result<Vec<Value>, ExecutionStepError> {
         let inputs = graph
             .edges_directed(self.0, Direction::Incoming)
             .filter_map(|edge| match edge.weight() {
@@ -32,28 +203,13 @@ impl ExecutionStep {
         let mut inputs = inputs
             .into_iter()
             .map(|(data_idx, source_idx)| -> Result<_, ExecutionStepError> {
-                // Update source from incoming DataFlow edges.
-                let mut new_value = None;
-
-                for edge in graph.edges_directed(source_idx, Direction::Incoming) {
-                    if let GraphEdge::DataFlow = edge.weight() {
-                        let source = edge.source();
-
-                        let source_weight = graph
-                            .node_weight(source)
-                            .ok_or(ExecutionStepError::NoWeight)?;
-
-                        let value = match source_weight {
-                            GraphNode::Store(value) => value,
-                            _ => return Err(ExecutionStepError::InvalidWeight),
-                        };
-
-                        new_value = Some(value.clone());
-                    }
+                if let Some(GraphNode::Constant(value)) = graph.node_weight(source_idx) {
+                    return Ok((data_idx, value.clone()));
                 }
 
-                if let Some(value) = new_value {
+                if let Some(value) = upstream_value(graph, source_idx)? {
                     graph[source_idx] = GraphNode::Store(value.clone());
+                    on_update(source_idx, &value);
                     return Ok((data_idx, value));
                 }
 
@@ -70,9 +226,45 @@ impl ExecutionStep {
 
         inputs.sort_by_key(|(idx, _)| *idx);
 
-        let inputs = inputs.into_iter().map(|(_, value)| value).collect();
+        let (allows_gaps, null_missing) = match graph.node_weight(self.0) {
+            Some(GraphNode::AsyncNode(node)) => {
+                (node.allows_input_gaps(), node.null_inputs_missing())
+            }
+            Some(GraphNode::SyncNode(node)) => {
+                (node.allows_input_gaps(), node.null_inputs_missing())
+            }
+            _ => (false, false),
+        };
 
-        // Execute node
+        if null_missing {
+            if let Some((idx, _)) = inputs.iter().find(|(_, value)| value.is_null()) {
+                return Err(NodeError::MissingInput(*idx).into());
+            }
+        }
+
+        if allows_gaps {
+            return Ok(inputs.into_iter().map(|(_, value)| value).collect());
+        }
+
+        // Inputs are passed by position, so a gap in the data indices
+        // would shift every following input.
+        if let Some(missing) = inputs
+            .iter()
+            .enumerate()
+            .find_map(|(i, (idx, _))| (*idx != i).then_some(i))
+        {
+            return Err(NodeError::MissingInput(missing).into());
+        }
+
+        Ok(inputs.into_iter().map(|(_, value)| value).collect())
+    }
+
+    /// Runs the node with the given inputs.
+    pub(crate) async fn run(
+        &self,
+        graph: &Graph,
+        inputs: Vec<Value>,
+    ) -> Result<Vec<Value>, ExecutionStepError> {
         let node = graph
             .node_weight(self.0)
             .ok_or(ExecutionStepError::NoWeight)?;
@@ -83,8 +275,25 @@ impl ExecutionStep {
             _ => return Err(ExecutionStepError::InvalidWeight),
         };
 
-        // Write outputs
-        let outputs = graph
+        Ok(res)
+    }
+
+    /// Runs the node, returning [`ExecutionStepError::Timeout`] if it takes longer than `timeout`.
+    pub(crate) async fn run_with_timeout(
+        &self,
+        graph: &Graph,
+        inputs: Vec<Value>,
+        timeout: Duration,
+    ) -> Result<Vec<Value>, ExecutionStepError> {
+        tokio::time::timeout(timeout, self.run(graph, inputs))
+            .await
+            .unwrap_or(Err(ExecutionStepError::Timeout(self.0)))
+    }
+
+    /// Writes the node's outputs to its output stores.
+    /// Returns the indices of the stores that were written to.
+    pub(crate) fn write_outputs(&self, graph: &mut Graph, outputs: Vec<Value>) -> Vec<NodeIndex> {
+        let stores = graph
             .edges_directed(self.0, Direction::Outgoing)
             .filter_map(|edge| match edge.weight() {
                 GraphEdge::DataMap(data_idx) => Some((edge.target(), *data_idx)),
@@ -92,30 +301,125 @@ impl ExecutionStep {
             })
             .collect::<Vec<_>>();
 
-        for (i, value) in res.into_iter().enumerate() {
-            let (store_idx, _) = match outputs.iter().find(|(_, idx)| *idx == i) {
+        let mut written = Vec::new();
+
+        for (i, value) in outputs.into_iter().enumerate() {
+            let (store_idx, _) = match stores.iter().find(|(_, idx)| *idx == i) {
                 Some(output) => output,
                 None => continue,
             };
 
+            if let GraphNode::Constant(_) = graph[*store_idx] {
+                warn!("Ignoring output {} written to constant {:?}", i, store_idx);
+                continue;
+            }
+
             graph[*store_idx] = GraphNode::Store(value);
+            written.push(*store_idx);
         }
 
-        // Get next steps
-        Ok(graph
-            .edges_directed(self.0, Direction::Outgoing)
-            .filter_map(|edge| match edge.weight() {
+        written
+    }
+
+    /// Returns the steps to execute after this one.
+    pub(crate) fn next_steps<'a>(
+        &self,
+        graph: &'a Graph,
+    ) -> impl Iterator<Item = ExecutionStep> + 'a {
+        let node = self.0;
+
+        graph
+            .edges_directed(node, Direction::Outgoing)
+            .filter_map(move |edge| match edge.weight() {
                 GraphEdge::ExecutionFlow => Some(ExecutionStep(edge.target())),
+                GraphEdge::ConditionalFlow(condition) if condition.evaluate_output(graph, node) => {
+                    Some(ExecutionStep(edge.target()))
+                }
                 _ => None,
-            }))
+            })
+    }
+
+    /// Returns the targets of conditional flows that are not taken after this step.
+    pub(crate) fn untaken_targets<'a>(
+        &self,
+        graph: &'a Graph,
+    ) -> impl Iterator<Item = NodeIndex> + 'a {
+        let node = self.0;
+
+        graph
+            .edges_directed(node, Direction::Outgoing)
+            .filter_map(move |edge| match edge.weight() {
+                GraphEdge::ConditionalFlow(condition)
+                    if !condition.evaluate_output(graph, node) =>
+                {
+                    Some(edge.target())
+                }
+                _ => None,
+            })
+    }
+}
+
+/// Follows incoming [`GraphEdge::DataFlow`] edges from a store to the store
+/// they start from, returning its value.
+/// Returns `None` if the store has no incoming data flow.
+///
+/// Intermediate stores are read, but not updated.
+fn upstream_value(graph: &Graph, store: NodeIndex) -> Result<Option<Value>, ExecutionStepError> {
+    Ok(upstream_store(graph, store)?.and_then(|upstream| graph[upstream].value().cloned()))
+}
+
+/// Follows incoming [`GraphEdge::DataFlow`] edges from a store to the store
+/// they start from.
+/// Returns `None` if the store has no incoming data flow.
+///
+/// A store should have at most one incoming data flow. If it has more, only one is followed.
+/// The chain ends at a [`GraphNode::Constant`], as data flows into it are ignored,
+/// or when it loops back to a store already visited.
+pub(crate) fn upstream_store(
+    graph: &Graph,
+    store: NodeIndex,
+) -> Result<Option<NodeIndex>, ExecutionStepError> {
+    let mut visited = HashSet::from([store]);
+    let mut current = store;
+
+    loop {
+        let upstream = graph
+            .edges_directed(current, Direction::Incoming)
+            .filter(|edge| matches!(edge.weight(), GraphEdge::DataFlow))
+            .map(|edge| edge.source())
+            .last();
+
+        let Some(upstream) = upstream else {
+            break;
+        };
+
+        let weight = graph
+            .node_weight(upstream)
+            .ok_or(ExecutionStepError::NoWeight)?;
+
+        match weight {
+            GraphNode::Store(_) if visited.insert(upstream) => current = upstream,
+            GraphNode::Store(_) => break,
+            GraphNode::Constant(_) => {
+                current = upstream;
+                break;
+            }
+            _ => return Err(ExecutionStepError::InvalidWeight),
+        }
+    }
+
+    if current == store {
+        return Ok(None);
     }
+
+    Ok(Some(current))
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         nodes::{AsyncNode, SyncNode},
-        Value,
+        GraphBuilder,
     };
 
     use super::*;
@@ -128,6 +432,28 @@ mod tests {
         }
     }
 
+    struct TestConcat;
+
+    impl SyncNode for TestConcat {
+        fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+            let out = inputs
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+
+            Ok(vec![Value::String(out)])
+        }
+    }
+
+    struct TestFail;
+
+    impl SyncNode for TestFail {
+        fn run(&self, _inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+            Err(NodeError::InternalError("failed".to_string()))
+        }
+    }
+
     struct TestAsync;
 
     impl AsyncNode for TestAsync {
@@ -139,21 +465,218 @@ mod tests {
         }
     }
 
+    struct TestSleep(Duration);
+
+    impl AsyncNode for TestSleep {
+        fn run(
+            &self,
+            inputs: Vec<Value>,
+        ) -> Box<dyn std::future::Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+            let duration = self.0;
+
+            Box::new(Box::pin(async move {
+                tokio::time::sleep(duration).await;
+                Ok(inputs)
+            }))
+        }
+    }
+
     #[tokio::test]
-    async fn test_sync_execution() {
+    async fn test_timeout() {
         let mut graph = Graph::default();
 
-        let input = graph.add_node(GraphNode::Store(Value::String("Hello, world!".to_string())));
-        let node = graph.add_node(GraphNode::SyncNode(Box::new(TestSync)));
+        let node = graph.add_node(GraphNode::AsyncNode(Box::new(TestSleep(
+            Duration::from_secs(10),
+        ))));
+
+        let res = ExecutionStep(node)
+            .execute_with(&mut graph, Duration::from_millis(10))
+            .await
+            .map(|steps| steps.count());
+
+        assert!(matches!(res, Err(ExecutionStepError::Timeout(n)) if n == node));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_in_time() {
+        let mut graph = Graph::default();
+
+        let input = graph.add_node(GraphNode::Store(Value::String("done".to_string())));
+        let node = graph.add_node(GraphNode::AsyncNode(Box::new(TestSleep(
+            Duration::from_millis(1),
+        ))));
         let output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
         graph.add_edge(input, node, GraphEdge::DataMap(0));
         graph.add_edge(node, output, GraphEdge::DataMap(0));
 
+        let next_steps = ExecutionStep(node)
+            .execute_with(&mut graph, Duration::from_secs(10))
+            .await
+            .unwrap()
+            .count();
+        assert_eq!(next_steps, 0);
+
+        match &graph[output] {
+            GraphNode::Store(value) => assert_eq!(value, &Value::String("done".to_string())),
+            _ => panic!(),
+        }
+    }
+
+    fn store_value(graph: &Graph, store: NodeIndex) -> Value {
+        graph[store].value().unwrap().clone()
+    }
+
+    #[test]
+    fn test_data_flow_chain() {
+        let mut graph = Graph::default();
+
+        let a = graph.add_node(GraphNode::Store(Value::String("a".to_string())));
+        let b = graph.add_node(GraphNode::Store(Value::String("b".to_string())));
+        let c = graph.add_node(GraphNode::Store(Value::String("c".to_string())));
+        graph.add_edge(a, b, GraphEdge::DataFlow);
+        graph.add_edge(b, c, GraphEdge::DataFlow);
+
+        let node = graph.add_node(GraphNode::SyncNode(Box::new(TestSync)));
+        graph.add_edge(c, node, GraphEdge::DataMap(0));
+
+        let inputs = ExecutionStep(node).read_inputs(&mut graph).unwrap();
+        assert_eq!(inputs, vec![Value::String("a".to_string())]);
+
+        // Only the node's own input is updated.
+        assert_eq!(store_value(&graph, c), Value::String("a".to_string()));
+        assert_eq!(store_value(&graph, b), Value::String("b".to_string()));
+        assert_eq!(store_value(&graph, a), Value::String("a".to_string()));
+    }
+
+    #[test]
+    fn test_data_flow_unrelated() {
+        let mut graph = Graph::default();
+
+        let source = graph.add_node(GraphNode::Store(Value::String("new".to_string())));
+        let input = graph.add_node(GraphNode::Store(Value::String("old".to_string())));
+        graph.add_edge(source, input, GraphEdge::DataFlow);
+
+        // Another store reading from the same source, but not an input of the node.
+        let other = graph.add_node(GraphNode::Store(Value::String("other".to_string())));
+        graph.add_edge(source, other, GraphEdge::DataFlow);
+
+        let node = graph.add_node(GraphNode::SyncNode(Box::new(TestSync)));
+        graph.add_edge(input, node, GraphEdge::DataMap(0));
+
+        ExecutionStep(node).read_inputs(&mut graph).unwrap();
+
+        assert_eq!(store_value(&graph, input), Value::String("new".to_string()));
+        assert_eq!(
+            store_value(&graph, other),
+            Value::String("other".to_string())
+        );
+        assert_eq!(
+            store_value(&graph, source),
+            Value::String("new".to_string())
+        );
+    }
+
+    #[test]
+    fn test_data_flow_loop() {
+        let mut graph = Graph::default();
+
+        let a = graph.add_node(GraphNode::Store(Value::String("a".to_string())));
+        let b = graph.add_node(GraphNode::Store(Value::String("b".to_string())));
+        graph.add_edge(a, b, GraphEdge::DataFlow);
+        graph.add_edge(b, a, GraphEdge::DataFlow);
+
+        let node = graph.add_node(GraphNode::SyncNode(Box::new(TestSync)));
+        graph.add_edge(b, node, GraphEdge::DataMap(0));
+
+        let inputs = ExecutionStep(node).read_inputs(&mut graph).unwrap();
+        assert_eq!(inputs, vec![Value::String("a".to_string())]);
+    }
+
+    /// Outputs its inputs, treating null inputs as missing.
+    struct TestRequired;
+
+    impl SyncNode for TestRequired {
+        fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+            Ok(inputs)
+        }
+
+        fn null_inputs_missing(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_null_input() {
+        let mut graph = Graph::default();
+
+        let a = graph.add_node(GraphNode::Store(Value::String("a".to_string())));
+        let b = graph.add_node(GraphNode::Store(Value::Null));
+
+        // Nodes receive null inputs by default, such as for optional inputs.
+        let optional = graph.add_node(GraphNode::SyncNode(Box::new(TestSync)));
+        graph.add_edge(a, optional, GraphEdge::DataMap(0));
+        graph.add_edge(b, optional, GraphEdge::DataMap(1));
+
+        let inputs = ExecutionStep(optional).read_inputs(&mut graph).unwrap();
+        assert_eq!(inputs, vec![Value::String("a".to_string()), Value::Null]);
+
+        let required = graph.add_node(GraphNode::SyncNode(Box::new(TestRequired)));
+        graph.add_edge(a, required, GraphEdge::DataMap(0));
+        graph.add_edge(b, required, GraphEdge::DataMap(1));
+
+        assert!(matches!(
+            ExecutionStep(required).read_inputs(&mut graph),
+            Err(ExecutionStepError::NodeError(NodeError::MissingInput(1)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_constant() {
+        let mut builder = GraphBuilder::default();
+
+        let seed = builder.constant(Value::String("seed".to_string()));
+        let node = builder.sync_node(TestSync);
+        builder
+            .connect_data(seed, node, 0)
+            .connect_data(node, seed, 0);
+
+        let other = builder.store(Value::String("other".to_string()));
+        builder.connect_stores(other, seed);
+
+        let mut graph = builder.build().unwrap();
+
+        for _ in 0..2 {
+            ExecutionStep(node)
+                .execute(&mut graph)
+                .await
+                .unwrap()
+                .for_each(drop);
+        }
+
+        match &graph[seed.0] {
+            GraphNode::Constant(value) => assert_eq!(value, &Value::String("seed".to_string())),
+            _ => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_execution() {
+        let mut builder = GraphBuilder::default();
+
+        let input = builder.store(Value::String("Hello, world!".to_string()));
+        let node = builder.sync_node(TestSync);
+        let output = builder.store(Value::String(Default::default()));
+        builder
+            .connect_data(input, node, 0)
+            .connect_data(node, output, 0);
+
+        let mut graph = builder.build().unwrap();
+
         let step = ExecutionStep(node);
         let next_steps = step.execute(&mut graph).await.unwrap().collect::<Vec<_>>();
         assert!(next_steps.is_empty());
 
-        let output_value = graph.node_weight(output).unwrap();
+        let output_value = graph.node_weight(output.0).unwrap();
         let output_value = match output_value {
             GraphNode::Store(value) => value,
             _ => panic!(),
@@ -182,4 +705,90 @@ mod tests {
         };
         assert_eq!(output_value, &Value::String("Hello, world!".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_multi_input_order() {
+        let mut graph = Graph::default();
+
+        let first = graph.add_node(GraphNode::Store(Value::String("a".to_string())));
+        let second = graph.add_node(GraphNode::Store(Value::String("b".to_string())));
+        let node = graph.add_node(GraphNode::SyncNode(Box::new(TestConcat)));
+        let output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+
+        // Add edges out of order.
+        graph.add_edge(second, node, GraphEdge::DataMap(1));
+        graph.add_edge(first, node, GraphEdge::DataMap(0));
+        graph.add_edge(node, output, GraphEdge::DataMap(0));
+
+        let next_steps = ExecutionStep(node).execute(&mut graph).await.unwrap();
+        assert_eq!(next_steps.count(), 0);
+
+        match &graph[output] {
+            GraphNode::Store(value) => assert_eq!(value, &Value::String("a,b".to_string())),
+            _ => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multi_input_gap() {
+        let mut graph = Graph::default();
+
+        let first = graph.add_node(GraphNode::Store(Value::String("a".to_string())));
+        let third = graph.add_node(GraphNode::Store(Value::String("c".to_string())));
+        let node = graph.add_node(GraphNode::SyncNode(Box::new(TestConcat)));
+        graph.add_edge(first, node, GraphEdge::DataMap(0));
+        graph.add_edge(third, node, GraphEdge::DataMap(2));
+
+        let res = ExecutionStep(node).execute(&mut graph).await;
+
+        assert!(matches!(
+            res,
+            Err(ExecutionStepError::NodeError(NodeError::MissingInput(1)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_error_flow() {
+        let mut graph = Graph::default();
+
+        let node = graph.add_node(GraphNode::SyncNode(Box::new(TestFail)));
+        let fallback = graph.add_node(GraphNode::SyncNode(Box::new(TestSync)));
+        let message = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(message, fallback, GraphEdge::DataMap(0));
+        graph.add_edge(node, fallback, GraphEdge::ErrorFlow);
+
+        let next_steps = ExecutionStep(node)
+            .execute(&mut graph)
+            .await
+            .unwrap()
+            .map(|step| step.0)
+            .collect::<Vec<_>>();
+        assert_eq!(next_steps, vec![fallback]);
+
+        match &graph[message] {
+            GraphNode::Store(value) => {
+                assert_eq!(value, &Value::String("Internal error: failed".to_string()))
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_error_without_flow() {
+        let mut graph = Graph::default();
+
+        let node = graph.add_node(GraphNode::SyncNode(Box::new(TestFail)));
+        let next = graph.add_node(GraphNode::SyncNode(Box::new(TestSync)));
+        graph.add_edge(node, next, GraphEdge::ExecutionFlow);
+
+        let res = ExecutionStep(node)
+            .execute(&mut graph)
+            .await
+            .map(|steps| steps.count());
+
+        assert!(matches!(
+            res,
+            Err(ExecutionStepError::NodeError(NodeError::InternalError(_)))
+        ));
+    }
 }
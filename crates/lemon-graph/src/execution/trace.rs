@@ -0,0 +1,191 @@
+use std::{sync::Mutex, time::Duration};
+
+use petgraph::graph::NodeIndex;
+
+use crate::Value;
+
+use super::{ExecutionObserver, ExecutionStepError};
+
+/// A record of every node run during an execution.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trace {
+    /// Node runs, in the order they finished.
+    pub nodes: Vec<NodeTrace>,
+}
+
+/// A single node run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeTrace {
+    pub node: NodeIndex,
+    pub inputs: Vec<Value>,
+    /// Empty if the node failed.
+    pub outputs: Vec<Value>,
+    pub duration: Duration,
+    pub error: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl Trace {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Observer that records a [`Trace`] of every node run.
+#[derive(Debug, Default)]
+pub struct TraceCollector {
+    trace: Mutex<Trace>,
+}
+
+impl TraceCollector {
+    /// Returns the trace recorded so far.
+    pub fn trace(&self) -> Trace {
+        self.trace.lock().unwrap().clone()
+    }
+
+    fn record(
+        &self,
+        node: NodeIndex,
+        inputs: Vec<Value>,
+        outputs: Vec<Value>,
+        duration: Duration,
+        error: Option<String>,
+    ) {
+        self.trace.lock().unwrap().nodes.push(NodeTrace {
+            node,
+            inputs,
+            outputs,
+            duration,
+            error,
+        });
+    }
+}
+
+impl ExecutionObserver for TraceCollector {
+    fn node_finished(
+        &self,
+        node: NodeIndex,
+        inputs: &[Value],
+        outputs: &[Value],
+        duration: Duration,
+    ) {
+        self.record(node, inputs.to_vec(), outputs.to_vec(), duration, None);
+    }
+
+    fn node_failed(
+        &self,
+        node: NodeIndex,
+        inputs: &[Value],
+        error: &ExecutionStepError,
+        duration: Duration,
+    ) {
+        self.record(
+            node,
+            inputs.to_vec(),
+            Vec::new(),
+            duration,
+            Some(error.to_string()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        nodes::{CallbackNode, LogNode},
+        Executor, Graph, GraphEdge,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_trace() {
+        let mut graph = Graph::default();
+
+        let upper = CallbackNode::new(&mut graph, |input| {
+            Value::String(input.to_string().to_uppercase())
+        });
+        let input = upper.input(&graph).unwrap();
+        input.set_value(&mut graph, "hello".to_string().into());
+
+        let collector = Arc::new(TraceCollector::default());
+
+        Executor::default()
+            .with_observer(collector.clone())
+            .run(&mut graph, upper.0)
+            .await
+            .unwrap();
+
+        let trace = collector.trace();
+        assert_eq!(trace.nodes.len(), 1);
+
+        let node = &trace.nodes[0];
+        assert_eq!(node.node, upper.0);
+        assert_eq!(node.inputs, vec!["hello".to_string().into()]);
+        assert_eq!(node.outputs, vec!["HELLO".to_string().into()]);
+        assert!(node.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_trace_repeated_node() {
+        let mut graph = Graph::default();
+
+        let callback = CallbackNode::new(&mut graph, |input| input);
+
+        // Log nodes fail without an input, and both errors are routed to the callback,
+        // which runs twice in the same wave.
+        for _ in 0..2 {
+            let log = LogNode::new(&mut graph);
+            let message = log.message(&graph).unwrap();
+            graph.remove_node(message.0);
+            graph.add_edge(log.0, callback.0, GraphEdge::ErrorFlow);
+        }
+
+        let collector = Arc::new(TraceCollector::default());
+
+        Executor::default()
+            .with_observer(collector.clone())
+            .run_all(&mut graph)
+            .await
+            .unwrap();
+
+        let runs = collector
+            .trace()
+            .nodes
+            .into_iter()
+            .filter(|trace| trace.node == callback.0)
+            .collect::<Vec<_>>();
+
+        assert_eq!(runs.len(), 2);
+
+        for run in runs {
+            assert_eq!(run.inputs.len(), 1);
+            assert_eq!(run.inputs, run.outputs);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_trace_json() {
+        let trace = Trace {
+            nodes: vec![NodeTrace {
+                node: NodeIndex::new(3),
+                inputs: vec![Value::String("a".to_string())],
+                outputs: vec![Value::USize(1)],
+                duration: Duration::from_millis(5),
+                error: None,
+            }],
+        };
+
+        let json = trace.to_json().unwrap();
+        assert_eq!(Trace::from_json(&json).unwrap(), trace);
+    }
+}
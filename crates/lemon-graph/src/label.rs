@@ -0,0 +1,101 @@
+use petgraph::graph::NodeIndex;
+
+use crate::Graph;
+
+/// Names for nodes, used in errors, tracing spans, and DOT output
+/// instead of their index.
+///
+/// Labels are stored alongside the graph's nodes, so they are never executed
+/// and do not change the graph's nodes or edges.
+pub trait GraphLabels {
+    /// Sets the label of a node, replacing any existing label.
+    fn set_label(&mut self, node: NodeIndex, label: impl Into<String>);
+
+    /// Returns the label of a node.
+    fn label(&self, node: NodeIndex) -> Option<&str>;
+
+    /// Returns the node with the given label.
+    /// If multiple nodes share a label, any one of them may be returned.
+    fn node_by_label(&self, label: &str) -> Option<NodeIndex>;
+}
+
+impl GraphLabels for Graph {
+    fn set_label(&mut self, node: NodeIndex, label: impl Into<String>) {
+        self.labels.insert(node, label.into());
+    }
+
+    fn label(&self, node: NodeIndex) -> Option<&str> {
+        self.labels.get(&node).map(String::as_str)
+    }
+
+    fn node_by_label(&self, label: &str) -> Option<NodeIndex> {
+        self.labels
+            .iter()
+            .find(|(_, l)| *l == label)
+            .map(|(node, _)| *node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{nodes::LogNode, GraphNode, Value};
+
+    use super::*;
+
+    #[test]
+    fn test_labels() {
+        let mut graph = Graph::default();
+        let a = LogNode::new(&mut graph);
+        let b = LogNode::new(&mut graph);
+
+        assert_eq!(graph.label(a.0), None);
+        assert_eq!(graph.node_by_label("summarizer"), None);
+
+        graph.set_label(a.0, "summarizer");
+        graph.set_label(b.0, "logger");
+
+        assert_eq!(graph.label(a.0), Some("summarizer"));
+        assert_eq!(graph.node_by_label("summarizer"), Some(a.0));
+        assert_eq!(graph.node_by_label("logger"), Some(b.0));
+
+        let node_count = graph.node_count();
+        graph.set_label(a.0, "renamed");
+
+        assert_eq!(graph.node_count(), node_count);
+        assert_eq!(graph.label(a.0), Some("renamed"));
+        assert_eq!(graph.node_by_label("renamed"), Some(a.0));
+        assert_eq!(graph.node_by_label("summarizer"), None);
+    }
+
+    #[test]
+    fn test_labels_not_nodes() {
+        let mut graph = Graph::default();
+        let log = LogNode::new(&mut graph);
+
+        let node_count = graph.node_count();
+        let edge_count = graph.edge_count();
+        graph.set_label(log.0, "logger");
+
+        assert_eq!(graph.node_count(), node_count);
+        assert_eq!(graph.edge_count(), edge_count);
+    }
+
+    #[test]
+    fn test_remove_labeled_node() {
+        let mut graph = Graph::default();
+        let a = graph.add_node(GraphNode::Store(Value::Null));
+        let b = graph.add_node(GraphNode::Store(Value::Null));
+        let c = graph.add_node(GraphNode::Store(Value::Null));
+
+        graph.set_label(a, "a");
+        graph.set_label(c, "c");
+
+        // The last node, `c`, takes the index of `a`.
+        graph.remove_node(a);
+
+        assert_eq!(graph.label(a), Some("c"));
+        assert_eq!(graph.label(b), None);
+        assert_eq!(graph.label(c), None);
+        assert_eq!(graph.node_by_label("a"), None);
+    }
+}
@@ -30,21 +30,50 @@
 //! }
 //! ```
 
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+};
+
 use nodes::{AsyncNode, SyncNode};
-use petgraph::graph::DiGraph;
+use petgraph::graph::{DiGraph, NodeIndex};
 
+mod builder;
+mod condition;
+mod dot;
 mod execution;
+mod label;
 pub mod nodes;
+#[cfg(feature = "serde")]
+mod serialize;
+mod validate;
 mod value;
 
+pub use builder::{GraphBuildError, GraphBuilder};
+pub use condition::Condition;
+pub use dot::to_dot;
 pub use execution::*;
-pub use value::Value;
+pub use label::GraphLabels;
+#[cfg(feature = "serde")]
+pub use serialize::{
+    to_json, DeserializeGraphError, NodeRegistry, SerializedGraph, SerializedNode,
+};
+pub use validate::{detect_cycles, validate, GraphValidationError, ValidationWarning};
+pub use value::{Value, ValueKind};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GraphEdge {
     /// Execution flow between nodes.
     ExecutionFlow,
+    /// Execution flow between nodes, only taken if the condition passes.
+    ConditionalFlow(Condition),
+    /// Execution flow between nodes, only taken if the source node errors.
+    /// The error message is written to the first input store of the target.
+    ErrorFlow,
     /// Data flow between stores.
+    /// Before a node runs, each of its input stores with an incoming data flow
+    /// is set to the value at the start of the chain of data flows leading to it.
     DataFlow,
     /// Data map from node -> store, or store -> node.
     /// The usize is the index of the data in the node.
@@ -58,6 +87,67 @@ pub enum GraphNode {
     SyncNode(Box<dyn SyncNode>),
     /// Used as an intermediary store for data between nodes.
     Store(Value),
+    /// A store whose value is never changed by execution.
+    /// Nodes can read it as an input, but outputs and data flows into it are ignored.
+    Constant(Value),
+}
+
+impl GraphNode {
+    /// Returns the value of a store or constant.
+    pub fn value(&self) -> Option<&Value> {
+        match self {
+            GraphNode::Store(value) | GraphNode::Constant(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A computation graph.
+///
+/// Dereferences to the underlying [`DiGraph`], which holds the nodes and edges.
+/// Node labels, see [`GraphLabels`], are stored alongside it.
+#[derive(Default)]
+pub struct Graph {
+    graph: DiGraph<GraphNode, GraphEdge>,
+    labels: HashMap<NodeIndex, String>,
 }
 
-pub type Graph = DiGraph<GraphNode, GraphEdge>;
+impl Graph {
+    pub fn with_capacity(nodes: usize, edges: usize) -> Self {
+        Self {
+            graph: DiGraph::with_capacity(nodes, edges),
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Removes a node, returning its weight.
+    ///
+    /// Like [`DiGraph::remove_node`], the last node takes the removed node's index,
+    /// and its label moves with it.
+    pub fn remove_node(&mut self, node: NodeIndex) -> Option<GraphNode> {
+        let weight = self.graph.remove_node(node)?;
+
+        self.labels.remove(&node);
+
+        let last = NodeIndex::new(self.graph.node_count());
+        if let Some(label) = self.labels.remove(&last) {
+            self.labels.insert(node, label);
+        }
+
+        Some(weight)
+    }
+}
+
+impl Deref for Graph {
+    type Target = DiGraph<GraphNode, GraphEdge>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graph
+    }
+}
+
+impl DerefMut for Graph {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.graph
+    }
+}
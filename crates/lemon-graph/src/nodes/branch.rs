@@ -0,0 +1,149 @@
+use petgraph::graph::NodeIndex;
+
+use crate::{Condition, Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Routes execution on a [`Value::Bool`] input,
+/// to `then` if it is `true` and to `otherwise` if it is `false`.
+///
+/// Outputs the input. Each target is connected with a [`GraphEdge::ConditionalFlow`]
+/// on that value, so only one of them runs next.
+/// Other input types fail with [`NodeError::ConversionError`].
+#[derive(Debug, Clone, Copy)]
+pub struct BranchNode(pub NodeIndex);
+
+impl From<BranchNode> for NodeIndex {
+    fn from(value: BranchNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for BranchNode {}
+
+impl BranchNode {
+    pub fn new(graph: &mut Graph, then: Option<NodeIndex>, otherwise: Option<NodeIndex>) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(BranchWeight)));
+
+        let input = graph.add_node(GraphNode::Store(Value::Bool(false)));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let output = graph.add_node(GraphNode::Store(Value::Bool(false)));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        for (value, target) in [(true, then), (false, otherwise)] {
+            if let Some(target) = target {
+                graph.add_edge(
+                    index,
+                    target,
+                    GraphEdge::ConditionalFlow(Condition::Equals(Value::Bool(value))),
+                );
+            }
+        }
+
+        Self(index)
+    }
+
+    /// The condition to branch on.
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    /// The condition that was branched on.
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct BranchWeight;
+
+impl SyncNode for BranchWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        match inputs.first() {
+            Some(Value::Bool(value)) => Ok(vec![Value::Bool(*value)]),
+            Some(v) => Err(NodeError::ConversionError(v.clone())),
+            None => Err(NodeError::MissingInput(0)),
+        }
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.branch"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{nodes::CallbackNode, ExecutionStepError, Executor};
+
+    use super::*;
+
+    /// Branches on `value` to a "then" and an "otherwise" target.
+    /// Returns the names of the targets that ran.
+    async fn branch(value: Value) -> Result<Vec<&'static str>, ExecutionStepError> {
+        let mut graph = Graph::default();
+        let ran = Rc::new(RefCell::new(Vec::new()));
+
+        let mut target = |name: &'static str| {
+            let ran = ran.clone();
+            CallbackNode::new(&mut graph, move |input| {
+                ran.borrow_mut().push(name);
+                input
+            })
+            .0
+        };
+
+        let then = target("then");
+        let otherwise = target("otherwise");
+
+        let branch = BranchNode::new(&mut graph, Some(then), Some(otherwise));
+
+        let input = branch.input(&graph).unwrap();
+        input.set_value(&mut graph, value);
+
+        Executor::execute(&mut graph, branch.0).await?;
+
+        let ran = ran.borrow().clone();
+        Ok(ran)
+    }
+
+    #[tokio::test]
+    async fn test_branch() {
+        assert_eq!(branch(Value::Bool(true)).await.unwrap(), ["then"]);
+        assert_eq!(branch(Value::Bool(false)).await.unwrap(), ["otherwise"]);
+    }
+
+    #[tokio::test]
+    async fn test_branch_not_bool() {
+        assert!(matches!(
+            branch(Value::String("true".to_string())).await,
+            Err(ExecutionStepError::NodeError(NodeError::ConversionError(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_branch_one_target() {
+        let mut graph = Graph::default();
+        let ran = Rc::new(RefCell::new(false));
+
+        let then = CallbackNode::new(&mut graph, {
+            let ran = ran.clone();
+            move |input| {
+                *ran.borrow_mut() = true;
+                input
+            }
+        });
+
+        let branch = BranchNode::new(&mut graph, Some(then.0), None);
+        let input = branch.input(&graph).unwrap();
+        input.set_value(&mut graph, Value::Bool(false));
+
+        Executor::execute(&mut graph, branch.0).await.unwrap();
+
+        assert!(!*ran.borrow());
+        let output = branch.output(&graph).unwrap();
+        assert_eq!(output.get(&graph).unwrap(), Value::Bool(false));
+    }
+}
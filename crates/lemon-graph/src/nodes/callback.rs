@@ -58,6 +58,10 @@ impl SyncNode for CallbackWeight {
 
         Ok(vec![output])
     }
+
+    fn type_name(&self) -> &str {
+        "lemon.callback"
+    }
 }
 
 #[cfg(test)]
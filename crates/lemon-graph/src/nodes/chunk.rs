@@ -0,0 +1,503 @@
+use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Splits text into chunks, such as for embedding.
+/// Outputs a [`Value::Vec`] of strings, in order.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkNode(pub NodeIndex);
+
+impl From<ChunkNode> for NodeIndex {
+    fn from(value: ChunkNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for ChunkNode {}
+
+impl ChunkNode {
+    pub fn new(graph: &mut Graph, weight: ChunkWeight) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(weight)));
+
+        let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let output = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkStrategy {
+    /// Chunks of `chunk_size` characters, each starting `overlap` characters
+    /// before the end of the previous chunk.
+    #[default]
+    Fixed,
+    /// Splits on blank lines, merging paragraphs up to `chunk_size` characters.
+    Paragraph,
+    /// Splits after sentence punctuation, merging sentences up to `chunk_size` characters.
+    Sentence,
+}
+
+impl ChunkStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fixed => "fixed",
+            Self::Paragraph => "paragraph",
+            Self::Sentence => "sentence",
+        }
+    }
+}
+
+impl TryFrom<&str> for ChunkStrategy {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "fixed" => Ok(Self::Fixed),
+            "paragraph" => Ok(Self::Paragraph),
+            "sentence" => Ok(Self::Sentence),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Measures the length of text, such as in tokens.
+pub type CountFn = Arc<dyn Fn(&str) -> usize>;
+
+/// Chunk sizes are measured in characters, or by [`ChunkWeight::counter`] if set.
+/// With the paragraph and sentence strategies, a single paragraph or sentence
+/// longer than `chunk_size` is split into fixed size chunks.
+#[derive(Clone)]
+pub struct ChunkWeight {
+    pub chunk_size: usize,
+    /// Only used by [`ChunkStrategy::Fixed`]. Must be less than `chunk_size`.
+    pub overlap: usize,
+    pub strategy: ChunkStrategy,
+    /// Measures chunks instead of counting characters, such as a tokenizer.
+    /// Fixed size chunks are then split on whitespace, so words are kept whole.
+    /// A single word longer than `chunk_size` becomes its own chunk.
+    pub counter: Option<CountFn>,
+}
+
+impl Debug for ChunkWeight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChunkWeight")
+            .field("chunk_size", &self.chunk_size)
+            .field("overlap", &self.overlap)
+            .field("strategy", &self.strategy)
+            .field("counter", &self.counter.is_some())
+            .finish()
+    }
+}
+
+impl Default for ChunkWeight {
+    fn default() -> Self {
+        Self {
+            chunk_size: 1000,
+            overlap: 0,
+            strategy: ChunkStrategy::default(),
+            counter: None,
+        }
+    }
+}
+
+impl ChunkWeight {
+    pub fn with_counter(mut self, counter: impl Fn(&str) -> usize + 'static) -> Self {
+        self.counter = Some(Arc::new(counter));
+        self
+    }
+
+    /// Creates a weight from its [`SyncNode::config`].
+    /// Missing fields use their defaults. The counter is not stored, so is not set.
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        let Value::Map(config) = config else {
+            return Err(NodeError::ConversionError(config.clone()));
+        };
+
+        let mut weight = Self::default();
+
+        for (key, value) in config {
+            match (key.as_str(), value) {
+                ("chunk_size", Value::USize(chunk_size)) if *chunk_size > 0 => {
+                    weight.chunk_size = *chunk_size
+                }
+                ("overlap", Value::USize(overlap)) => weight.overlap = *overlap,
+                ("strategy", Value::String(strategy)) => {
+                    weight.strategy = ChunkStrategy::try_from(strategy.as_str())
+                        .map_err(|_| NodeError::ConversionError(value.clone()))?;
+                }
+                _ => return Err(NodeError::ConversionError(value.clone())),
+            }
+        }
+
+        Ok(weight)
+    }
+
+    /// Splits `text` into chunks.
+    /// Errors if the chunk size is 0, or if the overlap is used and is not less than it.
+    pub fn chunk(&self, text: &str) -> Result<Vec<String>, NodeError> {
+        self.validate()?;
+
+        Ok(match self.strategy {
+            ChunkStrategy::Fixed => self.fixed(text, self.overlap),
+            ChunkStrategy::Paragraph => self.merge(paragraphs(text), "\n\n"),
+            ChunkStrategy::Sentence => self.merge(sentences(text), " "),
+        })
+    }
+
+    fn validate(&self) -> Result<(), NodeError> {
+        if self.chunk_size == 0 {
+            return Err(NodeError::InternalError(
+                "Chunk size must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.strategy == ChunkStrategy::Fixed && self.overlap >= self.chunk_size {
+            return Err(NodeError::InternalError(format!(
+                "Overlap of {} must be less than the chunk size of {}",
+                self.overlap, self.chunk_size
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn len(&self, text: &str) -> usize {
+        match &self.counter {
+            Some(counter) => counter(text),
+            None => text.chars().count(),
+        }
+    }
+
+    fn fixed(&self, text: &str, overlap: usize) -> Vec<String> {
+        if self.counter.is_some() {
+            return self.fixed_words(text, overlap);
+        }
+
+        let chunk_size = self.chunk_size;
+
+        let chars = text.chars().collect::<Vec<_>>();
+        let step = chunk_size - overlap;
+
+        let mut chunks = Vec::new();
+        let mut start = 0;
+
+        while start < chars.len() {
+            let end = (start + chunk_size).min(chars.len());
+            let chunk = chars[start..end].iter().collect::<String>();
+
+            if !chunk.trim().is_empty() {
+                chunks.push(chunk);
+            }
+
+            if end == chars.len() {
+                break;
+            }
+
+            start += step;
+        }
+
+        chunks
+    }
+
+    /// Fixed size chunks of whole words, measured by the counter.
+    fn fixed_words(&self, text: &str, overlap: usize) -> Vec<String> {
+        let words = text
+            .split_inclusive(char::is_whitespace)
+            .collect::<Vec<_>>();
+
+        let mut chunks = Vec::new();
+        let mut start = 0;
+
+        while start < words.len() {
+            // Always take at least one word, so long words still make progress.
+            let mut end = start + 1;
+
+            while end < words.len() && self.len(&words[start..=end].concat()) <= self.chunk_size {
+                end += 1;
+            }
+
+            let chunk = words[start..end].concat();
+
+            if !chunk.trim().is_empty() {
+                chunks.push(chunk.trim().to_string());
+            }
+
+            if end == words.len() {
+                break;
+            }
+
+            // Start the next chunk at the longest run of words that fits in the overlap.
+            start = (start + 1..end)
+                .find(|i| self.len(&words[*i..end].concat()) <= overlap)
+                .unwrap_or(end);
+        }
+
+        chunks
+    }
+
+    fn merge<'a>(&self, units: impl Iterator<Item = &'a str>, separator: &str) -> Vec<String> {
+        let mut chunks = Vec::new();
+        let mut current = String::new();
+
+        for unit in units {
+            let len = self.len(unit);
+
+            if len > self.chunk_size {
+                if !current.is_empty() {
+                    chunks.push(std::mem::take(&mut current));
+                }
+
+                chunks.extend(self.fixed(unit, 0));
+                continue;
+            }
+
+            if !current.is_empty()
+                && self.len(&current) + self.len(separator) + len > self.chunk_size
+            {
+                chunks.push(std::mem::take(&mut current));
+            }
+
+            if !current.is_empty() {
+                current.push_str(separator);
+            }
+
+            current.push_str(unit);
+        }
+
+        if !current.is_empty() {
+            chunks.push(current);
+        }
+
+        chunks
+    }
+}
+
+fn paragraphs(text: &str) -> impl Iterator<Item = &str> {
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            paragraphs.push(&text[start..offset]);
+            start = offset + line.len();
+        }
+
+        offset += line.len();
+    }
+
+    paragraphs.push(&text[start..]);
+
+    paragraphs
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+}
+
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+
+        if let Some((next, ws)) = chars.peek() {
+            if ws.is_whitespace() {
+                sentences.push(&text[start..*next]);
+                start = *next;
+            }
+        } else {
+            sentences.push(&text[start..i + c.len_utf8()]);
+            start = text.len();
+        }
+    }
+
+    sentences.push(&text[start..]);
+
+    sentences
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+impl SyncNode for ChunkWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        let text = match inputs.first() {
+            Some(Value::String(text)) => text,
+            Some(v) => return Err(NodeError::ConversionError(v.clone())),
+            None => return Err(NodeError::MissingInput(0)),
+        };
+
+        let chunks = self.chunk(text)?.into_iter().map(Value::String).collect();
+
+        Ok(vec![Value::Vec(chunks)])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.chunk"
+    }
+
+    fn config(&self) -> Option<Value> {
+        Some(Value::Map(BTreeMap::from([
+            ("chunk_size".to_string(), Value::USize(self.chunk_size)),
+            ("overlap".to_string(), Value::USize(self.overlap)),
+            (
+                "strategy".to_string(),
+                Value::String(self.strategy.as_str().to_string()),
+            ),
+        ])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Executor;
+
+    use super::*;
+
+    fn weight(chunk_size: usize, overlap: usize, strategy: ChunkStrategy) -> ChunkWeight {
+        ChunkWeight {
+            chunk_size,
+            overlap,
+            strategy,
+            counter: None,
+        }
+    }
+
+    #[test]
+    fn test_fixed() {
+        let chunks = weight(4, 1, ChunkStrategy::Fixed)
+            .chunk("abcdefghij")
+            .unwrap();
+        assert_eq!(chunks, vec!["abcd", "defg", "ghij"]);
+
+        let chunks = weight(4, 0, ChunkStrategy::Fixed)
+            .chunk("abcd    ")
+            .unwrap();
+        assert_eq!(chunks, vec!["abcd"]);
+
+        assert!(weight(4, 0, ChunkStrategy::Fixed)
+            .chunk("")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_fixed_short() {
+        let chunks = weight(100, 10, ChunkStrategy::Fixed)
+            .chunk("Hello, world!")
+            .unwrap();
+        assert_eq!(chunks, vec!["Hello, world!"]);
+    }
+
+    #[test]
+    fn test_zero_chunk_size() {
+        for strategy in [ChunkStrategy::Fixed, ChunkStrategy::Sentence] {
+            assert!(matches!(
+                weight(0, 0, strategy).chunk("abc"),
+                Err(NodeError::InternalError(_))
+            ));
+        }
+
+        let config = Value::Map(BTreeMap::from([(
+            "chunk_size".to_string(),
+            Value::USize(0),
+        )]));
+        assert!(matches!(
+            ChunkWeight::from_config(&config),
+            Err(NodeError::ConversionError(_))
+        ));
+    }
+
+    #[test]
+    fn test_fixed_counter() {
+        // Counts words, as a stand in for a tokenizer.
+        let weight =
+            weight(3, 1, ChunkStrategy::Fixed).with_counter(|text| text.split_whitespace().count());
+
+        let chunks = weight.chunk("one two three four five six").unwrap();
+        assert_eq!(chunks, vec!["one two three", "three four five", "five six"]);
+
+        let chunks = weight.chunk("one two").unwrap();
+        assert_eq!(chunks, vec!["one two"]);
+    }
+
+    #[test]
+    fn test_invalid_overlap() {
+        let res = weight(4, 4, ChunkStrategy::Fixed).run(vec!["abcdefgh".to_string().into()]);
+        assert!(matches!(res, Err(NodeError::InternalError(_))));
+
+        // The overlap is unused by the other strategies.
+        let chunks = weight(4, 4, ChunkStrategy::Sentence)
+            .chunk("Hi. Ok.")
+            .unwrap();
+        assert_eq!(chunks, vec!["Hi.", "Ok."]);
+    }
+
+    #[test]
+    fn test_paragraph() {
+        let text = "First paragraph.\n\nSecond.\n  \nThird.\n\n\n";
+
+        let chunks = weight(100, 0, ChunkStrategy::Paragraph)
+            .chunk(text)
+            .unwrap();
+        assert_eq!(chunks, vec!["First paragraph.\n\nSecond.\n\nThird."]);
+
+        let chunks = weight(16, 0, ChunkStrategy::Paragraph).chunk(text).unwrap();
+        assert_eq!(chunks, vec!["First paragraph.", "Second.\n\nThird."]);
+    }
+
+    #[test]
+    fn test_sentence() {
+        let text = "One. Two! Three? 3.5 is a number.";
+
+        let chunks = weight(10, 0, ChunkStrategy::Sentence).chunk(text).unwrap();
+        assert_eq!(chunks, vec!["One. Two!", "Three?", "3.5 is a n", "umber."]);
+
+        let chunks = weight(20, 0, ChunkStrategy::Sentence).chunk(text).unwrap();
+        assert_eq!(chunks, vec!["One. Two! Three?", "3.5 is a number."]);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_node() {
+        let mut graph = Graph::default();
+
+        let chunk = ChunkNode::new(&mut graph, weight(5, 0, ChunkStrategy::Fixed));
+        let input = chunk.input(&graph).unwrap();
+        input.set_value(&mut graph, "Hello, world!".to_string().into());
+
+        Executor::execute(&mut graph, chunk.0).await.unwrap();
+
+        let output = chunk.output(&graph).unwrap();
+        match &graph[output.0] {
+            GraphNode::Store(value) => assert_eq!(
+                value,
+                &Value::Vec(vec![
+                    "Hello".to_string().into(),
+                    ", wor".to_string().into(),
+                    "ld!".to_string().into(),
+                ])
+            ),
+            _ => panic!(),
+        }
+    }
+}
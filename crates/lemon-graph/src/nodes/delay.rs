@@ -0,0 +1,185 @@
+use std::{collections::BTreeMap, future::Future, time::Duration};
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{AsyncNode, GetStoreError, NodeError, NodeWrapper, StoreWrapper};
+
+/// Waits before continuing execution.
+///
+/// The duration is read in milliseconds from an optional numeric input.
+/// Without an input, or if it is [`Value::Null`], the duration given to the constructor is used.
+#[derive(Debug, Clone, Copy)]
+pub struct DelayNode(pub NodeIndex);
+
+impl From<DelayNode> for NodeIndex {
+    fn from(value: DelayNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for DelayNode {}
+
+impl DelayNode {
+    /// Creates a delay node without an input, always waiting for `duration`.
+    pub fn new(graph: &mut Graph, duration: Duration) -> Self {
+        let index = graph.add_node(GraphNode::AsyncNode(Box::new(DelayWeight { duration })));
+        Self(index)
+    }
+
+    /// Creates a delay node with a duration input, initialized to `duration`.
+    pub fn new_with_input(graph: &mut Graph, duration: Duration) -> Self {
+        let node = Self::new(graph, duration);
+
+        let input = graph.add_node(GraphNode::Store(
+            Value::USize(duration.as_millis() as usize),
+        ));
+        graph.add_edge(input, node.0, GraphEdge::DataMap(0));
+
+        node
+    }
+
+    /// The duration in milliseconds.
+    pub fn duration(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+}
+
+pub(crate) struct DelayWeight {
+    duration: Duration,
+}
+
+impl DelayWeight {
+    /// Creates a weight from its [`AsyncNode::config`].
+    /// The duration defaults to zero.
+    #[cfg(feature = "serde")]
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        let Value::Map(map) = config else {
+            return Err(NodeError::ConversionError(config.clone()));
+        };
+
+        let duration = match map.get("duration") {
+            Some(duration) => millis(duration)?,
+            None => Duration::ZERO,
+        };
+
+        Ok(Self { duration })
+    }
+}
+
+impl AsyncNode for DelayWeight {
+    fn run(
+        &self,
+        inputs: Vec<Value>,
+    ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+        let duration = match inputs.first() {
+            Some(Value::Null) | None => Ok(self.duration),
+            Some(value) => millis(value),
+        };
+
+        Box::new(Box::pin(async move {
+            tokio::time::sleep(duration?).await;
+            Ok(Vec::new())
+        }))
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.delay"
+    }
+
+    /// The duration is stored in milliseconds, like the input.
+    fn config(&self) -> Option<Value> {
+        Some(Value::Map(BTreeMap::from([(
+            "duration".to_string(),
+            Value::USize(self.duration.as_millis() as usize),
+        )])))
+    }
+}
+
+fn millis(value: &Value) -> Result<Duration, NodeError> {
+    let millis = value.as_f64()?;
+
+    if millis < 0.0 || !millis.is_finite() {
+        return Err(NodeError::InternalError(format!(
+            "Invalid delay: {}ms",
+            millis
+        )));
+    }
+
+    Ok(Duration::from_secs_f64(millis / 1000.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time::Instant;
+
+    use crate::{nodes::MathNode, nodes::MathOp, Executor};
+
+    use super::*;
+
+    /// Measures the execution time, which with a paused clock only
+    /// advances by the delays.
+    async fn elapsed(graph: &mut Graph, start: NodeIndex) -> Duration {
+        let now = Instant::now();
+        Executor::execute(graph, start).await.unwrap();
+        now.elapsed()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_static_delay() {
+        let mut graph = Graph::default();
+        let delay = DelayNode::new(&mut graph, Duration::from_millis(50));
+
+        assert_eq!(
+            elapsed(&mut graph, delay.0).await,
+            Duration::from_millis(50)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_input_delay() {
+        let mut graph = Graph::default();
+
+        let math = MathNode::new(&mut graph, MathOp::Multiply);
+        let lhs = math.lhs(&graph).unwrap();
+        lhs.set_value(&mut graph, Value::USize(20));
+        let rhs = math.rhs(&graph).unwrap();
+        rhs.set_value(&mut graph, Value::F32(2.5));
+
+        let delay = DelayNode::new_with_input(&mut graph, Duration::from_secs(10));
+        delay.run_after(&mut graph, math.0);
+
+        let output = math.output(&graph).unwrap();
+        let duration = delay.duration(&graph).unwrap();
+        duration.set_input(&mut graph, Some(output));
+
+        assert_eq!(elapsed(&mut graph, math.0).await, Duration::from_millis(50));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_null_delay() {
+        let mut graph = Graph::default();
+
+        let delay = DelayNode::new_with_input(&mut graph, Duration::from_millis(50));
+        let duration = delay.duration(&graph).unwrap();
+        duration.set_value(&mut graph, Value::Null);
+
+        // A null input uses the constructor's duration.
+        assert_eq!(
+            elapsed(&mut graph, delay.0).await,
+            Duration::from_millis(50)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_negative_delay() {
+        let mut graph = Graph::default();
+
+        let delay = DelayNode::new_with_input(&mut graph, Duration::ZERO);
+        let duration = delay.duration(&graph).unwrap();
+        duration.set_value(&mut graph, Value::ISize(-1));
+
+        assert!(Executor::execute(&mut graph, delay.0).await.is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    collections::BTreeMap,
+    env::{self, VarError},
+};
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Reads an environment variable when run, so graphs can be configured
+/// without rebuilding them.
+///
+/// Outputs the value of the variable, and whether it is set.
+/// A variable set to an empty string is set, so it outputs an empty string
+/// instead of the default.
+#[derive(Debug, Clone, Copy)]
+pub struct EnvVarNode(pub NodeIndex);
+
+impl From<EnvVarNode> for NodeIndex {
+    fn from(value: EnvVarNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for EnvVarNode {}
+
+impl EnvVarNode {
+    pub fn new(graph: &mut Graph, weight: EnvVarWeight) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(weight)));
+
+        let output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        let is_set = graph.add_node(GraphNode::Store(Value::Bool(false)));
+        graph.add_edge(index, is_set, GraphEdge::DataMap(1));
+
+        Self(index)
+    }
+
+    /// The value of the variable, or the default if it is unset.
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+
+    /// Whether the variable is set, as a [`Value::Bool`].
+    pub fn is_set(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvVarWeight {
+    pub name: String,
+    /// Output when the variable is unset.
+    pub default: Option<String>,
+    /// Errors when the variable is unset and there is no default.
+    /// Otherwise an empty string is output.
+    pub required: bool,
+}
+
+impl EnvVarWeight {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            default: None,
+            required: false,
+        }
+    }
+
+    pub fn with_default(mut self, default: impl Into<String>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    pub fn with_required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Creates a weight from its [`SyncNode::config`].
+    /// The name is required, other fields use their defaults if missing.
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        let Value::Map(map) = config else {
+            return Err(NodeError::ConversionError(config.clone()));
+        };
+
+        let mut weight = match map.get("name") {
+            Some(name) => Self::new(name.as_str()?),
+            None => return Err(NodeError::ConversionError(config.clone())),
+        };
+
+        for (key, value) in map {
+            match (key.as_str(), value) {
+                ("name", _) => {}
+                ("default", Value::String(default)) => weight.default = Some(default.clone()),
+                ("required", Value::Bool(required)) => weight.required = *required,
+                _ => return Err(NodeError::ConversionError(value.clone())),
+            }
+        }
+
+        Ok(weight)
+    }
+}
+
+impl SyncNode for EnvVarWeight {
+    fn run(&self, _inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        let (value, is_set) = match env::var(&self.name) {
+            Ok(value) => (value, true),
+            Err(VarError::NotPresent) => match &self.default {
+                Some(default) => (default.clone(), false),
+                None if self.required => {
+                    return Err(NodeError::InternalError(format!(
+                        "Environment variable {} is not set",
+                        self.name
+                    )))
+                }
+                None => (String::new(), false),
+            },
+            Err(VarError::NotUnicode(_)) => {
+                return Err(NodeError::InternalError(format!(
+                    "Environment variable {} is not valid unicode",
+                    self.name
+                )))
+            }
+        };
+
+        Ok(vec![Value::String(value), Value::Bool(is_set)])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.env_var"
+    }
+
+    fn config(&self) -> Option<Value> {
+        let mut config = BTreeMap::from([
+            ("name".to_string(), Value::String(self.name.clone())),
+            ("required".to_string(), Value::Bool(self.required)),
+        ]);
+
+        if let Some(default) = &self.default {
+            config.insert("default".to_string(), Value::String(default.clone()));
+        }
+
+        Some(Value::Map(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test uses its own variable, as tests run in parallel.
+
+    #[test]
+    fn test_env_var_set() {
+        env::set_var("LEMON_TEST_ENV_SET", "value");
+
+        let weight = EnvVarWeight::new("LEMON_TEST_ENV_SET").with_default("default");
+        assert_eq!(
+            weight.run(Vec::new()).unwrap(),
+            vec![Value::String("value".to_string()), Value::Bool(true)]
+        );
+
+        env::set_var("LEMON_TEST_ENV_SET", "");
+        assert_eq!(
+            weight.run(Vec::new()).unwrap(),
+            vec![Value::String(String::new()), Value::Bool(true)]
+        );
+
+        env::remove_var("LEMON_TEST_ENV_SET");
+    }
+
+    #[test]
+    fn test_env_var_default() {
+        let weight = EnvVarWeight::new("LEMON_TEST_ENV_DEFAULT")
+            .with_default("default")
+            .with_required();
+
+        assert_eq!(
+            weight.run(Vec::new()).unwrap(),
+            vec![Value::String("default".to_string()), Value::Bool(false)]
+        );
+    }
+
+    #[test]
+    fn test_env_var_unset() {
+        let weight = EnvVarWeight::new("LEMON_TEST_ENV_UNSET");
+        assert_eq!(
+            weight.run(Vec::new()).unwrap(),
+            vec![Value::String(String::new()), Value::Bool(false)]
+        );
+
+        let weight = weight.with_required();
+        assert!(matches!(
+            weight.run(Vec::new()),
+            Err(NodeError::InternalError(e)) if e.contains("LEMON_TEST_ENV_UNSET")
+        ));
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Extracts a nested value, such as a field of a parsed JSON response.
+///
+/// The path addresses [`Value::Map`] keys with dots and [`Value::Vec`] items
+/// with brackets, such as `choices[0].message.content`.
+/// An empty path outputs the whole input.
+#[derive(Debug, Clone, Copy)]
+pub struct ExtractNode(pub NodeIndex);
+
+impl From<ExtractNode> for NodeIndex {
+    fn from(value: ExtractNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for ExtractNode {}
+
+impl ExtractNode {
+    pub fn new(graph: &mut Graph, weight: ExtractWeight) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(weight)));
+
+        let input = graph.add_node(GraphNode::Store(Value::Null));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let output = graph.add_node(GraphNode::Store(Value::Null));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractWeight {
+    pub path: String,
+    /// Output when the path does not resolve.
+    /// If `None`, the node fails with [`NodeError::ConversionError`] instead.
+    pub default: Option<Value>,
+}
+
+impl ExtractWeight {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            default: None,
+        }
+    }
+
+    pub fn with_default(mut self, default: Value) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Creates a weight from its [`SyncNode::config`].
+    /// The path is required, there is no default unless set.
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        let Value::Map(map) = config else {
+            return Err(NodeError::ConversionError(config.clone()));
+        };
+
+        let path = match map.get("path") {
+            Some(path) => path.as_str()?,
+            None => return Err(NodeError::ConversionError(config.clone())),
+        };
+
+        Ok(Self {
+            path: path.to_string(),
+            default: map.get("default").cloned(),
+        })
+    }
+
+    /// Returns the value at the path, or `None` if it does not resolve.
+    /// Errors if the path is malformed.
+    pub fn extract<'a>(&self, value: &'a Value) -> Result<Option<&'a Value>, NodeError> {
+        let mut current = value;
+
+        for segment in parse_path(&self.path)? {
+            let next = match (segment, current) {
+                (Segment::Key(key), Value::Map(map)) => map.get(key),
+                (Segment::Index(index), Value::Vec(items)) => items.get(index),
+                _ => None,
+            };
+
+            match next {
+                Some(next) => current = next,
+                None => return Ok(None),
+            }
+        }
+
+        Ok(Some(current))
+    }
+}
+
+impl SyncNode for ExtractWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        let value = inputs.first().ok_or(NodeError::MissingInput(0))?;
+
+        let extracted = match (self.extract(value)?, &self.default) {
+            (Some(extracted), _) => extracted.clone(),
+            (None, Some(default)) => default.clone(),
+            (None, None) => return Err(NodeError::ConversionError(value.clone())),
+        };
+
+        Ok(vec![extracted])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.extract"
+    }
+
+    fn config(&self) -> Option<Value> {
+        let mut config = BTreeMap::from([("path".to_string(), Value::String(self.path.clone()))]);
+
+        if let Some(default) = &self.default {
+            config.insert("default".to_string(), default.clone());
+        }
+
+        Some(Value::Map(config))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment<'_>>, NodeError> {
+    let invalid = || NodeError::InternalError(format!("Invalid path `{}`", path));
+
+    let mut segments = Vec::new();
+
+    if path.is_empty() {
+        return Ok(segments);
+    }
+
+    for (i, part) in path.split('.').enumerate() {
+        let (key, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+
+        // Only the first part may start with an index, such as `[0].name`.
+        if key.is_empty() && (i > 0 || rest.is_empty()) {
+            return Err(invalid());
+        }
+
+        if !key.is_empty() {
+            segments.push(Segment::Key(key));
+        }
+
+        while !rest.is_empty() {
+            let (index, after) = rest
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'))
+                .ok_or_else(invalid)?;
+
+            segments.push(Segment::Index(index.parse().map_err(|_| invalid())?));
+            rest = after;
+        }
+    }
+
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::Executor;
+
+    use super::*;
+
+    fn map(entries: impl IntoIterator<Item = (&'static str, Value)>) -> Value {
+        Value::Map(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+
+    /// A chat completion response, with one choice.
+    fn response() -> Value {
+        map([(
+            "choices",
+            Value::Vec(vec![map([(
+                "message",
+                map([("content", Value::String("Hello!".to_string()))]),
+            )])]),
+        )])
+    }
+
+    #[tokio::test]
+    async fn test_extract() {
+        let mut graph = Graph::default();
+
+        let extract =
+            ExtractNode::new(&mut graph, ExtractWeight::new("choices[0].message.content"));
+        let input = extract.input(&graph).unwrap();
+        input.set_value(&mut graph, response());
+
+        Executor::execute(&mut graph, extract.0).await.unwrap();
+
+        let output = extract.output(&graph).unwrap();
+        assert_eq!(
+            output.get(&graph).unwrap(),
+            Value::String("Hello!".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_out_of_bounds() {
+        let weight = ExtractWeight::new("choices[1].message");
+        assert!(matches!(
+            weight.run(vec![response()]),
+            Err(NodeError::ConversionError(_))
+        ));
+
+        // Indexing a map, or a key of a vec, does not resolve.
+        let weight = ExtractWeight::new("choices.message");
+        assert_eq!(weight.extract(&response()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_extract_default() {
+        let weight = ExtractWeight::new("choices[0].message.name").with_default(Value::Null);
+        assert_eq!(weight.run(vec![response()]).unwrap(), vec![Value::Null]);
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(parse_path("").unwrap(), vec![]);
+        assert_eq!(
+            parse_path("[1][2].a.b[0]").unwrap(),
+            vec![
+                Segment::Index(1),
+                Segment::Index(2),
+                Segment::Key("a"),
+                Segment::Key("b"),
+                Segment::Index(0),
+            ]
+        );
+
+        for path in ["a..b", "a[x]", "a[0", "a[0]b", "a.[0]", "."] {
+            assert!(parse_path(path).is_err(), "{}", path);
+        }
+    }
+}
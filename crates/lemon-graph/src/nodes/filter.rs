@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Condition, Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Outputs the elements of a [`Value::Vec`] that pass a [`Condition`], in order.
+///
+/// Elements the condition can't test, such as numbers for [`Condition::Contains`],
+/// are dropped, or error if `strict` is set.
+#[derive(Debug, Clone, Copy)]
+pub struct FilterNode(pub NodeIndex);
+
+impl From<FilterNode> for NodeIndex {
+    fn from(value: FilterNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for FilterNode {}
+
+impl FilterNode {
+    pub fn new(graph: &mut Graph, condition: Condition, strict: bool) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(FilterWeight {
+            condition,
+            strict,
+        })));
+
+        let input = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let output = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+pub(crate) struct FilterWeight {
+    condition: Condition,
+    strict: bool,
+}
+
+impl FilterWeight {
+    /// Creates a weight from its [`SyncNode::config`].
+    /// The condition is required, `strict` defaults to `false`.
+    #[cfg(feature = "serde")]
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        let Value::Map(map) = config else {
+            return Err(NodeError::ConversionError(config.clone()));
+        };
+
+        let condition = match map.get("condition") {
+            Some(condition) => Condition::try_from(condition)?,
+            None => return Err(NodeError::ConversionError(config.clone())),
+        };
+
+        let strict = match map.get("strict") {
+            Some(strict) => strict.as_bool()?,
+            None => false,
+        };
+
+        Ok(Self { condition, strict })
+    }
+}
+
+impl SyncNode for FilterWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        let items = match inputs.into_iter().next() {
+            Some(Value::Vec(items)) => items,
+            Some(value) => return Err(NodeError::ConversionError(value)),
+            None => return Err(NodeError::MissingInput(0)),
+        };
+
+        let mut output = Vec::new();
+
+        for item in items {
+            if !self.condition.accepts(&item) {
+                if self.strict {
+                    return Err(NodeError::ConversionError(item));
+                }
+
+                continue;
+            }
+
+            if self.condition.evaluate(&item) {
+                output.push(item);
+            }
+        }
+
+        Ok(vec![Value::Vec(output)])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.filter"
+    }
+
+    fn config(&self) -> Option<Value> {
+        Some(Value::Map(BTreeMap::from([
+            ("condition".to_string(), Value::from(&self.condition)),
+            ("strict".to_string(), Value::Bool(self.strict)),
+        ])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Executor;
+
+    use super::*;
+
+    fn filter(condition: Condition, strict: bool, items: Vec<Value>) -> Result<Value, NodeError> {
+        let weight = FilterWeight { condition, strict };
+        weight
+            .run(vec![Value::Vec(items)])
+            .map(|mut outputs| outputs.remove(0))
+    }
+
+    fn strings(values: &[&str]) -> Vec<Value> {
+        values.iter().map(|v| v.to_string().into()).collect()
+    }
+
+    #[test]
+    fn test_filter_contains() {
+        let condition = Condition::Contains("an".to_string());
+        let items = strings(&["banana", "apple", "mango"]);
+
+        assert_eq!(
+            filter(condition.clone(), false, items).unwrap(),
+            Value::Vec(strings(&["banana", "mango"]))
+        );
+
+        assert_eq!(
+            filter(condition, false, strings(&["apple", "pear"])).unwrap(),
+            Value::Vec(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_filter_greater_than() {
+        let condition = Condition::GreaterThan(1.5);
+        let items = vec![
+            Value::USize(1),
+            Value::F32(2.5),
+            Value::String("3".to_string()),
+            Value::ISize(2),
+        ];
+
+        assert_eq!(
+            filter(condition.clone(), false, items.clone()).unwrap(),
+            Value::Vec(vec![Value::F32(2.5), Value::ISize(2)])
+        );
+
+        assert!(matches!(
+            filter(condition.clone(), true, items),
+            Err(NodeError::ConversionError(Value::String(_)))
+        ));
+
+        assert_eq!(
+            filter(condition, true, vec![Value::USize(0)]).unwrap(),
+            Value::Vec(Vec::new())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_filter_node() {
+        let mut graph = Graph::default();
+
+        let filter = FilterNode::new(&mut graph, Condition::Truthy, false);
+        let input = filter.input(&graph).unwrap();
+        input.set_value(&mut graph, Value::Vec(strings(&["a", "", "b"])));
+
+        Executor::execute(&mut graph, filter.0).await.unwrap();
+
+        let output = filter.output(&graph).unwrap();
+        assert_eq!(
+            output.get(&graph).unwrap(),
+            Value::Vec(strings(&["a", "b"]))
+        );
+    }
+}
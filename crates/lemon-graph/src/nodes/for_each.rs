@@ -0,0 +1,295 @@
+use std::{cell::RefCell, future::Future, rc::Rc};
+
+use petgraph::graph::NodeIndex;
+use tracing::warn;
+
+use crate::{Executor, Graph, GraphEdge, GraphNode, Value};
+
+use super::{AsyncNode, GetStoreError, ItemError, NodeError, NodeWrapper, StoreWrapper};
+
+/// Runs a body graph once for each item of a [`Value::Vec`],
+/// collecting the results into a [`Value::Vec`].
+///
+/// Iterations run in order, one at a time.
+/// By default the node fails at the first failed item. See
+/// [`ForEachWeight::with_continue_on_error`] to keep going instead.
+#[derive(Debug, Clone, Copy)]
+pub struct ForEachNode(pub NodeIndex);
+
+impl From<ForEachNode> for NodeIndex {
+    fn from(value: ForEachNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for ForEachNode {}
+
+impl ForEachNode {
+    pub fn new(graph: &mut Graph, weight: ForEachWeight) -> Self {
+        let index = graph.add_node(GraphNode::AsyncNode(Box::new(weight)));
+
+        let input = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let output = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        let errors = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(index, errors, GraphEdge::DataMap(1));
+
+        Self(index)
+    }
+
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+
+    /// Items that failed, when continuing on error, as [`ItemError`] values.
+    pub fn errors(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 1)
+    }
+}
+
+pub struct ForEachWeight {
+    /// The loop body, kept between runs.
+    pub body: Rc<RefCell<Graph>>,
+    /// Node the body is executed from.
+    pub entry: NodeIndex,
+    /// Store in the body each item is written to.
+    pub item: StoreWrapper,
+    /// Store in the body the result is read from, after each iteration.
+    pub result: StoreWrapper,
+    /// Leaves out items that fail, instead of stopping at the first error.
+    pub continue_on_error: bool,
+}
+
+impl ForEachWeight {
+    pub fn new(body: Graph, entry: NodeIndex, item: StoreWrapper, result: StoreWrapper) -> Self {
+        Self {
+            body: Rc::new(RefCell::new(body)),
+            entry,
+            item,
+            result,
+            continue_on_error: false,
+        }
+    }
+
+    /// Leaves out items that fail instead of stopping at the first error,
+    /// so the run completes with partial results.
+    /// Failed items are written to [`ForEachNode::errors`], and reported in
+    /// [`RunReport::item_errors`](crate::RunReport::item_errors).
+    pub fn with_continue_on_error(mut self) -> Self {
+        self.continue_on_error = true;
+        self
+    }
+}
+
+impl AsyncNode for ForEachWeight {
+    fn run(
+        &self,
+        inputs: Vec<Value>,
+    ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+        let body = self.body.clone();
+        let entry = self.entry;
+        let item = self.item;
+        let result = self.result;
+        let continue_on_error = self.continue_on_error;
+
+        Box::new(Box::pin(async move {
+            let items = match inputs.first() {
+                Some(Value::Vec(items)) => items.clone(),
+                Some(v) => return Err(NodeError::ConversionError(v.clone())),
+                None => return Err(NodeError::MissingInput(0)),
+            };
+
+            // Take the body out while running, so it is not borrowed across awaits.
+            let mut graph = std::mem::take(&mut *body.borrow_mut());
+
+            let mut results = Vec::with_capacity(items.len());
+            let mut errors = Vec::new();
+            let mut error = None;
+
+            for (i, value) in items.into_iter().enumerate() {
+                item.set_value(&mut graph, value);
+
+                if let Err(e) = Executor::execute(&mut graph, entry).await {
+                    if continue_on_error {
+                        warn!("ForEach item {} failed: {}", i, e);
+                        errors.push(ItemError::new(i, e).into());
+                        continue;
+                    }
+
+                    error = Some(NodeError::InternalError(format!(
+                        "Item {} failed: {}",
+                        i, e
+                    )));
+                    break;
+                }
+
+                match graph[result.0].value() {
+                    Some(value) => results.push(value.clone()),
+                    None => {
+                        error = Some(NodeError::InternalError(
+                            "Result is not a store".to_string(),
+                        ));
+                        break;
+                    }
+                }
+            }
+
+            *body.borrow_mut() = graph;
+
+            if let Some(e) = error {
+                return Err(e);
+            }
+
+            Ok(vec![Value::Vec(results), Value::Vec(errors)])
+        }))
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.for_each"
+    }
+
+    fn item_errors_output(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nodes::{CallbackNode, SyncNode};
+
+    use super::*;
+
+    /// Outputs its input, failing on `"fail"`.
+    struct TestFallible;
+
+    impl SyncNode for TestFallible {
+        fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+            match inputs.first() {
+                Some(Value::String(s)) if s == "fail" => {
+                    Err(NodeError::InternalError("failed".to_string()))
+                }
+                Some(v) => Ok(vec![v.clone()]),
+                None => Err(NodeError::MissingInput(0)),
+            }
+        }
+    }
+
+    fn identity_body() -> (Graph, NodeIndex, StoreWrapper, StoreWrapper) {
+        let mut body = Graph::default();
+        let identity = CallbackNode::new(&mut body, |input| input);
+        let item = identity.input(&body).unwrap();
+        let result = identity.output(&body).unwrap();
+        (body, identity.0, item, result)
+    }
+
+    fn fallible_body() -> (Graph, NodeIndex, StoreWrapper, StoreWrapper) {
+        let mut body = Graph::default();
+        let node = body.add_node(GraphNode::SyncNode(Box::new(TestFallible)));
+        let item = body.add_node(GraphNode::Store(Value::String(Default::default())));
+        body.add_edge(item, node, GraphEdge::DataMap(0));
+        let result = body.add_node(GraphNode::Store(Value::String(Default::default())));
+        body.add_edge(node, result, GraphEdge::DataMap(0));
+        (body, node, StoreWrapper(item), StoreWrapper(result))
+    }
+
+    async fn run(weight: ForEachWeight, items: Vec<Value>) -> Result<Value, ()> {
+        let mut graph = Graph::default();
+        let for_each = ForEachNode::new(&mut graph, weight);
+
+        let input = for_each.input(&graph).unwrap();
+        input.set_value(&mut graph, Value::Vec(items));
+
+        Executor::execute(&mut graph, for_each.0)
+            .await
+            .map_err(|_| ())?;
+
+        let output = for_each.output(&graph).unwrap();
+        match &graph[output.0] {
+            GraphNode::Store(value) => Ok(value.clone()),
+            _ => panic!(),
+        }
+    }
+
+    fn strings(values: &[&str]) -> Vec<Value> {
+        values
+            .iter()
+            .map(|v| Value::String(v.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_for_each() {
+        let (body, entry, item, result) = identity_body();
+        let weight = ForEachWeight::new(body, entry, item, result);
+
+        let output = run(weight, strings(&["a", "b", "c"])).await.unwrap();
+        assert_eq!(output, Value::Vec(strings(&["a", "b", "c"])));
+    }
+
+    #[tokio::test]
+    async fn test_for_each_empty() {
+        let (body, entry, item, result) = identity_body();
+        let weight = ForEachWeight::new(body, entry, item, result);
+
+        let output = run(weight, Vec::new()).await.unwrap();
+        assert_eq!(output, Value::Vec(Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn test_for_each_error() {
+        let (body, entry, item, result) = fallible_body();
+        let weight = ForEachWeight::new(body, entry, item, result);
+        assert!(run(weight, strings(&["a", "fail", "c"])).await.is_err());
+
+        let (body, entry, item, result) = fallible_body();
+        let weight = ForEachWeight::new(body, entry, item, result).with_continue_on_error();
+
+        let output = run(weight, strings(&["a", "fail", "c"])).await.unwrap();
+        assert_eq!(output, Value::Vec(strings(&["a", "c"])));
+    }
+
+    #[tokio::test]
+    async fn test_for_each_partial() {
+        let (body, entry, item, result) = fallible_body();
+        let weight = ForEachWeight::new(body, entry, item, result).with_continue_on_error();
+
+        let mut graph = Graph::default();
+        let for_each = ForEachNode::new(&mut graph, weight);
+
+        let input = for_each.input(&graph).unwrap();
+        input.set_value(&mut graph, Value::Vec(strings(&["a", "fail", "c"])));
+
+        let report = Executor::execute(&mut graph, for_each.0).await.unwrap();
+
+        let output = for_each.output(&graph).unwrap();
+        assert_eq!(
+            output.get(&graph).unwrap(),
+            Value::Vec(strings(&["a", "c"]))
+        );
+
+        let errors = for_each.errors(&graph).unwrap();
+        match errors.get(&graph).unwrap() {
+            Value::Vec(errors) => match errors.as_slice() {
+                [Value::Map(error)] => {
+                    assert_eq!(error.get("index"), Some(&Value::USize(1)));
+                    assert!(matches!(error.get("error"), Some(Value::String(_))));
+                }
+                errors => panic!("unexpected errors: {:?}", errors),
+            },
+            value => panic!("unexpected value: {:?}", value),
+        }
+
+        assert!(report.is_partial());
+        assert_eq!(report.item_errors.len(), 1);
+        assert_eq!(report.item_errors[0].0, for_each.0);
+        assert!(report.item_errors[0].1.starts_with("Item 1 failed: "));
+        assert!(report.errors.is_empty());
+    }
+}
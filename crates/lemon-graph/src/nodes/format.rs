@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Formats its inputs into a string, such as `"{0} said {1}"`.
+///
+/// Each `{n}` placeholder is replaced with input `n`, which can be used any
+/// number of times. Non-string values use their [`Display`](std::fmt::Display) output.
+/// Use `{{` and `}}` for literal braces.
+#[derive(Debug, Clone, Copy)]
+pub struct FormatNode(pub NodeIndex);
+
+impl From<FormatNode> for NodeIndex {
+    fn from(value: FormatNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for FormatNode {}
+
+impl FormatNode {
+    pub fn new(graph: &mut Graph, format: impl Into<String>, inputs: usize) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(FormatWeight {
+            format: format.into(),
+        })));
+
+        for i in 0..inputs {
+            let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+            graph.add_edge(input, index, GraphEdge::DataMap(i));
+        }
+
+        let output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn input(&self, graph: &Graph, index: usize) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, index)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+pub(crate) struct FormatWeight {
+    format: String,
+}
+
+impl FormatWeight {
+    /// Creates a weight from its [`SyncNode::config`].
+    #[cfg(feature = "serde")]
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        match config {
+            Value::Map(map) => match map.get("format") {
+                Some(Value::String(format)) => Ok(Self {
+                    format: format.clone(),
+                }),
+                Some(v) => Err(NodeError::ConversionError(v.clone())),
+                None => Err(NodeError::ConversionError(config.clone())),
+            },
+            _ => Err(NodeError::ConversionError(config.clone())),
+        }
+    }
+}
+
+/// Formats `values` into `format`.
+/// Errors with [`NodeError::MissingInput`] if a placeholder is out of range.
+pub fn format_values(format: &str, values: &[Value]) -> Result<String, NodeError> {
+    let mut out = String::with_capacity(format.len());
+    let mut rest = format;
+
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let brace = &rest[i..i + 1];
+        rest = &rest[i + 1..];
+
+        if rest.starts_with(brace) {
+            out.push_str(brace);
+            rest = &rest[1..];
+            continue;
+        }
+
+        let end = match (brace, rest.find('}')) {
+            ("{", Some(end)) => end,
+            _ => {
+                return Err(NodeError::InternalError(format!(
+                    "Unmatched {} in format string",
+                    brace
+                )))
+            }
+        };
+
+        let placeholder = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let index = placeholder.trim().parse::<usize>().map_err(|_| {
+            NodeError::InternalError(format!("Invalid placeholder {{{}}}", placeholder))
+        })?;
+
+        match values.get(index).ok_or(NodeError::MissingInput(index))? {
+            Value::String(value) => out.push_str(value),
+            value => out.push_str(&value.to_string()),
+        }
+    }
+
+    out.push_str(rest);
+
+    Ok(out)
+}
+
+impl SyncNode for FormatWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        Ok(vec![Value::String(format_values(&self.format, &inputs)?)])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.format"
+    }
+
+    fn config(&self) -> Option<Value> {
+        Some(Value::Map(BTreeMap::from([(
+            "format".to_string(),
+            Value::String(self.format.clone()),
+        )])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Executor;
+
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<Value> {
+        values
+            .iter()
+            .map(|v| Value::String(v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_concat() {
+        let values = strings(&["Hello", ", ", "world"]);
+        assert_eq!(
+            format_values("{0}{1}{2}!", &values).unwrap(),
+            "Hello, world!"
+        );
+    }
+
+    #[test]
+    fn test_reuse() {
+        let values = vec![Value::String("echo".to_string()), Value::USize(2)];
+        assert_eq!(
+            format_values("{0} {0} x{1} {{0}}", &values).unwrap(),
+            "echo echo x2 {0}"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let values = strings(&["a"]);
+
+        assert!(matches!(
+            format_values("{0} {1}", &values),
+            Err(NodeError::MissingInput(1))
+        ));
+        assert!(matches!(
+            format_values("{name}", &values),
+            Err(NodeError::InternalError(_))
+        ));
+        assert!(matches!(
+            format_values("{0", &values),
+            Err(NodeError::InternalError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_format_node() {
+        let mut graph = Graph::default();
+
+        let format = FormatNode::new(&mut graph, "{0} has {1} items", 2);
+        let name = format.input(&graph, 0).unwrap();
+        name.set_value(&mut graph, "The cart".to_string().into());
+        let count = format.input(&graph, 1).unwrap();
+        count.set_value(&mut graph, Value::USize(3));
+
+        Executor::execute(&mut graph, format.0).await.unwrap();
+
+        let output = format.output(&graph).unwrap();
+        assert_eq!(
+            graph[output.0].value(),
+            Some(&Value::String("The cart has 3 items".to_string()))
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use std::{collections::BTreeMap, future::Future, time::Duration};
+
+use petgraph::graph::NodeIndex;
+use reqwest::{redirect::Policy, Method};
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{AsyncNode, GetStoreError, NodeError, NodeWrapper, StoreWrapper};
+
+/// Sends an HTTP request, outputting the response body and status code.
+///
+/// Inputs are the URL, the method (`GET` if empty), the body (none if empty),
+/// and the headers as a [`Value::Map`] of strings.
+/// A [`Value::Null`] method, body, or headers input is treated as missing.
+/// Non-2xx responses are output like any other, unless
+/// [`HttpWeight::fail_on_error`] is set.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpNode(pub NodeIndex);
+
+impl From<HttpNode> for NodeIndex {
+    fn from(value: HttpNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for HttpNode {}
+
+impl HttpNode {
+    pub fn new(graph: &mut Graph, weight: HttpWeight) -> Self {
+        let index = graph.add_node(GraphNode::AsyncNode(Box::new(weight)));
+
+        for (i, value) in [
+            Value::String(Default::default()),
+            Value::String(Default::default()),
+            Value::String(Default::default()),
+            Value::Map(Default::default()),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let input = graph.add_node(GraphNode::Store(value));
+            graph.add_edge(input, index, GraphEdge::DataMap(i));
+        }
+
+        let body = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(index, body, GraphEdge::DataMap(0));
+
+        let status = graph.add_node(GraphNode::Store(Value::USize(0)));
+        graph.add_edge(index, status, GraphEdge::DataMap(1));
+
+        Self(index)
+    }
+
+    pub fn url(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn method(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 1)
+    }
+
+    pub fn body(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 2)
+    }
+
+    pub fn headers(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 3)
+    }
+
+    /// The response body.
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+
+    /// The response status code.
+    pub fn status(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpWeight {
+    /// Errors on non-2xx responses.
+    pub fail_on_error: bool,
+    pub timeout: Option<Duration>,
+    /// Maximum number of redirects to follow.
+    pub max_redirects: usize,
+}
+
+impl Default for HttpWeight {
+    fn default() -> Self {
+        Self {
+            fail_on_error: false,
+            timeout: None,
+            max_redirects: 10,
+        }
+    }
+}
+
+impl HttpWeight {
+    pub fn with_fail_on_error(mut self) -> Self {
+        self.fail_on_error = true;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// Creates a weight from its [`AsyncNode::config`].
+    /// Missing fields use their defaults.
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        let Value::Map(config) = config else {
+            return Err(NodeError::ConversionError(config.clone()));
+        };
+
+        let mut weight = Self::default();
+
+        for (key, value) in config {
+            match (key.as_str(), value) {
+                ("fail_on_error", Value::Bool(fail)) => weight.fail_on_error = *fail,
+                ("timeout", Value::USize(millis)) => {
+                    weight.timeout = Some(Duration::from_millis(*millis as u64))
+                }
+                ("max_redirects", Value::USize(max)) => weight.max_redirects = *max,
+                _ => return Err(NodeError::ConversionError(value.clone())),
+            }
+        }
+
+        Ok(weight)
+    }
+
+    fn client(&self) -> Result<reqwest::Client, NodeError> {
+        let mut builder = reqwest::Client::builder().redirect(match self.max_redirects {
+            0 => Policy::none(),
+            n => Policy::limited(n),
+        });
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        builder
+            .build()
+            .map_err(|e| NodeError::InternalError(e.to_string()))
+    }
+}
+
+impl AsyncNode for HttpWeight {
+    fn run(
+        &self,
+        inputs: Vec<Value>,
+    ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+        let weight = self.clone();
+
+        Box::new(Box::pin(async move {
+            let url = inputs.first().ok_or(NodeError::MissingInput(0))?.as_str()?;
+
+            let method = match inputs.get(1) {
+                Some(Value::String(method)) if !method.is_empty() => {
+                    Method::from_bytes(method.to_uppercase().as_bytes())
+                        .map_err(|_| NodeError::ConversionError(Value::String(method.clone())))?
+                }
+                Some(Value::String(_) | Value::Null) | None => Method::GET,
+                Some(v) => return Err(NodeError::ConversionError(v.clone())),
+            };
+
+            let mut request = weight.client()?.request(method, url);
+
+            match inputs.get(2) {
+                Some(Value::String(body)) if !body.is_empty() => {
+                    request = request.body(body.clone());
+                }
+                Some(Value::String(_) | Value::Null) | None => {}
+                Some(v) => return Err(NodeError::ConversionError(v.clone())),
+            }
+
+            match inputs.get(3) {
+                Some(Value::Map(headers)) => {
+                    for (name, value) in headers {
+                        request = request.header(name, value.as_str()?);
+                    }
+                }
+                Some(Value::Null) | None => {}
+                Some(v) => return Err(NodeError::ConversionError(v.clone())),
+            }
+
+            let response = request
+                .send()
+                .await
+                .map_err(|e| NodeError::InternalError(format!("Request failed: {}", e)))?;
+
+            let status = response.status();
+
+            let body = response
+                .text()
+                .await
+                .map_err(|e| NodeError::InternalError(format!("Failed to read body: {}", e)))?;
+
+            if weight.fail_on_error && !status.is_success() {
+                return Err(NodeError::InternalError(format!("{}: {}", status, body)));
+            }
+
+            Ok(vec![
+                Value::String(body),
+                Value::USize(status.as_u16() as usize),
+            ])
+        }))
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.http"
+    }
+
+    /// The timeout is stored in milliseconds.
+    fn config(&self) -> Option<Value> {
+        let mut config = BTreeMap::from([
+            ("fail_on_error".to_string(), Value::Bool(self.fail_on_error)),
+            (
+                "max_redirects".to_string(),
+                Value::USize(self.max_redirects),
+            ),
+        ]);
+
+        if let Some(timeout) = self.timeout {
+            config.insert(
+                "timeout".to_string(),
+                Value::USize(timeout.as_millis() as usize),
+            );
+        }
+
+        Some(Value::Map(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use wiremock::{
+        matchers::{body_string, header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::Executor;
+
+    use super::*;
+
+    async fn server() -> MockServer {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/echo"))
+            .and(header("x-test", "yes"))
+            .and(body_string("ping"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("pong"))
+            .mount(&server)
+            .await;
+
+        Mock::given(path("/missing"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    fn request(graph: &mut Graph, weight: HttpWeight, url: String) -> HttpNode {
+        let http = HttpNode::new(graph, weight);
+        let input = http.url(graph).unwrap();
+        input.set_value(graph, url.into());
+        http
+    }
+
+    #[tokio::test]
+    async fn test_http() {
+        let server = server().await;
+        let mut graph = Graph::default();
+
+        let http = request(
+            &mut graph,
+            HttpWeight::default(),
+            format!("{}/echo", server.uri()),
+        );
+
+        let method = http.method(&graph).unwrap();
+        method.set_value(&mut graph, "post".to_string().into());
+        let body = http.body(&graph).unwrap();
+        body.set_value(&mut graph, "ping".to_string().into());
+        let headers = http.headers(&graph).unwrap();
+        headers.set_value(
+            &mut graph,
+            BTreeMap::from([("x-test".to_string(), Value::String("yes".to_string()))]).into(),
+        );
+
+        Executor::execute(&mut graph, http.0).await.unwrap();
+
+        let output = http.output(&graph).unwrap();
+        assert_eq!(
+            graph[output.0].value(),
+            Some(&Value::String("pong".to_string()))
+        );
+        let status = http.status(&graph).unwrap();
+        assert_eq!(graph[status.0].value(), Some(&Value::USize(200)));
+    }
+
+    #[tokio::test]
+    async fn test_http_not_found() {
+        let server = server().await;
+        let url = format!("{}/missing", server.uri());
+
+        let mut graph = Graph::default();
+        let http = request(&mut graph, HttpWeight::default(), url.clone());
+
+        Executor::execute(&mut graph, http.0).await.unwrap();
+
+        let output = http.output(&graph).unwrap();
+        assert_eq!(
+            graph[output.0].value(),
+            Some(&Value::String("not found".to_string()))
+        );
+        let status = http.status(&graph).unwrap();
+        assert_eq!(graph[status.0].value(), Some(&Value::USize(404)));
+
+        let mut graph = Graph::default();
+        let http = request(&mut graph, HttpWeight::default().with_fail_on_error(), url);
+
+        assert!(Executor::execute(&mut graph, http.0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_null_inputs() {
+        let server = server().await;
+
+        let mut graph = Graph::default();
+        let http = request(
+            &mut graph,
+            HttpWeight::default(),
+            format!("{}/missing", server.uri()),
+        );
+
+        // Null inputs are treated as missing.
+        for input in [
+            http.method(&graph).unwrap(),
+            http.body(&graph).unwrap(),
+            http.headers(&graph).unwrap(),
+        ] {
+            input.set_value(&mut graph, Value::Null);
+        }
+
+        Executor::execute(&mut graph, http.0).await.unwrap();
+
+        let status = http.status(&graph).unwrap();
+        assert_eq!(graph[status.0].value(), Some(&Value::USize(404)));
+    }
+}
@@ -0,0 +1,123 @@
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Waits for every incoming execution flow to complete,
+/// then combines its inputs into a single [`Value::Vec`].
+///
+/// Flows that will not be taken, such as from a node that errored,
+/// are not waited for. Their inputs keep their previous values.
+/// If no incoming flow is taken, the join does not run.
+#[derive(Debug, Clone, Copy)]
+pub struct JoinNode(pub NodeIndex);
+
+impl From<JoinNode> for NodeIndex {
+    fn from(value: JoinNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for JoinNode {}
+
+impl JoinNode {
+    pub fn new(graph: &mut Graph, inputs: usize) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(JoinWeight)));
+
+        for i in 0..inputs {
+            let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+            graph.add_edge(input, index, GraphEdge::DataMap(i));
+        }
+
+        let output = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn input(&self, graph: &Graph, index: usize) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, index)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct JoinWeight;
+
+impl SyncNode for JoinWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        Ok(vec![Value::Vec(inputs)])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.join"
+    }
+
+    fn is_join(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::{nodes::CallbackNode, Executor};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_join() {
+        let mut graph = Graph::default();
+
+        let start = CallbackNode::new(&mut graph, |input| input);
+        let a = CallbackNode::new(&mut graph, |_| "a".to_string().into());
+        let b = CallbackNode::new(&mut graph, |_| "b".to_string().into());
+        a.run_after(&mut graph, start.0);
+        b.run_after(&mut graph, start.0);
+
+        let join = JoinNode::new(&mut graph, 2);
+        join.run_after(&mut graph, a.0);
+        join.run_after(&mut graph, b.0);
+
+        let a_output = a.output(&graph).unwrap();
+        join.input(&graph, 0)
+            .unwrap()
+            .set_input(&mut graph, Some(a_output));
+        let b_output = b.output(&graph).unwrap();
+        join.input(&graph, 1)
+            .unwrap()
+            .set_input(&mut graph, Some(b_output));
+
+        let runs = Rc::new(Cell::new(0));
+        let runs_cb = runs.clone();
+
+        let after = CallbackNode::new(&mut graph, move |input| {
+            runs_cb.set(runs_cb.get() + 1);
+            input
+        });
+        after.run_after(&mut graph, join.0);
+
+        let join_output = join.output(&graph).unwrap();
+        after
+            .input(&graph)
+            .unwrap()
+            .set_input(&mut graph, Some(join_output));
+
+        Executor::execute(&mut graph, start.0).await.unwrap();
+
+        assert_eq!(runs.get(), 1);
+
+        match &graph[join_output.0] {
+            GraphNode::Store(value) => assert_eq!(
+                value,
+                &Value::Vec(vec!["a".to_string().into(), "b".to_string().into()])
+            ),
+            _ => panic!(),
+        }
+    }
+}
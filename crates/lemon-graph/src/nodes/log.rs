@@ -34,7 +34,8 @@ impl LogNode {
     }
 }
 
-struct LogWeight;
+#[derive(Default)]
+pub(crate) struct LogWeight;
 
 impl SyncNode for LogWeight {
     fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
@@ -46,6 +47,10 @@ impl SyncNode for LogWeight {
 
         Ok(vec![])
     }
+
+    fn type_name(&self) -> &str {
+        "lemon.log"
+    }
 }
 
 #[cfg(test)]
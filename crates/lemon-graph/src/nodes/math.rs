@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Applies an arithmetic operation to two numbers.
+///
+/// If either input is a [`Value::F32`], the output is a [`Value::F32`].
+/// Otherwise two [`Value::USize`] inputs output a [`Value::USize`] when the result
+/// fits, and any other integers output a [`Value::ISize`].
+/// Integer division truncates toward zero.
+#[derive(Debug, Clone, Copy)]
+pub struct MathNode(pub NodeIndex);
+
+impl From<MathNode> for NodeIndex {
+    fn from(value: MathNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for MathNode {}
+
+impl MathNode {
+    pub fn new(graph: &mut Graph, op: MathOp) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(op)));
+
+        for i in 0..2 {
+            let input = graph.add_node(GraphNode::Store(Value::USize(0)));
+            graph.add_edge(input, index, GraphEdge::DataMap(i));
+        }
+
+        let output = graph.add_node(GraphNode::Store(Value::USize(0)));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    /// The left hand side of the operation.
+    pub fn lhs(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    /// The right hand side of the operation.
+    pub fn rhs(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 1)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathOp {
+    Add,
+    Subtract,
+    Multiply,
+    /// Errors when dividing by zero.
+    Divide,
+}
+
+impl MathOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Subtract => "subtract",
+            Self::Multiply => "multiply",
+            Self::Divide => "divide",
+        }
+    }
+
+    /// Creates an operation from its [`SyncNode::config`].
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        let Value::Map(map) = config else {
+            return Err(NodeError::ConversionError(config.clone()));
+        };
+
+        match map.get("op") {
+            Some(Value::String(op)) => {
+                Self::try_from(op.as_str()).map_err(|_| NodeError::ConversionError(config.clone()))
+            }
+            _ => Err(NodeError::ConversionError(config.clone())),
+        }
+    }
+
+    pub fn apply(&self, lhs: &Value, rhs: &Value) -> Result<Value, NodeError> {
+        match (lhs, rhs) {
+            (Value::F32(_), _) | (_, Value::F32(_)) => self.apply_f32(lhs, rhs),
+            (Value::USize(a), Value::USize(b)) => match self.apply_usize(*a, *b)? {
+                Some(value) => Ok(Value::USize(value)),
+                None => self.apply_isize(lhs, rhs),
+            },
+            _ => self.apply_isize(lhs, rhs),
+        }
+    }
+
+    fn apply_f32(&self, lhs: &Value, rhs: &Value) -> Result<Value, NodeError> {
+        let a = lhs.as_f64()? as f32;
+        let b = rhs.as_f64()? as f32;
+
+        Ok(Value::F32(match self {
+            Self::Add => a + b,
+            Self::Subtract => a - b,
+            Self::Multiply => a * b,
+            Self::Divide if b == 0.0 => return Err(divide_by_zero()),
+            Self::Divide => a / b,
+        }))
+    }
+
+    /// Returns `None` if the result does not fit in a `usize`.
+    fn apply_usize(&self, a: usize, b: usize) -> Result<Option<usize>, NodeError> {
+        Ok(match self {
+            Self::Add => a.checked_add(b),
+            Self::Subtract => a.checked_sub(b),
+            Self::Multiply => a.checked_mul(b),
+            Self::Divide if b == 0 => return Err(divide_by_zero()),
+            Self::Divide => Some(a / b),
+        })
+    }
+
+    fn apply_isize(&self, lhs: &Value, rhs: &Value) -> Result<Value, NodeError> {
+        let a = as_isize(lhs)?;
+        let b = as_isize(rhs)?;
+
+        let value = match self {
+            Self::Add => a.checked_add(b),
+            Self::Subtract => a.checked_sub(b),
+            Self::Multiply => a.checked_mul(b),
+            Self::Divide if b == 0 => return Err(divide_by_zero()),
+            Self::Divide => a.checked_div(b),
+        };
+
+        value
+            .map(Value::ISize)
+            .ok_or(NodeError::InternalError("Integer overflow".to_string()))
+    }
+}
+
+impl TryFrom<&str> for MathOp {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "add" => Ok(Self::Add),
+            "subtract" => Ok(Self::Subtract),
+            "multiply" => Ok(Self::Multiply),
+            "divide" => Ok(Self::Divide),
+            _ => Err(()),
+        }
+    }
+}
+
+fn as_isize(value: &Value) -> Result<isize, NodeError> {
+    match value {
+        Value::ISize(value) => Ok(*value),
+        Value::USize(v) => {
+            isize::try_from(*v).map_err(|_| NodeError::ConversionError(value.clone()))
+        }
+        _ => Err(NodeError::ConversionError(value.clone())),
+    }
+}
+
+fn divide_by_zero() -> NodeError {
+    NodeError::InternalError("Division by zero".to_string())
+}
+
+impl SyncNode for MathOp {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        let lhs = inputs.first().ok_or(NodeError::MissingInput(0))?;
+        let rhs = inputs.get(1).ok_or(NodeError::MissingInput(1))?;
+
+        Ok(vec![self.apply(lhs, rhs)?])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.math"
+    }
+
+    fn config(&self) -> Option<Value> {
+        Some(Value::Map(BTreeMap::from([(
+            "op".to_string(),
+            Value::String(self.as_str().to_string()),
+        )])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Executor;
+
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(
+            MathOp::Add
+                .apply(&Value::USize(2), &Value::USize(3))
+                .unwrap(),
+            Value::USize(5)
+        );
+        assert_eq!(
+            MathOp::Subtract
+                .apply(&Value::USize(2), &Value::USize(3))
+                .unwrap(),
+            Value::ISize(-1)
+        );
+        assert_eq!(
+            MathOp::Multiply
+                .apply(&Value::ISize(-2), &Value::USize(3))
+                .unwrap(),
+            Value::ISize(-6)
+        );
+        assert_eq!(
+            MathOp::Divide
+                .apply(&Value::USize(7), &Value::USize(2))
+                .unwrap(),
+            Value::USize(3)
+        );
+        assert_eq!(
+            MathOp::Add
+                .apply(&Value::USize(1), &Value::F32(0.5))
+                .unwrap(),
+            Value::F32(1.5)
+        );
+    }
+
+    #[test]
+    fn test_apply_errors() {
+        assert!(matches!(
+            MathOp::Divide.apply(&Value::USize(1), &Value::USize(0)),
+            Err(NodeError::InternalError(_))
+        ));
+        assert!(matches!(
+            MathOp::Divide.apply(&Value::F32(1.0), &Value::F32(0.0)),
+            Err(NodeError::InternalError(_))
+        ));
+        assert!(matches!(
+            MathOp::Add.apply(&Value::USize(1), &Value::String("2".to_string())),
+            Err(NodeError::ConversionError(_))
+        ));
+        assert!(matches!(
+            MathOp::Multiply.apply(&Value::ISize(isize::MAX), &Value::ISize(2)),
+            Err(NodeError::InternalError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_composition() {
+        let mut graph = Graph::default();
+
+        // (2 + 3) * 4
+        let add = MathNode::new(&mut graph, MathOp::Add);
+        let lhs = add.lhs(&graph).unwrap();
+        lhs.set_value(&mut graph, Value::USize(2));
+        let rhs = add.rhs(&graph).unwrap();
+        rhs.set_value(&mut graph, Value::USize(3));
+
+        let multiply = MathNode::new(&mut graph, MathOp::Multiply);
+        multiply.run_after(&mut graph, add.0);
+        let sum = add.output(&graph).unwrap();
+        let lhs = multiply.lhs(&graph).unwrap();
+        lhs.set_input(&mut graph, Some(sum));
+        let rhs = multiply.rhs(&graph).unwrap();
+        rhs.set_value(&mut graph, Value::USize(4));
+
+        Executor::execute(&mut graph, add.0).await.unwrap();
+
+        let output = multiply.output(&graph).unwrap();
+        assert_eq!(graph[output.0].value(), Some(&Value::USize(20)));
+    }
+}
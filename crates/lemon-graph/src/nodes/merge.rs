@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Waits for every incoming execution flow to complete,
+/// then collects its inputs into a single [`Value::Vec`] in data index order.
+///
+/// Unlike [`JoinNode`](super::JoinNode), inputs may be removed or left unconnected.
+/// Gaps in the data indices are skipped, unless `require_all` is set,
+/// in which case the first missing index errors.
+#[derive(Debug, Clone, Copy)]
+pub struct MergeNode(pub NodeIndex);
+
+impl From<MergeNode> for NodeIndex {
+    fn from(value: MergeNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for MergeNode {}
+
+impl MergeNode {
+    pub fn new(graph: &mut Graph, inputs: usize, require_all: bool) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(MergeWeight {
+            inputs,
+            require_all,
+        })));
+
+        for i in 0..inputs {
+            let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+            graph.add_edge(input, index, GraphEdge::DataMap(i));
+        }
+
+        let output = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn input(&self, graph: &Graph, index: usize) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, index)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+pub(crate) struct MergeWeight {
+    inputs: usize,
+    require_all: bool,
+}
+
+impl MergeWeight {
+    /// Creates a weight from its [`SyncNode::config`].
+    /// Missing fields default to no inputs, not all required.
+    #[cfg(feature = "serde")]
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        let Value::Map(config) = config else {
+            return Err(NodeError::ConversionError(config.clone()));
+        };
+
+        let mut weight = Self {
+            inputs: 0,
+            require_all: false,
+        };
+
+        for (key, value) in config {
+            match (key.as_str(), value) {
+                ("inputs", Value::USize(inputs)) => weight.inputs = *inputs,
+                ("require_all", Value::Bool(require_all)) => weight.require_all = *require_all,
+                _ => return Err(NodeError::ConversionError(value.clone())),
+            }
+        }
+
+        Ok(weight)
+    }
+}
+
+impl SyncNode for MergeWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        // Gaps before the last input are caught by the executor,
+        // leaving only missing trailing inputs to check.
+        if self.require_all && inputs.len() < self.inputs {
+            return Err(NodeError::MissingInput(inputs.len()));
+        }
+
+        Ok(vec![Value::Vec(inputs)])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.merge"
+    }
+
+    fn config(&self) -> Option<Value> {
+        Some(Value::Map(BTreeMap::from([
+            ("inputs".to_string(), Value::USize(self.inputs)),
+            ("require_all".to_string(), Value::Bool(self.require_all)),
+        ])))
+    }
+
+    fn is_join(&self) -> bool {
+        true
+    }
+
+    fn allows_input_gaps(&self) -> bool {
+        !self.require_all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{execution::ExecutionStepError, nodes::CallbackNode, Executor};
+
+    use super::*;
+
+    /// Merges the outputs of three parallel branches, outputting "a", "b", and "c".
+    /// The inputs at `removed` are disconnected.
+    async fn merge(removed: &[usize], require_all: bool) -> Result<Value, ExecutionStepError> {
+        let mut graph = Graph::default();
+
+        let start = CallbackNode::new(&mut graph, |input| input);
+        let merge = MergeNode::new(&mut graph, 3, require_all);
+
+        for (i, name) in ["a", "b", "c"].into_iter().enumerate() {
+            let branch = CallbackNode::new(&mut graph, move |_| name.to_string().into());
+            branch.run_after(&mut graph, start.0);
+            merge.run_after(&mut graph, branch.0);
+
+            let input = merge.input(&graph, i).unwrap();
+
+            if removed.contains(&i) {
+                let edge = graph.find_edge(input.0, merge.0).unwrap();
+                graph.remove_edge(edge);
+            } else {
+                let output = branch.output(&graph).unwrap();
+                input.set_input(&mut graph, Some(output));
+            }
+        }
+
+        Executor::execute(&mut graph, start.0).await?;
+
+        let output = merge.output(&graph).unwrap();
+        Ok(graph[output.0].value().unwrap().clone())
+    }
+
+    fn strings(values: &[&str]) -> Value {
+        Value::Vec(values.iter().map(|v| v.to_string().into()).collect())
+    }
+
+    #[tokio::test]
+    async fn test_merge() {
+        assert_eq!(merge(&[], false).await.unwrap(), strings(&["a", "b", "c"]));
+        assert_eq!(merge(&[], true).await.unwrap(), strings(&["a", "b", "c"]));
+    }
+
+    #[tokio::test]
+    async fn test_merge_gaps() {
+        assert_eq!(merge(&[1], false).await.unwrap(), strings(&["a", "c"]));
+        assert_eq!(merge(&[0, 2], false).await.unwrap(), strings(&["b"]));
+
+        assert!(matches!(
+            merge(&[1], true).await,
+            Err(ExecutionStepError::NodeError(NodeError::MissingInput(1)))
+        ));
+        assert!(matches!(
+            merge(&[2], true).await,
+            Err(ExecutionStepError::NodeError(NodeError::MissingInput(2)))
+        ));
+    }
+}
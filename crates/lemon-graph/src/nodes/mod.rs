@@ -1,16 +1,75 @@
 use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction};
-use std::future::Future;
+use std::{collections::BTreeMap, future::Future};
 use thiserror::Error;
 
+mod branch;
 mod callback;
+mod chunk;
+mod delay;
+mod env;
+mod extract;
+mod filter;
+mod for_each;
+mod format;
+#[cfg(feature = "http")]
+mod http;
+mod join;
 mod log;
+mod math;
+mod merge;
 mod prompt;
+mod random;
+mod reduce;
+mod similarity;
+mod subgraph;
+mod switch;
 
+pub use branch::BranchNode;
 pub use callback::CallbackNode;
+pub use chunk::{ChunkNode, ChunkStrategy, ChunkWeight, CountFn};
+pub use delay::DelayNode;
+pub use env::{EnvVarNode, EnvVarWeight};
+pub use extract::{ExtractNode, ExtractWeight};
+pub use filter::FilterNode;
+pub use for_each::{ForEachNode, ForEachWeight};
+pub use format::{format_values, FormatNode};
+#[cfg(feature = "http")]
+pub use http::{HttpNode, HttpWeight};
+pub use join::JoinNode;
 pub use log::LogNode;
+pub use math::{MathNode, MathOp};
+pub use merge::MergeNode;
 pub use prompt::PromptNode;
+pub use random::{RandomNode, RandomRange};
+pub use reduce::{ReduceNode, ReduceOp};
+pub use similarity::CosineSimilarityNode;
+pub use subgraph::{SubgraphNode, SubgraphWeight};
+pub use switch::SwitchNode;
 
-use crate::{Graph, GraphEdge, GraphNode, Value};
+#[cfg(feature = "serde")]
+pub(crate) use branch::BranchWeight;
+#[cfg(feature = "serde")]
+pub(crate) use delay::DelayWeight;
+#[cfg(feature = "serde")]
+pub(crate) use filter::FilterWeight;
+#[cfg(feature = "serde")]
+pub(crate) use format::FormatWeight;
+#[cfg(feature = "serde")]
+pub(crate) use join::JoinWeight;
+#[cfg(feature = "serde")]
+pub(crate) use log::LogWeight;
+#[cfg(feature = "serde")]
+pub(crate) use merge::MergeWeight;
+#[cfg(feature = "serde")]
+pub(crate) use prompt::PromptWeight;
+#[cfg(feature = "serde")]
+pub(crate) use random::RandomWeight;
+#[cfg(feature = "serde")]
+pub(crate) use similarity::CosineSimilarityWeight;
+#[cfg(feature = "serde")]
+pub(crate) use switch::SwitchWeight;
+
+use crate::{Condition, Graph, GraphEdge, GraphNode, Value};
 
 #[derive(Debug, Error)]
 pub enum NodeError {
@@ -20,6 +79,62 @@ pub enum NodeError {
     ConversionError(Value),
     #[error("Internal error: {0}")]
     InternalError(String),
+    /// A backend did not respond in time.
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    /// A backend rejected the request due to a rate limit.
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+    /// A backend rejected the request's credentials.
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    /// Any other backend failure.
+    #[error("Backend error: {0}")]
+    Backend(String),
+}
+
+/// An item a node failed on without failing itself, such as a prompt of a batch.
+/// See [`AsyncNode::item_errors_output`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Item {index} failed: {error}")]
+pub struct ItemError {
+    pub index: usize,
+    pub error: String,
+}
+
+impl ItemError {
+    pub fn new(index: usize, error: impl ToString) -> Self {
+        Self {
+            index,
+            error: error.to_string(),
+        }
+    }
+}
+
+/// Stored as a [`Value::Map`] with the `index` of the item and its `error` message.
+impl From<ItemError> for Value {
+    fn from(value: ItemError) -> Self {
+        Value::Map(BTreeMap::from([
+            ("index".to_string(), Value::USize(value.index)),
+            ("error".to_string(), Value::String(value.error)),
+        ]))
+    }
+}
+
+impl TryFrom<Value> for ItemError {
+    type Error = NodeError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        if let Value::Map(map) = &value {
+            if let (Some(Value::USize(index)), Some(Value::String(error))) =
+                (map.get("index"), map.get("error"))
+            {
+                return Ok(Self::new(*index, error));
+            }
+        }
+
+        Err(NodeError::ConversionError(value))
+    }
 }
 
 pub trait AsyncNode {
@@ -27,10 +142,90 @@ pub trait AsyncNode {
         &self,
         inputs: Vec<Value>,
     ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin>;
+
+    /// Name of the node's type, used to identify it when serializing.
+    ///
+    /// Defaults to [`std::any::type_name`], which may change between compiler versions.
+    /// Nodes that are saved should return a fixed name. Built-in nodes use `lemon.*`.
+    fn type_name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Configuration of the node, stored when serializing a graph and passed back
+    /// to the node's constructor when loading it. `None` if there is nothing to store.
+    fn config(&self) -> Option<Value> {
+        None
+    }
+
+    /// Whether the executor should wait for every incoming execution flow
+    /// before running this node.
+    fn is_join(&self) -> bool {
+        false
+    }
+
+    /// Whether the node accepts gaps in its input data indices.
+    /// If so, the inputs that are present are passed in index order.
+    fn allows_input_gaps(&self) -> bool {
+        false
+    }
+
+    /// Whether a [`Value::Null`] input counts as missing.
+    /// If so, the executor fails the node with [`NodeError::MissingInput`]
+    /// instead of running it.
+    fn null_inputs_missing(&self) -> bool {
+        false
+    }
+
+    /// Index of the output holding errors the node recovered from, such as failed
+    /// items of a batch, as a [`Value::Vec`] of [`ItemError`] values.
+    /// The executor reports them in [`RunReport::item_errors`](crate::RunReport::item_errors).
+    fn item_errors_output(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub trait SyncNode {
     fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError>;
+
+    /// Name of the node's type, used to identify it when serializing.
+    ///
+    /// Defaults to [`std::any::type_name`], which may change between compiler versions.
+    /// Nodes that are saved should return a fixed name. Built-in nodes use `lemon.*`.
+    fn type_name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Configuration of the node, stored when serializing a graph and passed back
+    /// to the node's constructor when loading it. `None` if there is nothing to store.
+    fn config(&self) -> Option<Value> {
+        None
+    }
+
+    /// Whether the executor should wait for every incoming execution flow
+    /// before running this node.
+    fn is_join(&self) -> bool {
+        false
+    }
+
+    /// Whether the node accepts gaps in its input data indices.
+    /// If so, the inputs that are present are passed in index order.
+    fn allows_input_gaps(&self) -> bool {
+        false
+    }
+
+    /// Whether a [`Value::Null`] input counts as missing.
+    /// If so, the executor fails the node with [`NodeError::MissingInput`]
+    /// instead of running it.
+    fn null_inputs_missing(&self) -> bool {
+        false
+    }
+
+    /// Index of the output holding errors the node recovered from, such as failed
+    /// items of a batch, as a [`Value::Vec`] of [`ItemError`] values.
+    /// The executor reports them in [`RunReport::item_errors`](crate::RunReport::item_errors).
+    fn item_errors_output(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub trait NodeWrapper: Copy + Into<NodeIndex> {
@@ -47,16 +242,43 @@ pub trait NodeWrapper: Copy + Into<NodeIndex> {
             .map(|edge| StoreWrapper(edge.target()))
     }
 
+    /// Returns the input store at the given data index.
+    fn input_store(self, graph: &Graph, index: usize) -> Result<StoreWrapper, GetStoreError> {
+        graph
+            .edges_directed(self.into(), Direction::Incoming)
+            .find(|edge| matches!(edge.weight(), GraphEdge::DataMap(i) if *i == index))
+            .map(|edge| StoreWrapper(edge.source()))
+            .ok_or(GetStoreError::NoStore)
+    }
+    /// Returns the output store at the given data index.
+    fn output_store(self, graph: &Graph, index: usize) -> Result<StoreWrapper, GetStoreError> {
+        graph
+            .edges_directed(self.into(), Direction::Outgoing)
+            .find(|edge| matches!(edge.weight(), GraphEdge::DataMap(i) if *i == index))
+            .map(|edge| StoreWrapper(edge.target()))
+            .ok_or(GetStoreError::NoStore)
+    }
+
     fn input_execution(self, graph: &Graph) -> impl Iterator<Item = NodeIndex> + '_ {
         graph
             .edges_directed(self.into(), Direction::Incoming)
-            .filter(|edge| matches!(edge.weight(), GraphEdge::ExecutionFlow))
+            .filter(|edge| {
+                matches!(
+                    edge.weight(),
+                    GraphEdge::ExecutionFlow | GraphEdge::ConditionalFlow(_)
+                )
+            })
             .map(|edge| edge.source())
     }
     fn output_execution(self, graph: &Graph) -> impl Iterator<Item = NodeIndex> + '_ {
         graph
             .edges_directed(self.into(), Direction::Outgoing)
-            .filter(|edge| matches!(edge.weight(), GraphEdge::ExecutionFlow))
+            .filter(|edge| {
+                matches!(
+                    edge.weight(),
+                    GraphEdge::ExecutionFlow | GraphEdge::ConditionalFlow(_)
+                )
+            })
             .map(|edge| edge.target())
     }
 
@@ -65,6 +287,18 @@ pub trait NodeWrapper: Copy + Into<NodeIndex> {
         graph.add_edge(node, self.into(), GraphEdge::ExecutionFlow);
     }
 
+    /// Adds a conditional flow from the given node to this node,
+    /// only taken if `condition` passes for the given node's first output.
+    fn run_after_if(self, graph: &mut Graph, node: NodeIndex, condition: Condition) {
+        graph.add_edge(node, self.into(), GraphEdge::ConditionalFlow(condition));
+    }
+
+    /// Adds an error flow from the given node to this node,
+    /// only taken if the given node errors.
+    fn run_on_error(self, graph: &mut Graph, node: NodeIndex) {
+        graph.add_edge(node, self.into(), GraphEdge::ErrorFlow);
+    }
+
     /// Adds an execution flow from this node to the given node.
     fn run_before(self, graph: &mut Graph, node: NodeIndex) {
         graph.add_edge(self.into(), node, GraphEdge::ExecutionFlow);
@@ -75,11 +309,21 @@ pub trait NodeWrapper: Copy + Into<NodeIndex> {
 pub enum GetStoreError {
     #[error("No store found")]
     NoStore,
+    #[error("Node is not a store")]
+    NotStore,
+    #[error("Conversion error, got {0:?}")]
+    ConversionError(Value),
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct StoreWrapper(pub NodeIndex);
 
+impl From<StoreWrapper> for NodeIndex {
+    fn from(value: StoreWrapper) -> Self {
+        value.0
+    }
+}
+
 impl StoreWrapper {
     /// Returns an iterator over any input stores.
     pub fn inputs(self, graph: &Graph) -> impl Iterator<Item = StoreWrapper> + '_ {
@@ -123,7 +367,97 @@ impl StoreWrapper {
 
     /// Sets the default value of the store.
     /// This will be used if no input is set.
+    /// Constants keep being constant, with the new value.
     pub fn set_value(&self, graph: &mut Graph, value: Value) {
-        graph[self.0] = GraphNode::Store(value);
+        graph[self.0] = match graph[self.0] {
+            GraphNode::Constant(_) => GraphNode::Constant(value),
+            _ => GraphNode::Store(value),
+        };
+    }
+
+    /// Returns the current value of the store.
+    pub fn get(&self, graph: &Graph) -> Result<Value, GetStoreError> {
+        graph
+            .node_weight(self.0)
+            .and_then(GraphNode::value)
+            .cloned()
+            .ok_or(GetStoreError::NotStore)
+    }
+
+    /// Sets the value of the store, like [`StoreWrapper::set_value`],
+    /// but errors instead of replacing a node that is not a store.
+    pub fn set(&self, graph: &mut Graph, value: Value) -> Result<(), GetStoreError> {
+        match graph.node_weight_mut(self.0) {
+            Some(GraphNode::Store(store) | GraphNode::Constant(store)) => {
+                *store = value;
+                Ok(())
+            }
+            _ => Err(GetStoreError::NotStore),
+        }
+    }
+
+    /// Returns the value of the store as a string.
+    pub fn get_string(&self, graph: &Graph) -> Result<String, GetStoreError> {
+        match self.get(graph)? {
+            Value::String(value) => Ok(value),
+            value => Err(GetStoreError::ConversionError(value)),
+        }
+    }
+
+    pub fn set_string(
+        &self,
+        graph: &mut Graph,
+        value: impl Into<String>,
+    ) -> Result<(), GetStoreError> {
+        self.set(graph, Value::String(value.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_get_set() {
+        let mut graph = Graph::default();
+        let store = StoreWrapper(graph.add_node(GraphNode::Store(Value::USize(0))));
+
+        store.set(&mut graph, Value::USize(3)).unwrap();
+        assert_eq!(store.get(&graph).unwrap(), Value::USize(3));
+
+        store.set_string(&mut graph, "Hello, world!").unwrap();
+        assert_eq!(store.get_string(&graph).unwrap(), "Hello, world!");
+
+        let constant = StoreWrapper(graph.add_node(GraphNode::Constant(Value::USize(0))));
+        constant.set(&mut graph, Value::USize(1)).unwrap();
+        assert!(matches!(
+            graph[constant.0],
+            GraphNode::Constant(Value::USize(1))
+        ));
+    }
+
+    #[test]
+    fn test_store_conversion_error() {
+        let mut graph = Graph::default();
+        let store = StoreWrapper(graph.add_node(GraphNode::Store(Value::USize(3))));
+
+        assert!(matches!(
+            store.get_string(&graph),
+            Err(GetStoreError::ConversionError(Value::USize(3)))
+        ));
+    }
+
+    #[test]
+    fn test_store_not_store() {
+        let mut graph = Graph::default();
+        let node = LogNode::new(&mut graph);
+        let wrapper = StoreWrapper(node.0);
+
+        assert!(matches!(wrapper.get(&graph), Err(GetStoreError::NotStore)));
+        assert!(matches!(
+            wrapper.set_string(&mut graph, "Hello"),
+            Err(GetStoreError::NotStore)
+        ));
+        assert!(matches!(graph[node.0], GraphNode::SyncNode(_)));
     }
 }
@@ -41,7 +41,8 @@ impl PromptNode {
     }
 }
 
-struct PromptWeight;
+#[derive(Default)]
+pub(crate) struct PromptWeight;
 
 impl SyncNode for PromptWeight {
     fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
@@ -64,4 +65,8 @@ impl SyncNode for PromptWeight {
 
         Ok(vec![output_value.trim().to_string().into()])
     }
+
+    fn type_name(&self) -> &str {
+        "lemon.prompt"
+    }
 }
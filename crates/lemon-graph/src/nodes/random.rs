@@ -0,0 +1,289 @@
+use std::{
+    cell::Cell,
+    collections::{hash_map::RandomState, BTreeMap},
+    hash::{BuildHasher, Hasher},
+};
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Outputs a random number each time it runs.
+///
+/// With a seed, the node produces the same sequence of numbers every time
+/// the graph is built, so runs are reproducible.
+/// The generator is not cryptographically secure.
+#[derive(Debug, Clone, Copy)]
+pub struct RandomNode(pub NodeIndex);
+
+impl From<RandomNode> for NodeIndex {
+    fn from(value: RandomNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for RandomNode {}
+
+impl RandomNode {
+    /// Creates a random node, seeded randomly if `seed` is `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty, or not finite.
+    pub fn new(graph: &mut Graph, range: RandomRange, seed: Option<u64>) -> Self {
+        assert!(range.is_valid(), "invalid random range: {:?}", range);
+
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(RandomWeight::new(
+            range, seed,
+        ))));
+
+        let output = graph.add_node(GraphNode::Store(range.min()));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+/// Range of numbers to generate, including the minimum and excluding the maximum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RandomRange {
+    /// Outputs a [`Value::F32`].
+    Float { min: f32, max: f32 },
+    /// Outputs a [`Value::ISize`].
+    Int { min: isize, max: isize },
+}
+
+impl RandomRange {
+    fn is_valid(&self) -> bool {
+        match self {
+            Self::Float { min, max } => min.is_finite() && max.is_finite() && min < max,
+            Self::Int { min, max } => min < max,
+        }
+    }
+
+    fn min(&self) -> Value {
+        match self {
+            Self::Float { min, .. } => Value::F32(*min),
+            Self::Int { min, .. } => Value::ISize(*min),
+        }
+    }
+
+    /// Maps 64 random bits into the range.
+    fn sample(&self, bits: u64) -> Value {
+        match *self {
+            Self::Float { min, max } => {
+                // The top 24 bits fill the mantissa of a float in [0, 1).
+                let unit = (bits >> 40) as f32 / (1u32 << 24) as f32;
+                let value = min + unit * (max - min);
+
+                // Rounding can reach the maximum for large ranges.
+                Value::F32(if value < max { value } else { min })
+            }
+            Self::Int { min, max } => {
+                let span = (max as i128 - min as i128) as u128;
+                let offset = (bits as u128 * span) >> 64;
+                Value::ISize((min as i128 + offset as i128) as isize)
+            }
+        }
+    }
+}
+
+pub(crate) struct RandomWeight {
+    range: RandomRange,
+    state: Cell<u64>,
+}
+
+impl RandomWeight {
+    fn new(range: RandomRange, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| RandomState::new().build_hasher().finish());
+
+        Self {
+            range,
+            state: Cell::new(seed),
+        }
+    }
+
+    /// Creates a weight from its [`SyncNode::config`].
+    /// The range is required. Without a seed, the weight is seeded randomly.
+    #[cfg(feature = "serde")]
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        let Value::Map(map) = config else {
+            return Err(NodeError::ConversionError(config.clone()));
+        };
+
+        let field = |key: &str| {
+            map.get(key)
+                .ok_or_else(|| NodeError::ConversionError(config.clone()))
+        };
+
+        let range = match field("range")?.as_str()? {
+            "float" => RandomRange::Float {
+                min: field("min")?.as_f64()? as f32,
+                max: field("max")?.as_f64()? as f32,
+            },
+            "int" => RandomRange::Int {
+                min: field("min")?.as_i64()? as isize,
+                max: field("max")?.as_i64()? as isize,
+            },
+            _ => return Err(NodeError::ConversionError(config.clone())),
+        };
+
+        if !range.is_valid() {
+            return Err(NodeError::ConversionError(config.clone()));
+        }
+
+        let seed = match map.get("seed") {
+            Some(Value::String(seed)) => Some(
+                seed.parse()
+                    .map_err(|_| NodeError::ConversionError(config.clone()))?,
+            ),
+            Some(v) => return Err(NodeError::ConversionError(v.clone())),
+            None => None,
+        };
+
+        Ok(Self::new(range, seed))
+    }
+
+    /// SplitMix64, a small generator with good statistical quality.
+    fn next(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        self.state.set(state);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl SyncNode for RandomWeight {
+    fn run(&self, _inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        Ok(vec![self.range.sample(self.next())])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.random"
+    }
+
+    /// The seed is the current state, so a loaded graph continues the sequence.
+    /// It is stored as a string, as it may not fit in a [`Value::USize`].
+    fn config(&self) -> Option<Value> {
+        let (range, min, max) = match self.range {
+            RandomRange::Float { min, max } => ("float", Value::F32(min), Value::F32(max)),
+            RandomRange::Int { min, max } => ("int", Value::ISize(min), Value::ISize(max)),
+        };
+
+        Some(Value::Map(BTreeMap::from([
+            ("range".to_string(), Value::String(range.to_string())),
+            ("min".to_string(), min),
+            ("max".to_string(), max),
+            (
+                "seed".to_string(),
+                Value::String(self.state.get().to_string()),
+            ),
+        ])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Executor;
+
+    use super::*;
+
+    async fn sample(graph: &mut Graph, node: RandomNode, n: usize) -> Vec<Value> {
+        let output = node.output(graph).unwrap();
+        let mut values = Vec::new();
+
+        for _ in 0..n {
+            Executor::execute(graph, node.0).await.unwrap();
+            values.push(output.get(graph).unwrap());
+        }
+
+        values
+    }
+
+    #[tokio::test]
+    async fn test_random_seeded() {
+        let range = RandomRange::Float { min: 0.0, max: 1.0 };
+
+        let mut graph = Graph::default();
+        let a = RandomNode::new(&mut graph, range, Some(42));
+        let b = RandomNode::new(&mut graph, range, Some(42));
+        let c = RandomNode::new(&mut graph, range, Some(7));
+
+        let a = sample(&mut graph, a, 10).await;
+        let b = sample(&mut graph, b, 10).await;
+        let c = sample(&mut graph, c, 10).await;
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        // Values change between runs.
+        assert!(a.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
+    #[tokio::test]
+    async fn test_random_float_range() {
+        let mut graph = Graph::default();
+        let node = RandomNode::new(
+            &mut graph,
+            RandomRange::Float {
+                min: -2.0,
+                max: 3.0,
+            },
+            None,
+        );
+
+        for value in sample(&mut graph, node, 1000).await {
+            match value {
+                Value::F32(value) => assert!((-2.0..3.0).contains(&value), "{}", value),
+                value => panic!("unexpected value: {:?}", value),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_random_int_range() {
+        let mut graph = Graph::default();
+        let node = RandomNode::new(&mut graph, RandomRange::Int { min: -3, max: 3 }, Some(1));
+
+        let values = sample(&mut graph, node, 1000).await;
+
+        for value in &values {
+            match value {
+                Value::ISize(value) => assert!((-3..3).contains(value), "{}", value),
+                value => panic!("unexpected value: {:?}", value),
+            }
+        }
+
+        // Every value in a small range is generated.
+        for i in -3..3 {
+            assert!(values.contains(&Value::ISize(i)));
+        }
+    }
+
+    #[test]
+    fn test_random_int_extremes() {
+        let range = RandomRange::Int {
+            min: isize::MIN,
+            max: isize::MAX,
+        };
+
+        assert_eq!(range.sample(0), Value::ISize(isize::MIN));
+        assert_eq!(range.sample(u64::MAX), Value::ISize(isize::MAX - 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_random_empty_range() {
+        let mut graph = Graph::default();
+        RandomNode::new(&mut graph, RandomRange::Int { min: 1, max: 1 }, None);
+    }
+}
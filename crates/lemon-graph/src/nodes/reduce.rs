@@ -0,0 +1,277 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value, ValueKind};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Combines the elements of a [`Value::Vec`] into a single value.
+#[derive(Debug, Clone, Copy)]
+pub struct ReduceNode(pub NodeIndex);
+
+impl From<ReduceNode> for NodeIndex {
+    fn from(value: ReduceNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for ReduceNode {}
+
+impl ReduceNode {
+    pub fn new(graph: &mut Graph, op: ReduceOp) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(op)));
+
+        let input = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let output = graph.add_node(GraphNode::Store(Value::USize(0)));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+/// Operation of a [`ReduceNode`].
+///
+/// Numeric operations require every element to be the same numeric type,
+/// and output that type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReduceOp {
+    /// Adds the elements. An empty list sums to `Value::USize(0)`.
+    Sum,
+    /// Joins the elements as strings, with a separator between them.
+    Join(String),
+    /// Errors on an empty list.
+    Max,
+    /// Errors on an empty list.
+    Min,
+    /// Outputs the number of elements as a [`Value::USize`].
+    Count,
+}
+
+impl ReduceOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sum => "sum",
+            Self::Join(_) => "join",
+            Self::Max => "max",
+            Self::Min => "min",
+            Self::Count => "count",
+        }
+    }
+
+    /// Creates an operation from its [`SyncNode::config`].
+    /// A join's separator defaults to an empty string.
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        let Value::Map(map) = config else {
+            return Err(NodeError::ConversionError(config.clone()));
+        };
+
+        let op = match map.get("op") {
+            Some(op) => op.as_str()?,
+            None => return Err(NodeError::ConversionError(config.clone())),
+        };
+
+        match op {
+            "sum" => Ok(Self::Sum),
+            "join" => match map.get("separator") {
+                Some(separator) => Ok(Self::Join(separator.as_str()?.to_string())),
+                None => Ok(Self::Join(String::new())),
+            },
+            "max" => Ok(Self::Max),
+            "min" => Ok(Self::Min),
+            "count" => Ok(Self::Count),
+            _ => Err(NodeError::ConversionError(config.clone())),
+        }
+    }
+
+    pub fn apply(&self, items: Vec<Value>) -> Result<Value, NodeError> {
+        match self {
+            Self::Sum => sum(items),
+            Self::Join(separator) => Ok(Value::String(
+                items
+                    .iter()
+                    .map(|item| item.to_string())
+                    .collect::<Vec<_>>()
+                    .join(separator),
+            )),
+            Self::Max => extreme(items, Ordering::Greater),
+            Self::Min => extreme(items, Ordering::Less),
+            Self::Count => Ok(Value::USize(items.len())),
+        }
+    }
+}
+
+/// Checks that every element is a number of the same type.
+fn check_numeric(items: &[Value]) -> Result<(), NodeError> {
+    let kind = match items.first() {
+        Some(item) => item.kind(),
+        None => return Ok(()),
+    };
+
+    if !matches!(kind, ValueKind::F32 | ValueKind::ISize | ValueKind::USize) {
+        return Err(NodeError::ConversionError(items[0].clone()));
+    }
+
+    match items.iter().find(|item| item.kind() != kind) {
+        Some(item) => Err(NodeError::ConversionError(item.clone())),
+        None => Ok(()),
+    }
+}
+
+fn sum(items: Vec<Value>) -> Result<Value, NodeError> {
+    check_numeric(&items)?;
+
+    let overflow = || NodeError::InternalError("Integer overflow".to_string());
+
+    items
+        .into_iter()
+        .try_fold(None, |total, item| {
+            Ok(Some(match (total, item) {
+                (None, item) => item,
+                (Some(Value::F32(a)), Value::F32(b)) => Value::F32(a + b),
+                (Some(Value::ISize(a)), Value::ISize(b)) => {
+                    Value::ISize(a.checked_add(b).ok_or_else(overflow)?)
+                }
+                (Some(Value::USize(a)), Value::USize(b)) => {
+                    Value::USize(a.checked_add(b).ok_or_else(overflow)?)
+                }
+                (_, item) => return Err(NodeError::ConversionError(item)),
+            }))
+        })
+        .map(|total| total.unwrap_or(Value::USize(0)))
+}
+
+/// Returns the element that compares as `ordering` against every other element.
+/// Ties keep the first element.
+fn extreme(items: Vec<Value>, ordering: Ordering) -> Result<Value, NodeError> {
+    check_numeric(&items)?;
+
+    items
+        .into_iter()
+        .reduce(|best, item| {
+            if compare(&item, &best) == ordering {
+                item
+            } else {
+                best
+            }
+        })
+        .ok_or(NodeError::InternalError("Empty list".to_string()))
+}
+
+/// Compares two numbers of the same type.
+fn compare(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::F32(a), Value::F32(b)) => a.total_cmp(b),
+        (Value::ISize(a), Value::ISize(b)) => a.cmp(b),
+        (Value::USize(a), Value::USize(b)) => a.cmp(b),
+        _ => Ordering::Equal,
+    }
+}
+
+impl SyncNode for ReduceOp {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        let items = match inputs.into_iter().next() {
+            Some(Value::Vec(items)) => items,
+            Some(value) => return Err(NodeError::ConversionError(value)),
+            None => return Err(NodeError::MissingInput(0)),
+        };
+
+        Ok(vec![self.apply(items)?])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.reduce"
+    }
+
+    fn config(&self) -> Option<Value> {
+        let mut config =
+            BTreeMap::from([("op".to_string(), Value::String(self.as_str().to_string()))]);
+
+        if let Self::Join(separator) = self {
+            config.insert("separator".to_string(), Value::String(separator.clone()));
+        }
+
+        Some(Value::Map(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Executor;
+
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<Value> {
+        values.iter().map(|v| v.to_string().into()).collect()
+    }
+
+    #[test]
+    fn test_sum() {
+        let items = vec![Value::USize(1), Value::USize(2), Value::USize(3)];
+        assert_eq!(ReduceOp::Sum.apply(items).unwrap(), Value::USize(6));
+
+        let items = vec![Value::ISize(-1), Value::ISize(3)];
+        assert_eq!(ReduceOp::Sum.apply(items).unwrap(), Value::ISize(2));
+
+        assert_eq!(ReduceOp::Sum.apply(Vec::new()).unwrap(), Value::USize(0));
+    }
+
+    #[test]
+    fn test_sum_type_error() {
+        assert!(matches!(
+            ReduceOp::Sum.apply(strings(&["a", "b"])),
+            Err(NodeError::ConversionError(Value::String(_)))
+        ));
+
+        assert!(matches!(
+            ReduceOp::Sum.apply(vec![Value::USize(1), Value::F32(2.0)]),
+            Err(NodeError::ConversionError(Value::F32(_)))
+        ));
+    }
+
+    #[test]
+    fn test_join() {
+        let op = ReduceOp::Join(", ".to_string());
+        assert_eq!(
+            op.apply(strings(&["a", "b", "c"])).unwrap(),
+            Value::String("a, b, c".to_string())
+        );
+        assert_eq!(
+            op.apply(vec![Value::USize(1), Value::Bool(true)]).unwrap(),
+            Value::String("1, true".to_string())
+        );
+    }
+
+    #[test]
+    fn test_max_min() {
+        let items = vec![Value::F32(1.5), Value::F32(-2.0), Value::F32(3.0)];
+        assert_eq!(ReduceOp::Max.apply(items.clone()).unwrap(), Value::F32(3.0));
+        assert_eq!(ReduceOp::Min.apply(items).unwrap(), Value::F32(-2.0));
+
+        assert!(ReduceOp::Max.apply(Vec::new()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reduce_node() {
+        let mut graph = Graph::default();
+
+        let reduce = ReduceNode::new(&mut graph, ReduceOp::Count);
+        let input = reduce.input(&graph).unwrap();
+        input.set_value(&mut graph, Value::Vec(strings(&["a", "b"])));
+
+        Executor::execute(&mut graph, reduce.0).await.unwrap();
+
+        let output = reduce.output(&graph).unwrap();
+        assert_eq!(output.get(&graph).unwrap(), Value::USize(2));
+    }
+}
@@ -0,0 +1,154 @@
+use petgraph::graph::NodeIndex;
+
+use crate::{Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Scores how similar two vectors are, such as embeddings of a query and a document.
+///
+/// Inputs are [`Value::Vec`]s of numbers, and the output is a [`Value::F32`]
+/// from -1 to 1. Errors if the vectors have different lengths.
+/// If either vector is all zeros, the score is 0.
+#[derive(Debug, Clone, Copy)]
+pub struct CosineSimilarityNode(pub NodeIndex);
+
+impl From<CosineSimilarityNode> for NodeIndex {
+    fn from(value: CosineSimilarityNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for CosineSimilarityNode {}
+
+impl CosineSimilarityNode {
+    pub fn new(graph: &mut Graph) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(CosineSimilarityWeight)));
+
+        for i in 0..2 {
+            let input = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+            graph.add_edge(input, index, GraphEdge::DataMap(i));
+        }
+
+        let output = graph.add_node(GraphNode::Store(Value::F32(0.0)));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn a(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn b(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 1)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct CosineSimilarityWeight;
+
+impl SyncNode for CosineSimilarityWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        let a = vector(inputs.first().ok_or(NodeError::MissingInput(0))?)?;
+        let b = vector(inputs.get(1).ok_or(NodeError::MissingInput(1))?)?;
+
+        Ok(vec![Value::F32(cosine_similarity(&a, &b)? as f32)])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.cosine_similarity"
+    }
+}
+
+fn vector(value: &Value) -> Result<Vec<f64>, NodeError> {
+    match value {
+        Value::Vec(items) => items.iter().map(Value::as_f64).collect(),
+        v => Err(NodeError::ConversionError(v.clone())),
+    }
+}
+
+fn cosine_similarity(a: &[f64], b: &[f64]) -> Result<f64, NodeError> {
+    if a.len() != b.len() {
+        return Err(NodeError::InternalError(format!(
+            "Cannot compare vectors of length {} and {}",
+            a.len(),
+            b.len()
+        )));
+    }
+
+    let dot = a.iter().zip(b).map(|(a, b)| a * b).sum::<f64>();
+    let norm_a = a.iter().map(|a| a * a).sum::<f64>().sqrt();
+    let norm_b = b.iter().map(|b| b * b).sum::<f64>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return Ok(0.0);
+    }
+
+    Ok(dot / (norm_a * norm_b))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Executor;
+
+    use super::*;
+
+    fn floats(values: &[f32]) -> Value {
+        Value::Vec(values.iter().copied().map(Value::F32).collect())
+    }
+
+    fn similarity(a: Value, b: Value) -> Result<f32, NodeError> {
+        match CosineSimilarityWeight.run(vec![a, b])?.as_slice() {
+            [Value::F32(score)] => Ok(*score),
+            outputs => panic!("unexpected outputs: {:?}", outputs),
+        }
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        let score = |a, b| similarity(floats(a), floats(b)).unwrap();
+
+        assert!((score(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!((score(&[1.0, 0.0], &[0.0, 2.0])).abs() < 1e-6);
+        assert!((score(&[1.0, 2.0], &[-1.0, -2.0]) + 1.0).abs() < 1e-6);
+        assert!((score(&[1.0, 1.0], &[1.0, 0.0]) - 1.0 / 2f32.sqrt()).abs() < 1e-6);
+        assert_eq!(score(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_cosine_similarity_mismatch() {
+        assert!(matches!(
+            similarity(floats(&[1.0, 0.0]), floats(&[1.0, 0.0, 0.0])),
+            Err(NodeError::InternalError(_))
+        ));
+        assert!(matches!(
+            similarity(floats(&[1.0]), Value::F32(1.0)),
+            Err(NodeError::ConversionError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cosine_similarity_node() {
+        let mut graph = Graph::default();
+        let node = CosineSimilarityNode::new(&mut graph);
+
+        node.a(&graph)
+            .unwrap()
+            .set_value(&mut graph, floats(&[3.0, 4.0]));
+        node.b(&graph).unwrap().set_value(
+            &mut graph,
+            Value::Vec(vec![Value::USize(3), Value::USize(4)]),
+        );
+
+        Executor::execute(&mut graph, node.0).await.unwrap();
+
+        match node.output(&graph).unwrap().get(&graph).unwrap() {
+            Value::F32(score) => assert!((score - 1.0).abs() < 1e-6),
+            value => panic!("unexpected value: {:?}", value),
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use std::{cell::RefCell, future::Future, rc::Rc};
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Executor, Graph, GraphEdge, GraphNode, Value};
+
+use super::{AsyncNode, GetStoreError, NodeError, NodeWrapper, StoreWrapper};
+
+/// Runs an inner graph as a single node, so a common sequence of nodes
+/// can be reused.
+///
+/// Each outer input is written to an inner store before the inner graph runs,
+/// and each outer output is read from an inner store after it finishes.
+/// Subgraphs can be nested.
+#[derive(Debug, Clone, Copy)]
+pub struct SubgraphNode(pub NodeIndex);
+
+impl From<SubgraphNode> for NodeIndex {
+    fn from(value: SubgraphNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for SubgraphNode {}
+
+impl SubgraphNode {
+    /// Creates a subgraph node, with an input and output store for each
+    /// mapped inner store, initialized to the inner store's value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a mapped inner store is not a store.
+    pub fn new(graph: &mut Graph, weight: SubgraphWeight) -> Self {
+        let (inputs, outputs) = {
+            let inner = weight.graph.borrow();
+            let value = |store: &StoreWrapper| {
+                inner[store.0]
+                    .value()
+                    .cloned()
+                    .expect("subgraph store is not a store")
+            };
+
+            (
+                weight.inputs.iter().map(value).collect::<Vec<_>>(),
+                weight.outputs.iter().map(value).collect::<Vec<_>>(),
+            )
+        };
+
+        let index = graph.add_node(GraphNode::AsyncNode(Box::new(weight)));
+
+        for (i, value) in inputs.into_iter().enumerate() {
+            let input = graph.add_node(GraphNode::Store(value));
+            graph.add_edge(input, index, GraphEdge::DataMap(i));
+        }
+
+        for (i, value) in outputs.into_iter().enumerate() {
+            let output = graph.add_node(GraphNode::Store(value));
+            graph.add_edge(index, output, GraphEdge::DataMap(i));
+        }
+
+        Self(index)
+    }
+
+    /// The outer store mapped to the inner input at `index`.
+    pub fn input(&self, graph: &Graph, index: usize) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, index)
+    }
+
+    /// The outer store mapped from the inner output at `index`.
+    pub fn output(&self, graph: &Graph, index: usize) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, index)
+    }
+}
+
+pub struct SubgraphWeight {
+    /// The inner graph, kept between runs.
+    pub graph: Rc<RefCell<Graph>>,
+    /// Node the inner graph is executed from.
+    pub entry: NodeIndex,
+    /// Inner stores each outer input is written to, by data index.
+    pub inputs: Vec<StoreWrapper>,
+    /// Inner stores each outer output is read from, by data index.
+    pub outputs: Vec<StoreWrapper>,
+}
+
+impl SubgraphWeight {
+    pub fn new(graph: Graph, entry: NodeIndex) -> Self {
+        Self {
+            graph: Rc::new(RefCell::new(graph)),
+            entry,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Maps the next outer input to an inner store.
+    pub fn with_input(mut self, store: StoreWrapper) -> Self {
+        self.inputs.push(store);
+        self
+    }
+
+    /// Maps an inner store to the next outer output.
+    pub fn with_output(mut self, store: StoreWrapper) -> Self {
+        self.outputs.push(store);
+        self
+    }
+}
+
+impl AsyncNode for SubgraphWeight {
+    fn run(
+        &self,
+        inputs: Vec<Value>,
+    ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+        let inner = self.graph.clone();
+        let entry = self.entry;
+        let input_stores = self.inputs.clone();
+        let output_stores = self.outputs.clone();
+
+        Box::new(Box::pin(async move {
+            if inputs.len() < input_stores.len() {
+                return Err(NodeError::MissingInput(inputs.len()));
+            }
+
+            // Take the graph out while running, so it is not borrowed across awaits.
+            let mut graph = std::mem::take(&mut *inner.borrow_mut());
+
+            for (store, value) in input_stores.iter().zip(inputs) {
+                store.set_value(&mut graph, value);
+            }
+
+            let res = match Executor::execute(&mut graph, entry).await {
+                Ok(_) => output_stores
+                    .iter()
+                    .map(|store| {
+                        graph[store.0].value().cloned().ok_or_else(|| {
+                            NodeError::InternalError("Output is not a store".to_string())
+                        })
+                    })
+                    .collect(),
+                Err(e) => Err(NodeError::InternalError(format!("Subgraph failed: {}", e))),
+            };
+
+            *inner.borrow_mut() = graph;
+
+            res
+        }))
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.subgraph"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nodes::{CallbackNode, SyncNode};
+
+    use super::*;
+
+    /// Appends a suffix to its input, failing on `"fail"`.
+    struct TestAppend(&'static str);
+
+    impl SyncNode for TestAppend {
+        fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+            match inputs.first() {
+                Some(Value::String(s)) if s == "fail" => {
+                    Err(NodeError::InternalError("failed".to_string()))
+                }
+                Some(Value::String(s)) => Ok(vec![Value::String(format!("{}{}", s, self.0))]),
+                Some(v) => Err(NodeError::ConversionError(v.clone())),
+                None => Err(NodeError::MissingInput(0)),
+            }
+        }
+    }
+
+    fn append(graph: &mut Graph, suffix: &'static str) -> (NodeIndex, StoreWrapper, StoreWrapper) {
+        let node = graph.add_node(GraphNode::SyncNode(Box::new(TestAppend(suffix))));
+        let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(input, node, GraphEdge::DataMap(0));
+        let output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(node, output, GraphEdge::DataMap(0));
+        (node, StoreWrapper(input), StoreWrapper(output))
+    }
+
+    /// An inner graph of two nodes, appending "a" then "b".
+    fn inner() -> SubgraphWeight {
+        let mut graph = Graph::default();
+
+        let (a, a_input, a_output) = append(&mut graph, "a");
+        let (b, b_input, b_output) = append(&mut graph, "b");
+        b_input.set_input(&mut graph, Some(a_output));
+        graph.add_edge(a, b, GraphEdge::ExecutionFlow);
+
+        SubgraphWeight::new(graph, a)
+            .with_input(a_input)
+            .with_output(b_output)
+    }
+
+    async fn run(weight: SubgraphWeight, input: &str) -> Result<Value, String> {
+        let mut graph = Graph::default();
+        let subgraph = SubgraphNode::new(&mut graph, weight);
+
+        let input_store = subgraph.input(&graph, 0).unwrap();
+        input_store.set_value(&mut graph, Value::String(input.to_string()));
+
+        // Reads the subgraph's output in the outer graph.
+        let output = subgraph.output(&graph, 0).unwrap();
+        let after = CallbackNode::new(&mut graph, |input| input);
+        after
+            .input(&graph)
+            .unwrap()
+            .set_input(&mut graph, Some(output));
+        graph.add_edge(subgraph.0, after.0, GraphEdge::ExecutionFlow);
+
+        Executor::execute(&mut graph, subgraph.0)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(after.output(&graph).unwrap().get(&graph).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_subgraph() {
+        let output = run(inner(), "x").await.unwrap();
+        assert_eq!(output, Value::String("xab".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_subgraph_reuse() {
+        let mut graph = Graph::default();
+        let subgraph = SubgraphNode::new(&mut graph, inner());
+        let input = subgraph.input(&graph, 0).unwrap();
+        let output = subgraph.output(&graph, 0).unwrap();
+
+        for value in ["x", "y"] {
+            input.set_value(&mut graph, Value::String(value.to_string()));
+            Executor::execute(&mut graph, subgraph.0).await.unwrap();
+            assert_eq!(
+                output.get(&graph).unwrap(),
+                Value::String(format!("{}ab", value))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subgraph_nested() {
+        let mut graph = Graph::default();
+        let nested = SubgraphNode::new(&mut graph, inner());
+        let input = nested.input(&graph, 0).unwrap();
+        let output = nested.output(&graph, 0).unwrap();
+
+        let weight = SubgraphWeight::new(graph, nested.0)
+            .with_input(input)
+            .with_output(output);
+
+        let output = run(weight, "x").await.unwrap();
+        assert_eq!(output, Value::String("xab".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_subgraph_error() {
+        let err = run(inner(), "fail").await.unwrap_err();
+        assert!(err.contains("Subgraph failed"), "{}", err);
+    }
+}
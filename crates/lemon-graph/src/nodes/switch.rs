@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+
+use petgraph::graph::NodeIndex;
+
+use crate::{Condition, Graph, GraphEdge, GraphNode, Value};
+
+use super::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode};
+
+/// Routes execution to the first case whose value equals the input,
+/// or to the default if no case matches.
+///
+/// Outputs the index of the matched case, or the number of cases if none matched.
+/// Each target is connected with a [`GraphEdge::ConditionalFlow`] on that index,
+/// so only the matched target runs next.
+#[derive(Debug, Clone, Copy)]
+pub struct SwitchNode(pub NodeIndex);
+
+impl From<SwitchNode> for NodeIndex {
+    fn from(value: SwitchNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for SwitchNode {}
+
+impl SwitchNode {
+    pub fn new(
+        graph: &mut Graph,
+        cases: Vec<(Value, NodeIndex)>,
+        default: Option<NodeIndex>,
+    ) -> Self {
+        let (values, targets): (Vec<_>, Vec<_>) = cases.into_iter().unzip();
+        let len = values.len();
+
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(SwitchWeight {
+            cases: values,
+        })));
+
+        let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let output = graph.add_node(GraphNode::Store(Value::USize(len)));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        for (i, target) in targets.into_iter().chain(default).enumerate() {
+            graph.add_edge(
+                index,
+                target,
+                GraphEdge::ConditionalFlow(Condition::Equals(Value::USize(i))),
+            );
+        }
+
+        Self(index)
+    }
+
+    /// The value to match against.
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    /// The index of the matched case.
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+pub(crate) struct SwitchWeight {
+    cases: Vec<Value>,
+}
+
+impl SwitchWeight {
+    /// Creates a weight from its [`SyncNode::config`].
+    #[cfg(feature = "serde")]
+    pub fn from_config(config: &Value) -> Result<Self, NodeError> {
+        match config {
+            Value::Map(map) => match map.get("cases") {
+                Some(Value::Vec(cases)) => Ok(Self {
+                    cases: cases.clone(),
+                }),
+                None => Ok(Self { cases: Vec::new() }),
+                Some(v) => Err(NodeError::ConversionError(v.clone())),
+            },
+            _ => Err(NodeError::ConversionError(config.clone())),
+        }
+    }
+}
+
+impl SyncNode for SwitchWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        let value = inputs.first().ok_or(NodeError::MissingInput(0))?;
+
+        let index = self
+            .cases
+            .iter()
+            .position(|case| case == value)
+            .unwrap_or(self.cases.len());
+
+        Ok(vec![Value::USize(index)])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.switch"
+    }
+
+    fn config(&self) -> Option<Value> {
+        Some(Value::Map(BTreeMap::from([(
+            "cases".to_string(),
+            Value::Vec(self.cases.clone()),
+        )])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{nodes::CallbackNode, Executor};
+
+    use super::*;
+
+    /// Switches on `value` with a case for "a" and "b".
+    /// Returns the names of the targets that ran.
+    async fn switch(value: Value, default: bool) -> Vec<&'static str> {
+        let mut graph = Graph::default();
+        let ran = Rc::new(RefCell::new(Vec::new()));
+
+        let mut target = |name: &'static str| {
+            let ran = ran.clone();
+            CallbackNode::new(&mut graph, move |input| {
+                ran.borrow_mut().push(name);
+                input
+            })
+            .0
+        };
+
+        let a = target("a");
+        let b = target("b");
+        let fallback = target("default");
+
+        let switch = SwitchNode::new(
+            &mut graph,
+            vec![
+                (Value::String("a".to_string()), a),
+                (Value::String("b".to_string()), b),
+            ],
+            default.then_some(fallback),
+        );
+
+        let input = switch.input(&graph).unwrap();
+        input.set_value(&mut graph, value);
+
+        Executor::execute(&mut graph, switch.0).await.unwrap();
+
+        let ran = ran.borrow().clone();
+        ran
+    }
+
+    #[tokio::test]
+    async fn test_switch_match() {
+        assert_eq!(switch(Value::String("a".to_string()), true).await, ["a"]);
+        assert_eq!(switch(Value::String("b".to_string()), true).await, ["b"]);
+    }
+
+    #[tokio::test]
+    async fn test_switch_default() {
+        assert_eq!(
+            switch(Value::String("c".to_string()), true).await,
+            ["default"]
+        );
+        assert_eq!(switch(Value::USize(0), true).await, ["default"]);
+    }
+
+    #[tokio::test]
+    async fn test_switch_no_match() {
+        assert!(switch(Value::String("c".to_string()), false)
+            .await
+            .is_empty());
+    }
+}
@@ -0,0 +1,479 @@
+use std::collections::{BTreeMap, HashMap};
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[cfg(feature = "http")]
+use crate::nodes::HttpWeight;
+use crate::{
+    nodes::{
+        AsyncNode, BranchWeight, ChunkWeight, CosineSimilarityWeight, DelayWeight, EnvVarWeight,
+        ExtractWeight, FilterWeight, FormatWeight, JoinWeight, LogWeight, MathOp, MergeWeight,
+        NodeError, PromptWeight, RandomWeight, ReduceOp, SwitchWeight, SyncNode,
+    },
+    Graph, GraphEdge, GraphLabels, GraphNode, Value,
+};
+
+/// Serializable form of a [`Graph`].
+///
+/// Executable nodes are stored by their type name, see [`SyncNode::type_name`],
+/// and are rebuilt from a [`NodeRegistry`].
+/// A weight's configuration is stored if it provides one, see [`SyncNode::config`].
+/// Node labels are stored, see [`GraphLabels`].
+/// Other node state is not stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializedGraph {
+    pub nodes: Vec<SerializedNode>,
+    /// Edges as `(source, target, weight)`, using indices into `nodes`.
+    pub edges: Vec<(usize, usize, GraphEdge)>,
+    /// Configuration of executable nodes, by index into `nodes`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub configs: BTreeMap<usize, Value>,
+    /// Labels of nodes, by index into `nodes`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<usize, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SerializedNode {
+    AsyncNode(String),
+    SyncNode(String),
+    Store(Value),
+    Constant(Value),
+}
+
+impl From<&Graph> for SerializedGraph {
+    fn from(graph: &Graph) -> Self {
+        let nodes = graph
+            .node_weights()
+            .map(|node| match node {
+                GraphNode::AsyncNode(node) => SerializedNode::AsyncNode(node.type_name().into()),
+                GraphNode::SyncNode(node) => SerializedNode::SyncNode(node.type_name().into()),
+                GraphNode::Store(value) => SerializedNode::Store(value.clone()),
+                GraphNode::Constant(value) => SerializedNode::Constant(value.clone()),
+            })
+            .collect();
+
+        let edges = graph
+            .edge_references()
+            .map(|edge| {
+                (
+                    edge.source().index(),
+                    edge.target().index(),
+                    edge.weight().clone(),
+                )
+            })
+            .collect();
+
+        let configs = graph
+            .node_indices()
+            .filter_map(|index| {
+                let config = match &graph[index] {
+                    GraphNode::AsyncNode(node) => node.config(),
+                    GraphNode::SyncNode(node) => node.config(),
+                    _ => None,
+                };
+                config.map(|config| (index.index(), config))
+            })
+            .collect();
+
+        let labels = graph
+            .node_indices()
+            .filter_map(|index| Some((index.index(), graph.label(index)?.to_string())))
+            .collect();
+
+        Self {
+            nodes,
+            edges,
+            configs,
+            labels,
+        }
+    }
+}
+
+impl SerializedGraph {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DeserializeGraphError {
+    #[error("Unknown node type {0}")]
+    UnknownNode(String),
+    #[error("Edge or label references missing node {0}")]
+    MissingNode(usize),
+    #[error("Invalid config for node type {0}: {1}")]
+    InvalidConfig(String, NodeError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+type NodeConstructor = Box<dyn Fn(Option<&Value>) -> Result<GraphNode, NodeError>>;
+
+/// Constructs executable nodes by type name, when loading a [`SerializedGraph`].
+pub struct NodeRegistry {
+    constructors: HashMap<String, NodeConstructor>,
+}
+
+impl Default for NodeRegistry {
+    /// Registers the built-in nodes, rebuilt from their configuration.
+    ///
+    /// Nodes holding closures or graphs, such as a
+    /// [`CallbackNode`](crate::nodes::CallbackNode), are not registered.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+
+        registry.register_sync::<BranchWeight>();
+        registry.register_sync::<CosineSimilarityWeight>();
+        registry.register_sync::<JoinWeight>();
+        registry.register_sync::<LogWeight>();
+        registry.register_sync::<PromptWeight>();
+
+        registry.register_sync_from_config("lemon.chunk", ChunkWeight::from_config);
+        registry.register_sync_from_config("lemon.env_var", EnvVarWeight::from_config);
+        registry.register_sync_from_config("lemon.extract", ExtractWeight::from_config);
+        registry.register_sync_from_config("lemon.filter", FilterWeight::from_config);
+        registry.register_sync_from_config("lemon.format", FormatWeight::from_config);
+        registry.register_sync_from_config("lemon.math", MathOp::from_config);
+        registry.register_sync_from_config("lemon.merge", MergeWeight::from_config);
+        registry.register_sync_from_config("lemon.random", RandomWeight::from_config);
+        registry.register_sync_from_config("lemon.reduce", ReduceOp::from_config);
+        registry.register_sync_from_config("lemon.switch", SwitchWeight::from_config);
+
+        registry.register_async_from_config("lemon.delay", DelayWeight::from_config);
+        #[cfg(feature = "http")]
+        registry.register_async_from_config("lemon.http", HttpWeight::from_config);
+
+        registry
+    }
+}
+
+impl NodeRegistry {
+    /// Creates a registry with no nodes.
+    pub fn empty() -> Self {
+        Self {
+            constructors: HashMap::new(),
+        }
+    }
+
+    /// Registers a constructor for nodes with the given type name.
+    /// Any stored configuration is ignored, see [`NodeRegistry::register_configured`].
+    pub fn register(&mut self, name: impl Into<String>, f: impl Fn() -> GraphNode + 'static) {
+        self.constructors
+            .insert(name.into(), Box::new(move |_| Ok(f())));
+    }
+
+    /// Registers a constructor that is passed the node's stored configuration,
+    /// or `None` if it had none. See [`SyncNode::config`].
+    pub fn register_configured(
+        &mut self,
+        name: impl Into<String>,
+        f: impl Fn(Option<&Value>) -> Result<GraphNode, NodeError> + 'static,
+    ) {
+        self.constructors.insert(name.into(), Box::new(f));
+    }
+
+    /// Registers an async node, constructed using its default.
+    pub fn register_async<T: AsyncNode + Default + 'static>(&mut self) {
+        let name = T::default().type_name().to_string();
+        self.register(name, || GraphNode::AsyncNode(Box::<T>::default()));
+    }
+
+    /// Registers a sync node, constructed using its default.
+    pub fn register_sync<T: SyncNode + Default + 'static>(&mut self) {
+        let name = T::default().type_name().to_string();
+        self.register(name, || GraphNode::SyncNode(Box::<T>::default()));
+    }
+
+    /// Registers an async node, constructed from its configuration.
+    /// A node stored without a configuration is passed an empty [`Value::Map`].
+    pub fn register_async_from_config<T: AsyncNode + 'static>(
+        &mut self,
+        name: impl Into<String>,
+        from_config: impl Fn(&Value) -> Result<T, NodeError> + 'static,
+    ) {
+        self.register_configured(name, move |config| {
+            let weight = from_config(config.unwrap_or(&Value::Map(BTreeMap::new())))?;
+            Ok(GraphNode::AsyncNode(Box::new(weight)))
+        });
+    }
+
+    /// Registers a sync node, constructed from its configuration.
+    /// A node stored without a configuration is passed an empty [`Value::Map`].
+    pub fn register_sync_from_config<T: SyncNode + 'static>(
+        &mut self,
+        name: impl Into<String>,
+        from_config: impl Fn(&Value) -> Result<T, NodeError> + 'static,
+    ) {
+        self.register_configured(name, move |config| {
+            let weight = from_config(config.unwrap_or(&Value::Map(BTreeMap::new())))?;
+            Ok(GraphNode::SyncNode(Box::new(weight)))
+        });
+    }
+
+    /// Rebuilds a graph, keeping the index of every node.
+    pub fn build(&self, serialized: &SerializedGraph) -> Result<Graph, DeserializeGraphError> {
+        let mut graph = Graph::with_capacity(serialized.nodes.len(), serialized.edges.len());
+
+        for (i, node) in serialized.nodes.iter().enumerate() {
+            let node = match node {
+                SerializedNode::AsyncNode(name) | SerializedNode::SyncNode(name) => {
+                    let constructor = self
+                        .constructors
+                        .get(name)
+                        .ok_or_else(|| DeserializeGraphError::UnknownNode(name.clone()))?;
+                    constructor(serialized.configs.get(&i))
+                        .map_err(|e| DeserializeGraphError::InvalidConfig(name.clone(), e))?
+                }
+                SerializedNode::Store(value) => GraphNode::Store(value.clone()),
+                SerializedNode::Constant(value) => GraphNode::Constant(value.clone()),
+            };
+
+            graph.add_node(node);
+        }
+
+        for (source, target, edge) in &serialized.edges {
+            for index in [source, target] {
+                if *index >= serialized.nodes.len() {
+                    return Err(DeserializeGraphError::MissingNode(*index));
+                }
+            }
+
+            graph.add_edge(
+                NodeIndex::new(*source),
+                NodeIndex::new(*target),
+                edge.clone(),
+            );
+        }
+
+        for (index, label) in &serialized.labels {
+            if *index >= serialized.nodes.len() {
+                return Err(DeserializeGraphError::MissingNode(*index));
+            }
+
+            graph.set_label(NodeIndex::new(*index), label.clone());
+        }
+
+        Ok(graph)
+    }
+
+    /// Loads a graph from JSON, such as from a `.lemon` file.
+    pub fn from_json(&self, json: &str) -> Result<Graph, DeserializeGraphError> {
+        self.build(&SerializedGraph::from_json(json)?)
+    }
+}
+
+/// Saves a graph as JSON.
+/// See [`SerializedGraph`] for what is stored.
+pub fn to_json(graph: &Graph) -> Result<String, serde_json::Error> {
+    SerializedGraph::from(graph).to_json()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        nodes::{
+            BranchNode, ChunkNode, ChunkStrategy, CosineSimilarityNode, DelayNode, EnvVarNode,
+            ExtractNode, FilterNode, FormatNode, JoinNode, LogNode, MathNode, MergeNode,
+            NodeWrapper, PromptNode, RandomNode, RandomRange, ReduceNode, StoreWrapper, SwitchNode,
+        },
+        Condition, Executor, GraphLabels,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut graph = Graph::default();
+
+        let a = LogNode::new(&mut graph);
+        let b = LogNode::new(&mut graph);
+        b.run_after(&mut graph, a.0);
+        b.run_after_if(&mut graph, a.0, Condition::Equals(Value::USize(1)));
+
+        let message = a.message(&graph).unwrap();
+        message.set_value(&mut graph, "Hello!".to_string().into());
+
+        let store = graph.add_node(GraphNode::Store(Value::Vec(vec![
+            Value::Bool(true),
+            Value::F32(0.5),
+        ])));
+        message.set_input(&mut graph, Some(StoreWrapper(store)));
+
+        graph.set_label(a.0, "first");
+
+        let json = to_json(&graph).unwrap();
+        let loaded = NodeRegistry::default().from_json(&json).unwrap();
+
+        assert_eq!(loaded.node_count(), graph.node_count());
+        assert_eq!(loaded.edge_count(), graph.edge_count());
+        assert_eq!(
+            SerializedGraph::from(&loaded),
+            SerializedGraph::from(&graph)
+        );
+
+        match &loaded[message.0] {
+            GraphNode::Store(value) => assert_eq!(value, &Value::String("Hello!".to_string())),
+            _ => panic!(),
+        }
+
+        assert_eq!(loaded.node_by_label("first"), Some(a.0));
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let mut graph = Graph::default();
+
+        let weight = ChunkWeight {
+            chunk_size: 20,
+            overlap: 5,
+            strategy: ChunkStrategy::Sentence,
+            ..Default::default()
+        };
+        let chunk = ChunkNode::new(&mut graph, weight);
+        LogNode::new(&mut graph);
+
+        let serialized = SerializedGraph::from(&graph);
+        assert_eq!(serialized.configs.len(), 1);
+
+        let loaded = NodeRegistry::default()
+            .from_json(&serialized.to_json().unwrap())
+            .unwrap();
+
+        match &loaded[chunk.0] {
+            GraphNode::SyncNode(node) => {
+                assert_eq!(node.config(), serialized.configs.get(&0).cloned())
+            }
+            _ => panic!(),
+        }
+
+        // Constructors registered without a config ignore it.
+        let mut registry = NodeRegistry::default();
+        registry.register_sync::<ChunkWeight>();
+        let loaded = registry.build(&serialized).unwrap();
+        assert_ne!(SerializedGraph::from(&loaded), serialized);
+
+        let mut invalid = serialized;
+        invalid
+            .configs
+            .insert(0, Value::String("large".to_string()));
+        assert!(matches!(
+            NodeRegistry::default().build(&invalid),
+            Err(DeserializeGraphError::InvalidConfig(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_built_in_round_trip() {
+        let mut graph = Graph::default();
+
+        let log = LogNode::new(&mut graph);
+        let cases = vec![(Value::String("a".to_string()), log.0)];
+        SwitchNode::new(&mut graph, cases, Some(log.0));
+        BranchNode::new(&mut graph, Some(log.0), None);
+        MathNode::new(&mut graph, MathOp::Divide);
+        ReduceNode::new(&mut graph, ReduceOp::Join(", ".to_string()));
+        FilterNode::new(&mut graph, Condition::GreaterThan(0.25), true);
+        FormatNode::new(&mut graph, "{0}!", 1);
+        MergeNode::new(&mut graph, 2, true);
+        JoinNode::new(&mut graph, 2);
+        PromptNode::new(&mut graph);
+        CosineSimilarityNode::new(&mut graph);
+        DelayNode::new(&mut graph, Duration::from_millis(1500));
+        RandomNode::new(
+            &mut graph,
+            RandomRange::Int { min: -5, max: 5 },
+            Some(u64::MAX),
+        );
+        ExtractNode::new(
+            &mut graph,
+            ExtractWeight::new("a[0]").with_default(Value::Null),
+        );
+        EnvVarNode::new(
+            &mut graph,
+            EnvVarWeight::new("HOME").with_default("/").with_required(),
+        );
+
+        let serialized = SerializedGraph::from(&graph);
+        let loaded = NodeRegistry::default()
+            .from_json(&serialized.to_json().unwrap())
+            .unwrap();
+
+        assert_eq!(SerializedGraph::from(&loaded), serialized);
+
+        for node in &serialized.nodes {
+            if let SerializedNode::AsyncNode(name) | SerializedNode::SyncNode(name) = node {
+                assert!(name.starts_with("lemon."), "{}", name);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_switch_math_round_trip() {
+        let mut graph = Graph::default();
+
+        let log = LogNode::new(&mut graph);
+        let math = MathNode::new(&mut graph, MathOp::Multiply);
+        let switch = SwitchNode::new(
+            &mut graph,
+            vec![
+                (Value::String("log".to_string()), log.0),
+                (Value::String("math".to_string()), math.0),
+            ],
+            None,
+        );
+
+        let lhs = math.lhs(&graph).unwrap();
+        lhs.set_value(&mut graph, Value::USize(6));
+        let rhs = math.rhs(&graph).unwrap();
+        rhs.set_value(&mut graph, Value::USize(7));
+        let input = switch.input(&graph).unwrap();
+        input.set_value(&mut graph, Value::String("math".to_string()));
+
+        let mut loaded = NodeRegistry::default()
+            .from_json(&to_json(&graph).unwrap())
+            .unwrap();
+
+        Executor::execute(&mut loaded, switch.0).await.unwrap();
+
+        let output = switch.output(&loaded).unwrap();
+        assert_eq!(output.get(&loaded).unwrap(), Value::USize(1));
+        let output = math.output(&loaded).unwrap();
+        assert_eq!(output.get(&loaded).unwrap(), Value::USize(42));
+    }
+
+    #[test]
+    fn test_missing_config() {
+        let mut graph = Graph::default();
+        let filter = FilterNode::new(&mut graph, Condition::Truthy, false);
+
+        let mut serialized = SerializedGraph::from(&graph);
+        serialized.configs.remove(&filter.0.index());
+
+        assert!(matches!(
+            NodeRegistry::default().build(&serialized),
+            Err(DeserializeGraphError::InvalidConfig(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_node() {
+        let mut graph = Graph::default();
+        LogNode::new(&mut graph);
+
+        let json = to_json(&graph).unwrap();
+
+        assert!(matches!(
+            NodeRegistry::empty().from_json(&json),
+            Err(DeserializeGraphError::UnknownNode(_))
+        ));
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+
+use petgraph::{
+    algo::tarjan_scc,
+    graph::NodeIndex,
+    visit::{EdgeFiltered, EdgeRef},
+    Direction,
+};
+use thiserror::Error;
+
+use crate::{Graph, GraphEdge, GraphNode};
+
+#[derive(Debug, Error)]
+pub enum GraphValidationError {
+    #[error("Execution cycle through nodes {0:?}")]
+    ExecutionCycle(Vec<NodeIndex>),
+}
+
+/// A likely mistake in a graph, found by [`validate`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValidationWarning {
+    /// Multiple stores map to the same input of a node.
+    /// Only one of them is read.
+    #[error("Node {node:?} has multiple stores for input {index}")]
+    DuplicateInput { node: NodeIndex, index: usize },
+    /// Multiple stores map to the same output of a node.
+    /// Only one of them is written.
+    #[error("Node {node:?} has multiple stores for output {index}")]
+    DuplicateOutput { node: NodeIndex, index: usize },
+    /// An input store has nothing writing to it, so it always holds its initial value.
+    #[error("Input {index} of node {node:?} is not connected")]
+    UnconnectedInput {
+        node: NodeIndex,
+        index: usize,
+        store: NodeIndex,
+    },
+    /// A node has stores for later outputs, but none for this one,
+    /// so the output is dropped.
+    #[error("Node {node:?} has no store for output {index}")]
+    MissingOutput { node: NodeIndex, index: usize },
+}
+
+/// Checks the graph for mistakes that would otherwise only show at runtime,
+/// such as inputs that are never written.
+/// Unlike [`detect_cycles`], these do not prevent execution.
+///
+/// Stores given a value before execution, with [`StoreWrapper::set_value`](crate::nodes::StoreWrapper::set_value),
+/// are reported as unconnected. Use a [`GraphNode::Constant`] for fixed inputs instead.
+pub fn validate(graph: &Graph) -> Vec<ValidationWarning> {
+    let mut warnings = Vec::new();
+
+    for node in graph.node_indices() {
+        if !matches!(
+            graph[node],
+            GraphNode::AsyncNode(_) | GraphNode::SyncNode(_)
+        ) {
+            continue;
+        }
+
+        let mut inputs = BTreeMap::<usize, Vec<NodeIndex>>::new();
+        let mut outputs = BTreeMap::<usize, Vec<NodeIndex>>::new();
+
+        for edge in graph.edges_directed(node, Direction::Incoming) {
+            if let GraphEdge::DataMap(index) = edge.weight() {
+                inputs.entry(*index).or_default().push(edge.source());
+            }
+        }
+
+        for edge in graph.edges_directed(node, Direction::Outgoing) {
+            if let GraphEdge::DataMap(index) = edge.weight() {
+                outputs.entry(*index).or_default().push(edge.target());
+            }
+        }
+
+        for (&index, stores) in &inputs {
+            if stores.len() > 1 {
+                warnings.push(ValidationWarning::DuplicateInput { node, index });
+            }
+
+            for &store in stores {
+                if matches!(graph[store], GraphNode::Store(_)) && !is_written(graph, store) {
+                    warnings.push(ValidationWarning::UnconnectedInput { node, index, store });
+                }
+            }
+        }
+
+        for (&index, stores) in &outputs {
+            if stores.len() > 1 {
+                warnings.push(ValidationWarning::DuplicateOutput { node, index });
+            }
+        }
+
+        if let Some(&last) = outputs.keys().last() {
+            warnings.extend(
+                (0..last)
+                    .filter(|index| !outputs.contains_key(index))
+                    .map(|index| ValidationWarning::MissingOutput { node, index }),
+            );
+        }
+    }
+
+    warnings
+}
+
+/// Whether a store is written by a node or another store.
+fn is_written(graph: &Graph, store: NodeIndex) -> bool {
+    graph
+        .edges_directed(store, Direction::Incoming)
+        .any(|edge| matches!(edge.weight(), GraphEdge::DataFlow | GraphEdge::DataMap(_)))
+}
+
+/// Checks the graph for cycles of [`GraphEdge::ExecutionFlow`] edges,
+/// which would cause execution to run forever.
+///
+/// Cycles of other edges are allowed, such as a node that reads and writes
+/// the same store, or a loop of [`GraphEdge::ConditionalFlow`] edges that
+/// ends once its condition fails.
+/// On failure, returns every node that is part of a cycle.
+pub fn detect_cycles(graph: &Graph) -> Result<(), GraphValidationError> {
+    let execution = EdgeFiltered::from_fn(&**graph, |edge| {
+        matches!(edge.weight(), GraphEdge::ExecutionFlow)
+    });
+
+    let mut nodes = tarjan_scc(&execution)
+        .into_iter()
+        .filter(|scc| match scc.as_slice() {
+            [node] => graph
+                .edges_connecting(*node, *node)
+                .any(|edge| matches!(edge.weight(), GraphEdge::ExecutionFlow)),
+            _ => true,
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+
+    if nodes.is_empty() {
+        return Ok(());
+    }
+
+    nodes.sort();
+
+    Err(GraphValidationError::ExecutionCycle(nodes))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        nodes::{CallbackNode, LogNode, NodeWrapper, StoreWrapper},
+        Value,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_dag() {
+        let mut graph = Graph::default();
+
+        let a = LogNode::new(&mut graph);
+        let b = LogNode::new(&mut graph);
+        let c = LogNode::new(&mut graph);
+        b.run_after(&mut graph, a.0);
+        c.run_after(&mut graph, a.0);
+        c.run_after(&mut graph, b.0);
+
+        assert!(detect_cycles(&graph).is_ok());
+    }
+
+    #[test]
+    fn test_self_loop() {
+        let mut graph = Graph::default();
+
+        let a = LogNode::new(&mut graph);
+        a.run_after(&mut graph, a.0);
+
+        assert!(matches!(
+            detect_cycles(&graph),
+            Err(GraphValidationError::ExecutionCycle(nodes)) if nodes == vec![a.0]
+        ));
+    }
+
+    #[test]
+    fn test_two_node_cycle() {
+        let mut graph = Graph::default();
+
+        let a = LogNode::new(&mut graph);
+        let b = LogNode::new(&mut graph);
+        let c = LogNode::new(&mut graph);
+        b.run_after(&mut graph, a.0);
+        a.run_after(&mut graph, b.0);
+        c.run_after(&mut graph, b.0);
+
+        assert!(matches!(
+            detect_cycles(&graph),
+            Err(GraphValidationError::ExecutionCycle(nodes)) if nodes == vec![a.0, b.0]
+        ));
+    }
+
+    #[test]
+    fn test_data_cycle() {
+        let mut graph = Graph::default();
+
+        let a = LogNode::new(&mut graph);
+        let message = a.message(&graph).unwrap();
+        graph.add_edge(a.0, message.0, GraphEdge::DataMap(0));
+
+        assert!(detect_cycles(&graph).is_ok());
+    }
+
+    #[test]
+    fn test_validate_clean() {
+        let mut graph = Graph::default();
+
+        let constant = graph.add_node(GraphNode::Constant(Value::String("hi".to_string())));
+        let callback = CallbackNode::new(&mut graph, |input| input);
+        callback
+            .input(&graph)
+            .unwrap()
+            .set_input(&mut graph, Some(StoreWrapper(constant)));
+
+        let log = LogNode::new(&mut graph);
+        log.run_after(&mut graph, callback.0);
+        let output = callback.output(&graph).unwrap();
+        log.message(&graph)
+            .unwrap()
+            .set_input(&mut graph, Some(output));
+
+        assert_eq!(validate(&graph), Vec::new());
+    }
+
+    #[test]
+    fn test_validate_duplicate_input() {
+        let mut graph = Graph::default();
+
+        let log = LogNode::new(&mut graph);
+        let constant = graph.add_node(GraphNode::Constant(Value::String("hi".to_string())));
+        log.message(&graph)
+            .unwrap()
+            .set_input(&mut graph, Some(StoreWrapper(constant)));
+        graph.add_edge(constant, log.0, GraphEdge::DataMap(0));
+
+        assert_eq!(
+            validate(&graph),
+            vec![ValidationWarning::DuplicateInput {
+                node: log.0,
+                index: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_unconnected_input() {
+        let mut graph = Graph::default();
+
+        let log = LogNode::new(&mut graph);
+        let message = log.message(&graph).unwrap();
+
+        assert_eq!(
+            validate(&graph),
+            vec![ValidationWarning::UnconnectedInput {
+                node: log.0,
+                index: 0,
+                store: message.0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_missing_output() {
+        let mut graph = Graph::default();
+
+        let callback = CallbackNode::new(&mut graph, |input| input);
+        let store = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(callback.0, store, GraphEdge::DataMap(2));
+
+        let warnings = validate(&graph);
+        assert!(warnings.contains(&ValidationWarning::MissingOutput {
+            node: callback.0,
+            index: 1
+        }));
+        assert!(!warnings
+            .iter()
+            .any(|warning| matches!(warning, ValidationWarning::MissingOutput { index: 0, .. })));
+    }
+}
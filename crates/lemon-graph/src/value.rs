@@ -1,16 +1,128 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+use crate::nodes::NodeError;
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Value {
     Bool(bool),
     Bytes(Vec<u8>),
     F32(f32),
     ISize(isize),
+    /// Named values, such as structured output.
+    Map(BTreeMap<String, Value>),
+    /// No value, distinct from an empty string.
+    /// Displayed as an empty string.
+    Null,
     String(String),
     USize(usize),
     Vec(Vec<Value>),
 }
 
+/// The variant of a [`Value`], without its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Bool,
+    Bytes,
+    F32,
+    ISize,
+    Map,
+    Null,
+    String,
+    USize,
+    Vec,
+}
+
+impl Value {
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            Value::Bool(_) => ValueKind::Bool,
+            Value::Bytes(_) => ValueKind::Bytes,
+            Value::F32(_) => ValueKind::F32,
+            Value::ISize(_) => ValueKind::ISize,
+            Value::Map(_) => ValueKind::Map,
+            Value::Null => ValueKind::Null,
+            Value::String(_) => ValueKind::String,
+            Value::USize(_) => ValueKind::USize,
+            Value::Vec(_) => ValueKind::Vec,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    fn conversion_error(&self) -> NodeError {
+        NodeError::ConversionError(self.clone())
+    }
+
+    pub fn as_str(&self) -> Result<&str, NodeError> {
+        match self {
+            Value::String(value) => Ok(value),
+            _ => Err(self.conversion_error()),
+        }
+    }
+
+    /// Returns an integer from [`Value::ISize`], or a [`Value::USize`] that fits.
+    pub fn as_i64(&self) -> Result<i64, NodeError> {
+        match self {
+            Value::ISize(value) => Ok(*value as i64),
+            Value::USize(value) => i64::try_from(*value).map_err(|_| self.conversion_error()),
+            _ => Err(self.conversion_error()),
+        }
+    }
+
+    /// Returns a float from any numeric value.
+    pub fn as_f64(&self) -> Result<f64, NodeError> {
+        match self {
+            Value::F32(value) => Ok(*value as f64),
+            Value::ISize(value) => Ok(*value as f64),
+            Value::USize(value) => Ok(*value as f64),
+            _ => Err(self.conversion_error()),
+        }
+    }
+
+    pub fn as_bool(&self) -> Result<bool, NodeError> {
+        match self {
+            Value::Bool(value) => Ok(*value),
+            _ => Err(self.conversion_error()),
+        }
+    }
+
+    /// Converts the value to the given kind, parsing strings if needed,
+    /// such as `"42"` to [`Value::USize`].
+    ///
+    /// Any value can be converted to a [`Value::String`] using its [`Display`] output.
+    /// Numbers are converted if the value fits the target type.
+    pub fn coerce_to(&self, kind: ValueKind) -> Result<Value, NodeError> {
+        if self.kind() == kind {
+            return Ok(self.clone());
+        }
+
+        let coerced = match (self, kind) {
+            (_, ValueKind::String) => Some(Value::String(self.to_string())),
+            (Value::String(value), ValueKind::Bool) => value.trim().parse().ok().map(Value::Bool),
+            (Value::String(value), ValueKind::F32) => value.trim().parse().ok().map(Value::F32),
+            (Value::String(value), ValueKind::ISize) => value.trim().parse().ok().map(Value::ISize),
+            (Value::String(value), ValueKind::USize) => value.trim().parse().ok().map(Value::USize),
+            (Value::ISize(value), ValueKind::USize) => {
+                usize::try_from(*value).ok().map(Value::USize)
+            }
+            (Value::USize(value), ValueKind::ISize) => {
+                isize::try_from(*value).ok().map(Value::ISize)
+            }
+            (Value::ISize(value), ValueKind::F32) => Some(Value::F32(*value as f32)),
+            (Value::USize(value), ValueKind::F32) => Some(Value::F32(*value as f32)),
+            _ => None,
+        };
+
+        coerced.ok_or_else(|| self.conversion_error())
+    }
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -18,6 +130,19 @@ impl Display for Value {
             Value::Bytes(value) => write!(f, "{:?}", value),
             Value::F32(value) => write!(f, "{}", value),
             Value::ISize(value) => write!(f, "{}", value),
+            Value::Map(value) => {
+                write!(f, "{{")?;
+
+                for (i, (key, value)) in value.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+
+                write!(f, "}}")
+            }
+            Value::Null => Ok(()),
             Value::String(value) => write!(f, "{}", value),
             Value::USize(value) => write!(f, "{}", value),
             Value::Vec(value) => write!(f, "{:?}", value),
@@ -49,6 +174,19 @@ impl From<isize> for Value {
     }
 }
 
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(value: BTreeMap<String, Value>) -> Self {
+        Value::Map(value)
+    }
+}
+
+/// `None` is converted to [`Value::Null`].
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
 impl From<String> for Value {
     fn from(value: String) -> Self {
         Value::String(value)
@@ -100,6 +238,22 @@ impl TryFrom<Value> for f32 {
     }
 }
 
+impl TryFrom<Value> for i64 {
+    type Error = ();
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.as_i64().map_err(|_| ())
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = ();
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.as_f64().map_err(|_| ())
+    }
+}
+
 impl TryFrom<Value> for isize {
     type Error = ();
 
@@ -111,6 +265,17 @@ impl TryFrom<Value> for isize {
     }
 }
 
+impl TryFrom<Value> for BTreeMap<String, Value> {
+    type Error = ();
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Map(value) => Ok(value),
+            _ => Err(()),
+        }
+    }
+}
+
 impl TryFrom<Value> for String {
     type Error = ();
 
@@ -143,3 +308,121 @@ impl TryFrom<Value> for Vec<Value> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> BTreeMap<String, Value> {
+        BTreeMap::from([
+            ("name".to_string(), Value::String("Bob".to_string())),
+            ("age".to_string(), Value::USize(42)),
+            ("tags".to_string(), Value::Vec(vec![Value::Bool(true)])),
+        ])
+    }
+
+    #[test]
+    fn test_map_round_trip() {
+        let value = Value::from(map());
+        assert_eq!(value, Value::Map(map()));
+        assert_eq!(BTreeMap::try_from(value), Ok(map()));
+        assert_eq!(BTreeMap::try_from(Value::USize(1)), Err(()));
+    }
+
+    #[test]
+    fn test_map_display() {
+        assert_eq!(
+            Value::from(map()).to_string(),
+            "{age: 42, name: Bob, tags: [Bool(true)]}"
+        );
+        assert_eq!(Value::Map(BTreeMap::new()).to_string(), "{}");
+    }
+
+    #[test]
+    fn test_as() {
+        assert_eq!(Value::String("hi".to_string()).as_str().unwrap(), "hi");
+        assert_eq!(Value::USize(3).as_i64().unwrap(), 3);
+        assert_eq!(Value::ISize(-3).as_i64().unwrap(), -3);
+        assert_eq!(Value::F32(0.5).as_f64().unwrap(), 0.5);
+        assert!(Value::Bool(true).as_bool().unwrap());
+
+        assert!(matches!(
+            Value::USize(3).as_str(),
+            Err(NodeError::ConversionError(Value::USize(3)))
+        ));
+        assert!(Value::USize(usize::MAX).as_i64().is_err());
+        assert!(Value::String("1".to_string()).as_f64().is_err());
+        assert!(Value::USize(1).as_bool().is_err());
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(i64::try_from(Value::ISize(-1)), Ok(-1));
+        assert_eq!(f64::try_from(Value::USize(2)), Ok(2.0));
+        assert_eq!(i64::try_from(Value::F32(1.0)), Err(()));
+    }
+
+    #[test]
+    fn test_coerce() {
+        let string = |s: &str| Value::String(s.to_string());
+
+        assert_eq!(
+            string("42").coerce_to(ValueKind::USize).unwrap(),
+            Value::USize(42)
+        );
+        assert_eq!(
+            string(" -1 ").coerce_to(ValueKind::ISize).unwrap(),
+            Value::ISize(-1)
+        );
+        assert_eq!(
+            string("0.5").coerce_to(ValueKind::F32).unwrap(),
+            Value::F32(0.5)
+        );
+        assert_eq!(
+            string("true").coerce_to(ValueKind::Bool).unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            Value::USize(7).coerce_to(ValueKind::String).unwrap(),
+            string("7")
+        );
+        assert_eq!(
+            Value::USize(7).coerce_to(ValueKind::ISize).unwrap(),
+            Value::ISize(7)
+        );
+
+        assert!(string("forty").coerce_to(ValueKind::USize).is_err());
+        assert!(string("-1").coerce_to(ValueKind::USize).is_err());
+        assert!(Value::ISize(-1).coerce_to(ValueKind::USize).is_err());
+        assert!(Value::Bool(true).coerce_to(ValueKind::Vec).is_err());
+    }
+
+    #[test]
+    fn test_null() {
+        assert_eq!(Value::Null, Value::Null);
+        assert_ne!(Value::Null, Value::String(String::new()));
+        assert_ne!(Value::Null, Value::Bool(false));
+
+        assert!(Value::Null.is_null());
+        assert!(!Value::USize(0).is_null());
+        assert_eq!(Value::Null.kind(), ValueKind::Null);
+
+        assert_eq!(Value::Null.to_string(), "");
+        assert_eq!(
+            Value::Null.coerce_to(ValueKind::String).unwrap(),
+            Value::String(String::new())
+        );
+        assert!(Value::Null.as_str().is_err());
+
+        assert_eq!(Value::from(None::<String>), Value::Null);
+        assert_eq!(Value::from(Some(1usize)), Value::USize(1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_map_serde() {
+        let value = Value::from(map());
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), value);
+    }
+}
@@ -2,13 +2,12 @@ use std::sync::Arc;
 
 use lemon_graph::{
     nodes::{CallbackNode, NodeWrapper, PromptNode},
-    Executor, Value,
+    Executor, Graph, Value,
 };
 use lemon_llm::{
     ollama::{OllamaBackend, OllamaModel},
     LlmNode, LlmWeight,
 };
-use petgraph::Graph;
 
 #[tokio::main]
 async fn main() {
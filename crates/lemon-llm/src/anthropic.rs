@@ -0,0 +1,362 @@
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{GenerateError, GenerateOptions, GenerateOutput, LlmBackend, Pricing, Usage};
+
+const DEFAULT_ANTHROPIC_URL: &str = "https://api.anthropic.com";
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Backend for Anthropic's Messages API.
+pub struct AnthropicBackend {
+    /// URL of the API, without the `/v1` path.
+    pub base_url: String,
+    pub api_key: String,
+    pub model: String,
+    /// Maximum number of tokens to generate, unless overridden by
+    /// [`GenerateOptions::max_tokens`].
+    pub max_tokens: u32,
+    /// Pricing of the model, used by [`LlmBackend::cost_estimate`].
+    pub pricing: Option<Pricing>,
+    /// Whether to cache the system prompt, so repeated requests with the same
+    /// system prompt are cheaper and faster.
+    pub cache_system: bool,
+}
+
+impl AnthropicBackend {
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>, max_tokens: u32) -> Self {
+        Self {
+            base_url: DEFAULT_ANTHROPIC_URL.to_string(),
+            api_key: api_key.into(),
+            model: model.into(),
+            max_tokens,
+            pricing: None,
+            cache_system: false,
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
+    pub fn with_cached_system(mut self) -> Self {
+        self.cache_system = true;
+        self
+    }
+
+    fn request<'a>(&'a self, prompt: &'a str, options: &'a GenerateOptions) -> MessagesRequest<'a> {
+        MessagesRequest {
+            model: &self.model,
+            max_tokens: options.max_tokens.unwrap_or(self.max_tokens),
+            system: options.system.as_deref().map(|text| {
+                if self.cache_system {
+                    System::Blocks(vec![SystemBlock {
+                        kind: "text",
+                        text,
+                        cache_control: CacheControl { kind: "ephemeral" },
+                    }])
+                } else {
+                    System::Text(text)
+                }
+            }),
+            messages: vec![Message {
+                role: "user",
+                content: prompt,
+            }],
+            temperature: options.temperature,
+            top_p: options.top_p,
+            stop_sequences: &options.stop,
+        }
+    }
+}
+
+impl LlmBackend for AnthropicBackend {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.generate_with(prompt, &GenerateOptions::default())
+            .await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        Ok(self.generate_detailed(prompt, options).await?.text)
+    }
+
+    /// Reasoning is read from `thinking` content blocks,
+    /// returned when extended thinking is enabled.
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        let response = reqwest::Client::new()
+            .post(format!(
+                "{}/v1/messages",
+                self.base_url.trim_end_matches('/')
+            ))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&self.request(prompt, options))
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        let status = response.status();
+
+        let text = response
+            .text()
+            .await
+            .map_err(|e| GenerateError::BackendError(e.to_string()))?;
+
+        if !status.is_success() {
+            return Err(GenerateError::from_status(
+                status.as_u16(),
+                format!("{}: {}", status, text),
+            ));
+        }
+
+        let response = serde_json::from_str::<MessagesResponse>(&text)
+            .map_err(|e| GenerateError::BackendError(format!("Invalid response: {}", e)))?;
+
+        let mut output = GenerateOutput {
+            usage: Some(Usage {
+                prompt_tokens: response.usage.input_tokens,
+                completion_tokens: response.usage.output_tokens,
+                cache_read_tokens: response.usage.cache_read_input_tokens.unwrap_or_default(),
+                cache_creation_tokens: response
+                    .usage
+                    .cache_creation_input_tokens
+                    .unwrap_or_default(),
+            }),
+            ..Default::default()
+        };
+
+        for block in response.content {
+            match block {
+                ContentBlock::Text { text } => output.text.push_str(&text),
+                ContentBlock::Thinking { thinking } => output
+                    .reasoning
+                    .get_or_insert_with(String::new)
+                    .push_str(&thinking),
+                ContentBlock::Other => {}
+            }
+        }
+
+        debug!("Anthropic response: {}", output.text);
+
+        Ok(output)
+    }
+
+    fn cost_estimate(&self, usage: &Usage) -> Option<f64> {
+        self.pricing.map(|pricing| pricing.cost(usage))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<System<'a>>,
+    messages: Vec<Message<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop_sequences: &'a [String],
+}
+
+/// The system prompt, as text or as content blocks.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum System<'a> {
+    Text(&'a str),
+    Blocks(Vec<SystemBlock<'a>>),
+}
+
+#[derive(Debug, Serialize)]
+struct SystemBlock<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    text: &'a str,
+    cache_control: CacheControl,
+}
+
+#[derive(Debug, Serialize)]
+struct CacheControl {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+    usage: MessagesUsage,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u32>,
+    #[serde(default)]
+    cache_creation_input_tokens: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        thinking: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{
+        matchers::{body_json, header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_anthropic_backend() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header("x-api-key", "test-key"))
+            .and(header("anthropic-version", ANTHROPIC_VERSION))
+            .and(body_json(json!({
+                "model": "claude-sonnet-4-5",
+                "max_tokens": 256,
+                "system": "Be brief.",
+                "messages": [{ "role": "user", "content": "Hello?" }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "msg_123",
+                "type": "message",
+                "role": "assistant",
+                "model": "claude-sonnet-4-5",
+                "content": [
+                    { "type": "thinking", "thinking": "A greeting.", "signature": "abc" },
+                    { "type": "text", "text": "Hi!" }
+                ],
+                "stop_reason": "end_turn",
+                "usage": { "input_tokens": 10, "output_tokens": 3 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend =
+            AnthropicBackend::new("test-key", "claude-sonnet-4-5", 256).with_base_url(server.uri());
+
+        let options = GenerateOptions {
+            system: Some("Be brief.".to_string()),
+            ..Default::default()
+        };
+
+        let output = backend.generate_detailed("Hello?", &options).await.unwrap();
+
+        assert_eq!(output.text, "Hi!");
+        assert_eq!(output.reasoning.as_deref(), Some("A greeting."));
+        assert_eq!(output.usage.unwrap().total_tokens(), 13);
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_cached_system() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(body_json(json!({
+                "model": "claude-sonnet-4-5",
+                "max_tokens": 256,
+                "system": [{
+                    "type": "text",
+                    "text": "Be brief.",
+                    "cache_control": { "type": "ephemeral" }
+                }],
+                "messages": [{ "role": "user", "content": "Hello?" }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "content": [{ "type": "text", "text": "Hi!" }],
+                "usage": {
+                    "input_tokens": 2,
+                    "output_tokens": 3,
+                    "cache_read_input_tokens": 1000,
+                    "cache_creation_input_tokens": 0
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = AnthropicBackend::new("test-key", "claude-sonnet-4-5", 256)
+            .with_base_url(server.uri())
+            .with_cached_system();
+
+        let options = GenerateOptions {
+            system: Some("Be brief.".to_string()),
+            ..Default::default()
+        };
+
+        let output = backend.generate_detailed("Hello?", &options).await.unwrap();
+
+        assert_eq!(output.text, "Hi!");
+        assert_eq!(
+            output.usage,
+            Some(Usage {
+                prompt_tokens: 2,
+                completion_tokens: 3,
+                cache_read_tokens: 1000,
+                cache_creation_tokens: 0,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_backend_error() {
+        let server = MockServer::start().await;
+
+        Mock::given(path("/v1/messages"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "type": "error",
+                "error": { "type": "invalid_request_error", "message": "max_tokens: too large" }
+            })))
+            .mount(&server)
+            .await;
+
+        let backend = AnthropicBackend::new("test-key", "claude-sonnet-4-5", 1_000_000)
+            .with_base_url(server.uri());
+
+        let res = backend.generate("Hello?").await;
+        assert!(
+            matches!(res, Err(GenerateError::Permanent(e)) if e.contains("max_tokens: too large"))
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures_util::{Stream, StreamExt};
+use tracing::warn;
+
+use crate::{
+    dynamic::{forwarded_stream, send_chunks},
+    DynLlmBackend, GenerateError, GenerateOptions, GenerateOutput, LlmBackend,
+};
+
+/// How long a backend is skipped after it fails, by default.
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Spreads generations across backends by weighted round-robin,
+/// such as multiple servers running the same model.
+///
+/// A backend with weight 2 receives twice as many generations as one with weight 1,
+/// interleaved rather than in bursts. Backends with weight 0 are never used.
+///
+/// Unlike [`FallbackBackend`](crate::FallbackBackend), a failed generation is not
+/// sent to another backend, its error is returned.
+/// Instead, backends that fail with a retryable error are skipped for
+/// [`LoadBalancedBackend::cooldown`], so wrapping this in a
+/// [`RetryBackend`](crate::RetryBackend) retries on a different backend.
+/// If every backend is cooling down, they are all used.
+pub struct LoadBalancedBackend {
+    /// Each backend, with its weight.
+    pub backends: Vec<(Arc<dyn DynLlmBackend>, u32)>,
+    pub cooldown: Duration,
+    state: Mutex<BalancerState>,
+}
+
+#[derive(Default)]
+struct BalancerState {
+    /// Smooth weighted round-robin counters, by backend.
+    current: Vec<i64>,
+    /// When each backend last failed.
+    failed_at: Vec<Option<Instant>>,
+}
+
+impl LoadBalancedBackend {
+    pub fn new(backends: Vec<(Arc<dyn DynLlmBackend>, u32)>) -> Self {
+        Self {
+            backends,
+            cooldown: DEFAULT_COOLDOWN,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Chooses the backend for the next generation.
+    fn next(&self) -> Option<usize> {
+        let mut state = self.state.lock().unwrap();
+        state.current.resize(self.backends.len(), 0);
+        state.failed_at.resize(self.backends.len(), None);
+
+        let weighted = (0..self.backends.len())
+            .filter(|i| self.backends[*i].1 > 0)
+            .collect::<Vec<_>>();
+
+        let available = weighted
+            .iter()
+            .copied()
+            .filter(|i| {
+                state.failed_at[*i].is_none_or(|failed_at| failed_at.elapsed() >= self.cooldown)
+            })
+            .collect::<Vec<_>>();
+
+        let candidates = if available.is_empty() {
+            weighted
+        } else {
+            available
+        };
+
+        let mut total = 0;
+        let mut chosen = None;
+
+        for i in candidates {
+            let weight = i64::from(self.backends[i].1);
+            state.current[i] += weight;
+            total += weight;
+
+            if chosen.is_none_or(|c: usize| state.current[i] > state.current[c]) {
+                chosen = Some(i);
+            }
+        }
+
+        let chosen = chosen?;
+        state.current[chosen] -= total;
+
+        Some(chosen)
+    }
+
+    async fn balanced<'a, R, F: Future<Output = Result<R, GenerateError>> + 'a>(
+        &'a self,
+        f: impl Fn(&'a dyn DynLlmBackend) -> F,
+    ) -> Result<R, GenerateError> {
+        let i = self
+            .next()
+            .ok_or_else(|| GenerateError::Permanent("No backends".to_string()))?;
+
+        let res = f(self.backends[i].0.as_ref()).await;
+
+        if let Err(e) = &res {
+            self.failed(i, e);
+        }
+
+        res
+    }
+
+    /// Starts the cooldown of a backend, if the error is retryable.
+    fn failed(&self, i: usize, e: &GenerateError) {
+        if e.is_retryable() {
+            warn!(
+                "Backend {} failed, skipping it for {:?}: {}",
+                i, self.cooldown, e
+            );
+            self.state.lock().unwrap().failed_at[i] = Some(Instant::now());
+        }
+    }
+}
+
+impl LlmBackend for LoadBalancedBackend {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.balanced(|backend| backend.generate_boxed(prompt))
+            .await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        self.balanced(|backend| backend.generate_with_boxed(prompt, options))
+            .await
+    }
+
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        self.balanced(|backend| backend.generate_detailed_boxed(prompt, options))
+            .await
+    }
+
+    /// A stream that yields a retryable error starts the backend's cooldown.
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Stream<Item = Result<String, GenerateError>> {
+        forwarded_stream(move |tx| async move {
+            let Some(i) = self.next() else {
+                let error = GenerateError::Permanent("No backends".to_string());
+                // The stream is dropped if the receiver is.
+                let _ = tx.send(Err(error)).await;
+                return;
+            };
+
+            let chunks = self.backends[i]
+                .0
+                .generate_stream_boxed(prompt, options)
+                .inspect(|res| {
+                    if let Err(e) = res {
+                        self.failed(i, e);
+                    }
+                });
+
+            send_chunks(chunks, &tx).await;
+        })
+    }
+
+    /// Initializes every backend, succeeding if any of them do.
+    async fn init(&self) -> Result<(), GenerateError> {
+        let mut res = Err(GenerateError::Permanent("No backends".to_string()));
+
+        for (backend, _) in &self.backends {
+            match backend.init_boxed().await {
+                Ok(()) => res = Ok(()),
+                Err(e) if res.is_err() => res = Err(e),
+                Err(e) => warn!("Backend failed to initialize: {}", e),
+            }
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::MockBackend;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_load_balance() {
+        let mocks = [
+            Arc::new(MockBackend::fixed("a")),
+            Arc::new(MockBackend::fixed("b")),
+            Arc::new(MockBackend::fixed("c")),
+        ];
+
+        let backend = LoadBalancedBackend::new(vec![
+            (mocks[0].clone(), 1),
+            (mocks[1].clone(), 2),
+            (mocks[2].clone(), 3),
+        ]);
+
+        let mut responses = Vec::new();
+
+        for _ in 0..60 {
+            responses.push(backend.generate("hi").await.unwrap());
+        }
+
+        let counts = mocks.iter().map(|m| m.prompts().len()).collect::<Vec<_>>();
+        assert_eq!(counts, vec![10, 20, 30]);
+
+        // Calls are interleaved, rather than sent in bursts.
+        assert_eq!(responses[..6].concat(), "cbacbc");
+    }
+
+    #[tokio::test]
+    async fn test_load_balance_cooldown() {
+        let failing = Arc::new(MockBackend::scripted([Err(GenerateError::Transient(
+            "connection refused".to_string(),
+        ))]));
+        let healthy = Arc::new(MockBackend::fixed("ok"));
+
+        let backend = LoadBalancedBackend::new(vec![(failing.clone(), 1), (healthy.clone(), 1)]);
+
+        assert!(backend.generate("hi").await.is_err());
+
+        // The failed backend is skipped until its cooldown ends.
+        for _ in 0..4 {
+            assert_eq!(backend.generate("hi").await.unwrap(), "ok");
+        }
+
+        assert_eq!(failing.prompts().len(), 1);
+        assert_eq!(healthy.prompts().len(), 4);
+
+        let backend = backend.with_cooldown(Duration::ZERO);
+        backend.generate("hi").await.ok();
+        backend.generate("hi").await.ok();
+        assert_eq!(failing.prompts().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_load_balance_stream() {
+        let failing = Arc::new(MockBackend::scripted([Err(GenerateError::Transient(
+            "connection refused".to_string(),
+        ))]));
+        let healthy = Arc::new(MockBackend::fixed("hello there").with_word_stream());
+
+        let backend = LoadBalancedBackend::new(vec![(failing.clone(), 1), (healthy.clone(), 1)]);
+
+        let options = GenerateOptions::default();
+
+        let chunks = backend.generate_stream("hi", &options).collect::<Vec<_>>();
+        assert!(chunks.await[0].is_err());
+
+        // The failed backend is cooling down, and the chunks of the healthy one are kept.
+        for _ in 0..2 {
+            let chunks = backend
+                .generate_stream("hi", &options)
+                .collect::<Vec<_>>()
+                .await;
+            let chunks = chunks.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(chunks, vec!["hello ", "there"]);
+        }
+
+        assert_eq!(failing.prompts().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_load_balance_empty() {
+        let backend = LoadBalancedBackend::new(vec![(Arc::new(MockBackend::fixed("a")), 0)]);
+        assert!(matches!(
+            backend.generate("hi").await,
+            Err(GenerateError::Permanent(_))
+        ));
+    }
+}
@@ -0,0 +1,226 @@
+use std::{future::Future, sync::Arc};
+
+use lemon_graph::{
+    nodes::{AsyncNode, GetStoreError, ItemError, NodeError, NodeWrapper, StoreWrapper},
+    Graph, GraphEdge, GraphNode, Value,
+};
+use petgraph::graph::NodeIndex;
+use tokio::sync::OnceCell;
+
+use crate::{BatchMode, LlmBackend};
+
+/// Generates a response for each prompt of a [`Value::Vec`],
+/// using [`LlmBackend::generate_batch`].
+///
+/// Outputs a [`Value::Vec`] of responses, in the same order as the prompts.
+#[derive(Debug, Clone, Copy)]
+pub struct LlmBatchNode(pub NodeIndex);
+
+impl From<LlmBatchNode> for NodeIndex {
+    fn from(value: LlmBatchNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for LlmBatchNode {}
+
+impl LlmBatchNode {
+    pub fn new<T: LlmBackend + 'static>(graph: &mut Graph, weight: LlmBatchWeight<T>) -> Self {
+        let index = graph.add_node(GraphNode::AsyncNode(Box::new(weight)));
+
+        let input = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let output = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        let errors = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(index, errors, GraphEdge::DataMap(1));
+
+        Self(index)
+    }
+
+    /// The prompts, as a [`Value::Vec`] of [`Value::String`].
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+
+    /// Prompts that failed, with [`BatchMode::CollectAll`], as [`ItemError`] values.
+    pub fn errors(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 1)
+    }
+}
+
+pub struct LlmBatchWeight<T: LlmBackend> {
+    pub backend: Arc<T>,
+    /// How failed prompts are handled.
+    ///
+    /// With [`BatchMode::CollectAll`], failed prompts output [`Value::Null`]
+    /// and are written to [`LlmBatchNode::errors`], and are reported in
+    /// [`RunReport::item_errors`](lemon_graph::RunReport::item_errors).
+    pub mode: BatchMode,
+    initialized: Arc<OnceCell<()>>,
+}
+
+impl<T: LlmBackend> LlmBatchWeight<T> {
+    pub fn new(backend: Arc<T>) -> Self {
+        Self {
+            backend,
+            mode: BatchMode::default(),
+            initialized: Default::default(),
+        }
+    }
+
+    pub fn with_mode(mut self, mode: BatchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+impl<T: LlmBackend + 'static> AsyncNode for LlmBatchWeight<T> {
+    fn run(
+        &self,
+        inputs: Vec<Value>,
+    ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+        let backend = self.backend.clone();
+        let initialized = self.initialized.clone();
+        let mode = self.mode;
+
+        Box::new(Box::pin(async move {
+            let prompts = match inputs.first() {
+                Some(Value::Vec(prompts)) => prompts
+                    .iter()
+                    .map(|prompt| prompt.as_str().map(str::to_string))
+                    .collect::<Result<Vec<_>, _>>()?,
+                Some(v) => return Err(NodeError::ConversionError(v.clone())),
+                None => return Err(NodeError::MissingInput(0)),
+            };
+
+            initialized
+                .get_or_try_init(|| backend.init())
+                .await
+                .map_err(NodeError::from)?;
+
+            let results = backend.generate_batch(&prompts, mode).await?;
+
+            let mut responses = Vec::with_capacity(results.len());
+            let mut errors = Vec::new();
+
+            for (i, res) in results.into_iter().enumerate() {
+                match res {
+                    Ok(response) => responses.push(Value::String(response)),
+                    Err(e) => {
+                        responses.push(Value::Null);
+                        errors.push(ItemError::new(i, e).into());
+                    }
+                }
+            }
+
+            Ok(vec![Value::Vec(responses), Value::Vec(errors)])
+        }))
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.llm_batch"
+    }
+
+    fn item_errors_output(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lemon_graph::{Executor, RunReport};
+
+    use crate::{mock::MockBackend, GenerateError};
+
+    use super::*;
+
+    /// Uppercases prompts, failing if the prompt contains "fail".
+    fn test_backend() -> Arc<MockBackend> {
+        Arc::new(MockBackend::from_fn(|prompt| {
+            if prompt.contains("fail") {
+                Err(GenerateError::BackendError("fail".to_string()))
+            } else {
+                Ok(prompt.to_uppercase())
+            }
+        }))
+    }
+
+    fn strings(values: &[&str]) -> Value {
+        Value::Vec(
+            values
+                .iter()
+                .map(|v| Value::String(v.to_string()))
+                .collect(),
+        )
+    }
+
+    async fn run(
+        weight: LlmBatchWeight<MockBackend>,
+        prompts: &[&str],
+    ) -> (Graph, LlmBatchNode, Result<RunReport, String>) {
+        let mut graph = Graph::default();
+        let batch = LlmBatchNode::new(&mut graph, weight);
+
+        let input = batch.input(&graph).unwrap();
+        input.set_value(&mut graph, strings(prompts));
+
+        let res = Executor::execute(&mut graph, batch.0)
+            .await
+            .map_err(|e| e.to_string());
+
+        (graph, batch, res)
+    }
+
+    #[tokio::test]
+    async fn test_llm_batch() {
+        let backend = test_backend();
+        let (graph, batch, res) = run(LlmBatchWeight::new(backend), &["a", "b", "c"]).await;
+        res.unwrap();
+
+        let output = batch.output(&graph).unwrap();
+        assert_eq!(output.get(&graph).unwrap(), strings(&["A", "B", "C"]));
+    }
+
+    #[tokio::test]
+    async fn test_llm_batch_fail_fast() {
+        let backend = test_backend();
+        let (_, _, res) = run(LlmBatchWeight::new(backend), &["a", "fail", "c"]).await;
+
+        assert!(res.unwrap_err().contains("fail"));
+    }
+
+    #[tokio::test]
+    async fn test_llm_batch_collect_all() {
+        let weight = LlmBatchWeight::new(test_backend()).with_mode(BatchMode::CollectAll);
+        let (graph, batch, res) = run(weight, &["a", "fail", "c"]).await;
+        let report = res.unwrap();
+
+        let output = batch.output(&graph).unwrap();
+        assert_eq!(
+            output.get(&graph).unwrap(),
+            Value::Vec(vec![
+                Value::String("A".to_string()),
+                Value::Null,
+                Value::String("C".to_string()),
+            ])
+        );
+
+        let error = ItemError::new(1, GenerateError::BackendError("fail".to_string()));
+
+        let errors = batch.errors(&graph).unwrap();
+        assert_eq!(
+            errors.get(&graph).unwrap(),
+            Value::Vec(vec![error.clone().into()])
+        );
+
+        assert!(report.is_partial());
+        assert_eq!(report.item_errors, vec![(batch.0, error.to_string())]);
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use crate::{GenerateError, GenerateOptions, GenerateOutput, LlmBackend, Usage};
+
+/// Caches responses in memory, keyed by the prompt and options.
+///
+/// Cache hits do not call the inner backend.
+/// Only successful responses are cached, and streamed generations are not cached.
+pub struct CachingBackend<T: LlmBackend> {
+    pub backend: Arc<T>,
+    cache: Mutex<Cache>,
+}
+
+#[derive(Default)]
+struct Cache {
+    max_entries: Option<usize>,
+    entries: HashMap<String, String>,
+    /// Keys from least to most recently used.
+    order: VecDeque<String>,
+}
+
+impl Cache {
+    fn get(&mut self, key: &str) -> Option<String> {
+        let value = self.entries.get(key)?.clone();
+        self.touch(key);
+        Some(value)
+    }
+
+    fn insert(&mut self, key: String, value: String) {
+        if self.max_entries == Some(0) {
+            return;
+        }
+
+        if self.entries.insert(key.clone(), value).is_some() {
+            self.touch(&key);
+            return;
+        }
+
+        self.order.push_back(key);
+
+        if let Some(max) = self.max_entries {
+            while self.entries.len() > max {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.entries.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &str) {
+        if let Some(i) = self.order.iter().position(|k| k == key) {
+            if let Some(key) = self.order.remove(i) {
+                self.order.push_back(key);
+            }
+        }
+    }
+}
+
+impl<T: LlmBackend> CachingBackend<T> {
+    pub fn new(backend: Arc<T>) -> Self {
+        Self {
+            backend,
+            cache: Mutex::default(),
+        }
+    }
+
+    /// Limits the number of cached responses,
+    /// evicting the least recently used first.
+    pub fn with_max_entries(self, max_entries: usize) -> Self {
+        self.cache.lock().unwrap().max_entries = Some(max_entries);
+        self
+    }
+
+    /// Removes every cached response.
+    pub fn clear(&self) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.entries.clear();
+        cache.order.clear();
+    }
+
+    /// Returns the number of cached responses.
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn key(prompt: &str, options: &GenerateOptions) -> String {
+        format!("{:?}\n{}", options, prompt)
+    }
+}
+
+impl<T: LlmBackend> LlmBackend for CachingBackend<T> {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.generate_with(prompt, &GenerateOptions::default())
+            .await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        let key = Self::key(prompt, options);
+
+        if let Some(text) = self.cache.lock().unwrap().get(&key) {
+            return Ok(text);
+        }
+
+        let text = self.backend.generate_with(prompt, options).await?;
+        self.cache.lock().unwrap().insert(key, text.clone());
+
+        Ok(text)
+    }
+
+    /// Cache hits only return the text, without reasoning or usage,
+    /// as no tokens were used.
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        let key = Self::key(prompt, options);
+
+        if let Some(text) = self.cache.lock().unwrap().get(&key) {
+            return Ok(GenerateOutput {
+                text,
+                ..Default::default()
+            });
+        }
+
+        let output = self.backend.generate_detailed(prompt, options).await?;
+        self.cache.lock().unwrap().insert(key, output.text.clone());
+
+        Ok(output)
+    }
+
+    async fn init(&self) -> Result<(), GenerateError> {
+        self.backend.init().await
+    }
+
+    fn cost_estimate(&self, usage: &Usage) -> Option<f64> {
+        self.backend.cost_estimate(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::MockBackend;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cache_hit() {
+        let mock = Arc::new(MockBackend::fixed("cached"));
+        let backend = CachingBackend::new(mock.clone());
+
+        assert_eq!(backend.generate("a").await.unwrap(), "cached");
+        assert_eq!(backend.generate("a").await.unwrap(), "cached");
+        assert_eq!(mock.prompts(), vec!["a"]);
+
+        let options = GenerateOptions {
+            temperature: Some(0.5),
+            ..Default::default()
+        };
+        backend.generate_with("a", &options).await.unwrap();
+        assert_eq!(mock.prompts().len(), 2);
+
+        backend.clear();
+        assert!(backend.is_empty());
+        backend.generate("a").await.unwrap();
+        assert_eq!(mock.prompts().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_cache_eviction() {
+        let mock = Arc::new(MockBackend::from_fn(|prompt| Ok(prompt.to_string())));
+        let backend = CachingBackend::new(mock.clone()).with_max_entries(2);
+
+        backend.generate("a").await.unwrap();
+        backend.generate("b").await.unwrap();
+        // Uses "a", so "b" is evicted next.
+        backend.generate("a").await.unwrap();
+        backend.generate("c").await.unwrap();
+        assert_eq!(backend.len(), 2);
+
+        backend.generate("a").await.unwrap();
+        backend.generate("b").await.unwrap();
+        assert_eq!(mock.prompts(), vec!["a", "b", "c", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_cache_errors() {
+        let mock = Arc::new(MockBackend::scripted([
+            Err(GenerateError::Transient("busy".to_string())),
+            Ok("ok".to_string()),
+        ]));
+        let backend = CachingBackend::new(mock.clone());
+
+        assert!(backend.generate("a").await.is_err());
+        assert_eq!(backend.generate("a").await.unwrap(), "ok");
+        assert_eq!(backend.generate("a").await.unwrap(), "ok");
+        assert_eq!(mock.prompts().len(), 2);
+    }
+}
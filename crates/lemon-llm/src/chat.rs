@@ -0,0 +1,496 @@
+use std::{fmt::Display, future::Future, sync::Arc};
+
+use lemon_graph::{
+    nodes::{AsyncNode, GetStoreError, NodeError, NodeWrapper, StoreWrapper},
+    Graph, GraphEdge, GraphNode, Value,
+};
+use petgraph::graph::NodeIndex;
+
+use crate::{json::to_value, GenerateError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::User => "user",
+            Self::Assistant => "assistant",
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for Role {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "system" => Ok(Self::System),
+            "user" => Ok(Self::User),
+            "assistant" => Ok(Self::Assistant),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn new(role: Role, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+        }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new(Role::System, content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new(Role::User, content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new(Role::Assistant, content)
+    }
+}
+
+/// Messages are stored as a [`Value::Map`] with `role` and `content` keys.
+impl From<ChatMessage> for Value {
+    fn from(value: ChatMessage) -> Self {
+        Value::Map(
+            [
+                (
+                    "role".to_string(),
+                    Value::String(value.role.as_str().to_string()),
+                ),
+                ("content".to_string(), Value::String(value.content)),
+            ]
+            .into(),
+        )
+    }
+}
+
+impl TryFrom<Value> for ChatMessage {
+    type Error = NodeError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        if let Value::Map(map) = &value {
+            if let (Some(Value::String(role)), Some(Value::String(content))) =
+                (map.get("role"), map.get("content"))
+            {
+                if let Ok(role) = Role::try_from(role.as_str()) {
+                    return Ok(Self::new(role, content.clone()));
+                }
+            }
+        }
+
+        Err(NodeError::ConversionError(value))
+    }
+}
+
+/// A function the model may request to call, see [`LlmChatBackend::generate_with_tools`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tool {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema of the tool's arguments.
+    pub parameters_schema: serde_json::Value,
+}
+
+impl Tool {
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters_schema: serde_json::Value,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters_schema,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolResponse {
+    Text(String),
+    /// A request to call a tool, to be executed by the caller.
+    ToolCall {
+        name: String,
+        arguments: Value,
+    },
+}
+
+impl ToolResponse {
+    /// Creates a tool call from JSON arguments, as returned by most APIs.
+    /// Fails if the arguments cannot be converted to a [`Value`].
+    pub fn tool_call(
+        name: impl Into<String>,
+        arguments: serde_json::Value,
+    ) -> Result<Self, GenerateError> {
+        let arguments = to_value(arguments).ok_or(GenerateError::BackendError(
+            "Invalid tool call arguments".to_string(),
+        ))?;
+
+        Ok(Self::ToolCall {
+            name: name.into(),
+            arguments,
+        })
+    }
+}
+
+pub trait LlmChatBackend {
+    /// Generates the next assistant message of a conversation.
+    fn generate_chat(
+        &self,
+        messages: &[ChatMessage],
+    ) -> impl Future<Output = Result<String, GenerateError>>;
+
+    /// Generates the next assistant message, letting the model request a call to one of `tools`.
+    ///
+    /// By default, tools are ignored and [`LlmChatBackend::generate_chat`] is called.
+    fn generate_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        _tools: &[Tool],
+    ) -> impl Future<Output = Result<ToolResponse, GenerateError>> {
+        async move { Ok(ToolResponse::Text(self.generate_chat(messages).await?)) }
+    }
+}
+
+/// Multi-turn chat, remembering earlier messages across executions.
+///
+/// The history is kept in a single store, used as both an input and an output,
+/// holding a [`Value::Vec`] of messages.
+/// Each run appends the new user message and the assistant's reply.
+#[derive(Debug, Clone, Copy)]
+pub struct ChatNode(pub NodeIndex);
+
+impl From<ChatNode> for NodeIndex {
+    fn from(value: ChatNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for ChatNode {}
+
+impl ChatNode {
+    pub fn new<T: LlmChatBackend + 'static>(graph: &mut Graph, weight: ChatWeight<T>) -> Self {
+        let index = graph.add_node(GraphNode::AsyncNode(Box::new(weight)));
+
+        let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let history = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(history, index, GraphEdge::DataMap(1));
+        graph.add_edge(index, history, GraphEdge::DataMap(1));
+
+        let output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    /// The user message.
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    /// The assistant's reply.
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+
+    /// The conversation history.
+    /// Setting its value replaces the history, such as to start with a system message.
+    pub fn history(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 1)
+    }
+}
+
+pub struct ChatWeight<T: LlmChatBackend> {
+    pub backend: Arc<T>,
+    /// Maximum number of messages to keep, dropping the oldest first.
+    /// System messages at the start of the history are always kept, and not counted.
+    /// The newest message is also always kept, so the user message is sent
+    /// even with a maximum of 0.
+    pub max_messages: Option<usize>,
+}
+
+impl<T: LlmChatBackend> ChatWeight<T> {
+    pub fn new(backend: Arc<T>) -> Self {
+        Self {
+            backend,
+            max_messages: None,
+        }
+    }
+
+    pub fn with_max_messages(mut self, max_messages: usize) -> Self {
+        self.max_messages = Some(max_messages);
+        self
+    }
+}
+
+fn trim(history: &mut Vec<ChatMessage>, max_messages: Option<usize>) {
+    if let Some(max) = max_messages {
+        let system = history
+            .iter()
+            .take_while(|message| message.role == Role::System)
+            .count();
+        let excess = (history.len() - system).saturating_sub(max.max(1));
+        history.drain(system..system + excess);
+    }
+}
+
+impl<T: LlmChatBackend + 'static> AsyncNode for ChatWeight<T> {
+    fn run(
+        &self,
+        inputs: Vec<Value>,
+    ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+        let backend = self.backend.clone();
+        let max_messages = self.max_messages;
+
+        Box::new(Box::pin(async move {
+            let message = match inputs.first() {
+                Some(Value::String(message)) => message.clone(),
+                Some(v) => return Err(NodeError::ConversionError(v.clone())),
+                None => return Err(NodeError::MissingInput(0)),
+            };
+
+            let mut history = match inputs.get(1) {
+                Some(Value::Vec(values)) => values
+                    .iter()
+                    .map(|value| ChatMessage::try_from(value.clone()))
+                    .collect::<Result<Vec<_>, _>>()?,
+                Some(v) => return Err(NodeError::ConversionError(v.clone())),
+                None => return Err(NodeError::MissingInput(1)),
+            };
+
+            history.push(ChatMessage::user(message));
+            trim(&mut history, max_messages);
+
+            let reply = backend.generate_chat(&history).await?;
+
+            history.push(ChatMessage::assistant(reply.clone()));
+            trim(&mut history, max_messages);
+
+            Ok(vec![
+                Value::String(reply),
+                Value::Vec(history.into_iter().map(Value::from).collect()),
+            ])
+        }))
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.chat"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lemon_graph::Executor;
+
+    use crate::mock::MockBackend;
+
+    use super::*;
+
+    fn history(graph: &Graph, chat: ChatNode) -> Vec<ChatMessage> {
+        let history = chat.history(graph).unwrap();
+        match &graph[history.0] {
+            GraphNode::Store(Value::Vec(values)) => values
+                .iter()
+                .map(|value| ChatMessage::try_from(value.clone()).unwrap())
+                .collect(),
+            _ => panic!(),
+        }
+    }
+
+    async fn send(graph: &mut Graph, chat: ChatNode, message: &str) {
+        let input = chat.input(graph).unwrap();
+        input.set_value(graph, message.to_string().into());
+        Executor::execute(graph, chat.0).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_chat_history() {
+        let mut graph = Graph::default();
+
+        let backend = Arc::new(MockBackend::from_fn(|prompt| Ok(prompt.to_uppercase())));
+        let chat = ChatNode::new(&mut graph, ChatWeight::new(backend));
+
+        assert!(history(&graph, chat).is_empty());
+
+        send(&mut graph, chat, "hello").await;
+        send(&mut graph, chat, "again").await;
+
+        assert_eq!(
+            history(&graph, chat),
+            vec![
+                ChatMessage::user("hello"),
+                ChatMessage::assistant("HELLO"),
+                ChatMessage::user("again"),
+                ChatMessage::assistant("AGAIN"),
+            ]
+        );
+
+        let output = chat.output(&graph).unwrap();
+        match &graph[output.0] {
+            GraphNode::Store(value) => assert_eq!(value, &Value::String("AGAIN".to_string())),
+            _ => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_max_messages() {
+        let mut graph = Graph::default();
+
+        let backend = Arc::new(MockBackend::fixed("ok"));
+        let chat = ChatNode::new(&mut graph, ChatWeight::new(backend).with_max_messages(3));
+
+        send(&mut graph, chat, "one").await;
+        send(&mut graph, chat, "two").await;
+
+        assert_eq!(
+            history(&graph, chat),
+            vec![
+                ChatMessage::assistant("ok"),
+                ChatMessage::user("two"),
+                ChatMessage::assistant("ok"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_max_messages_zero() {
+        let mut graph = Graph::default();
+
+        let backend = Arc::new(MockBackend::from_fn(|prompt| Ok(prompt.to_uppercase())));
+        let chat = ChatNode::new(&mut graph, ChatWeight::new(backend).with_max_messages(0));
+
+        send(&mut graph, chat, "one").await;
+        send(&mut graph, chat, "two").await;
+
+        // The user message is still sent, and only the reply is kept.
+        assert_eq!(history(&graph, chat), vec![ChatMessage::assistant("TWO")]);
+    }
+
+    #[tokio::test]
+    async fn test_chat_max_messages_system() {
+        let mut graph = Graph::default();
+
+        let backend = Arc::new(MockBackend::fixed("ok"));
+        let chat = ChatNode::new(&mut graph, ChatWeight::new(backend).with_max_messages(2));
+
+        let history_store = chat.history(&graph).unwrap();
+        history_store.set_value(
+            &mut graph,
+            Value::Vec(vec![ChatMessage::system("Be brief.").into()]),
+        );
+
+        send(&mut graph, chat, "one").await;
+        send(&mut graph, chat, "two").await;
+
+        assert_eq!(
+            history(&graph, chat),
+            vec![
+                ChatMessage::system("Be brief."),
+                ChatMessage::user("two"),
+                ChatMessage::assistant("ok"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_call() {
+        let backend = MockBackend::scripted([
+            Ok(r#"{"name": "get_weather", "arguments": {"city": "Paris", "days": 3}}"#.to_string()),
+            Ok(r#"{"name": "unknown", "arguments": {}}"#.to_string()),
+        ]);
+
+        let tools = [Tool::new(
+            "get_weather",
+            "Gets the weather forecast for a city.",
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "city": { "type": "string" },
+                    "days": { "type": "integer" }
+                },
+                "required": ["city"]
+            }),
+        )];
+
+        let messages = [ChatMessage::user("What's the weather in Paris?")];
+
+        assert_eq!(
+            backend
+                .generate_with_tools(&messages, &tools)
+                .await
+                .unwrap(),
+            ToolResponse::ToolCall {
+                name: "get_weather".to_string(),
+                arguments: Value::Map(
+                    [
+                        ("city".to_string(), Value::String("Paris".to_string())),
+                        ("days".to_string(), Value::USize(3)),
+                    ]
+                    .into()
+                ),
+            }
+        );
+
+        // Calls to tools that were not offered are plain text.
+        assert!(matches!(
+            backend
+                .generate_with_tools(&messages, &tools)
+                .await
+                .unwrap(),
+            ToolResponse::Text(_)
+        ));
+    }
+
+    #[test]
+    fn test_message_value() {
+        let message = ChatMessage::system("Be brief.");
+        let value = Value::from(message.clone());
+
+        assert!(matches!(&value, Value::Map(map) if map.len() == 2));
+        assert_eq!(ChatMessage::try_from(value).unwrap(), message);
+
+        let value = Value::String("hi".to_string());
+        assert!(matches!(
+            ChatMessage::try_from(value.clone()),
+            Err(NodeError::ConversionError(v)) if v == value
+        ));
+
+        // Unknown roles are invalid.
+        let value = Value::Map(
+            [
+                ("role".to_string(), Value::String("tool".to_string())),
+                ("content".to_string(), Value::String("hi".to_string())),
+            ]
+            .into(),
+        );
+        assert!(matches!(
+            ChatMessage::try_from(value.clone()),
+            Err(NodeError::ConversionError(v)) if v == value
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use futures_util::{
+    future::{LocalBoxFuture, Shared},
+    FutureExt,
+};
+
+use crate::{GenerateError, GenerateOptions, GenerateOutput, LlmBackend, Usage};
+
+type InFlight = Shared<LocalBoxFuture<'static, Result<GenerateOutput, GenerateError>>>;
+
+/// Coalesces identical generations that run at the same time into a single request,
+/// keyed by the prompt and options.
+///
+/// Unlike [`CachingBackend`](crate::CachingBackend), nothing is kept once a
+/// request finishes, so a later identical prompt makes a new request.
+/// Every caller receives the same result, including errors.
+/// Streamed generations are not coalesced.
+pub struct CoalescingBackend<T: LlmBackend> {
+    pub backend: Arc<T>,
+    in_flight: Arc<Mutex<HashMap<String, InFlight>>>,
+}
+
+impl<T: LlmBackend + 'static> CoalescingBackend<T> {
+    pub fn new(backend: Arc<T>) -> Self {
+        Self {
+            backend,
+            in_flight: Arc::default(),
+        }
+    }
+
+    /// Returns the number of requests in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap().len()
+    }
+
+    fn key(prompt: &str, options: &GenerateOptions) -> String {
+        format!("{:?}\n{}", options, prompt)
+    }
+
+    fn request(&self, prompt: &str, options: &GenerateOptions) -> InFlight {
+        let key = Self::key(prompt, options);
+        let mut in_flight = self.in_flight.lock().unwrap();
+
+        if let Some(request) = in_flight.get(&key) {
+            return request.clone();
+        }
+
+        let backend = self.backend.clone();
+        let map = self.in_flight.clone();
+        let prompt = prompt.to_string();
+        let options = options.clone();
+        let entry = key.clone();
+
+        // The entry is removed by the request itself, so it is removed once it
+        // resolves no matter which caller polls it.
+        let request = async move {
+            let res = backend.generate_detailed(&prompt, &options).await;
+            map.lock().unwrap().remove(&entry);
+            res
+        }
+        .boxed_local()
+        .shared();
+
+        in_flight.insert(key, request.clone());
+        request
+    }
+}
+
+impl<T: LlmBackend + 'static> LlmBackend for CoalescingBackend<T> {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.generate_with(prompt, &GenerateOptions::default())
+            .await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        self.request(prompt, options)
+            .await
+            .map(|output| output.text)
+    }
+
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        self.request(prompt, options).await
+    }
+
+    async fn init(&self) -> Result<(), GenerateError> {
+        self.backend.init().await
+    }
+
+    fn cost_estimate(&self, usage: &Usage) -> Option<f64> {
+        self.backend.cost_estimate(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::mock::MockBackend;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_coalesce() {
+        let mock = Arc::new(MockBackend::fixed("ok").with_delay(Duration::from_millis(20)));
+        let backend = CoalescingBackend::new(mock.clone());
+
+        let (a, b) = tokio::join!(backend.generate("a"), backend.generate("a"));
+        assert_eq!(a.unwrap(), "ok");
+        assert_eq!(b.unwrap(), "ok");
+        assert_eq!(mock.prompts(), vec!["a"]);
+        assert_eq!(backend.in_flight(), 0);
+
+        // Finished requests are not reused.
+        backend.generate("a").await.unwrap();
+        assert_eq!(mock.prompts().len(), 2);
+
+        // Different prompts are not coalesced.
+        let (a, b) = tokio::join!(backend.generate("a"), backend.generate("b"));
+        assert!(a.is_ok() && b.is_ok());
+        assert_eq!(mock.prompts().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_error() {
+        let mock = Arc::new(
+            MockBackend::scripted([
+                Err(GenerateError::Transient("busy".to_string())),
+                Ok("ok".to_string()),
+            ])
+            .with_delay(Duration::from_millis(20)),
+        );
+        let backend = CoalescingBackend::new(mock.clone());
+
+        let (a, b) = tokio::join!(backend.generate("a"), backend.generate("a"));
+        assert!(matches!(a, Err(GenerateError::Transient(_))));
+        assert!(matches!(b, Err(GenerateError::Transient(_))));
+        assert_eq!(backend.in_flight(), 0);
+
+        assert_eq!(backend.generate("a").await.unwrap(), "ok");
+        assert_eq!(mock.prompts().len(), 2);
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    future::Future,
+    pin::{pin, Pin},
+    sync::Arc,
+};
+
+use futures_util::{future, stream, FutureExt, Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::{GenerateError, GenerateOptions, GenerateOutput, LlmBackend, Usage};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+pub type BoxStream<'a, T> = Pin<Box<dyn Stream<Item = T> + 'a>>;
+
+pub(crate) type ChunkSender = mpsc::Sender<Result<String, GenerateError>>;
+
+/// Streams the chunks sent by `forward`, which is polled alongside the stream.
+///
+/// A stream created inside `forward` can borrow from it, such as a filtered prompt,
+/// and can borrow arguments with different lifetimes, which a returned
+/// [`BoxStream`] could not.
+pub(crate) fn forwarded_stream<F: Future<Output = ()>>(
+    forward: impl FnOnce(ChunkSender) -> F,
+) -> impl Stream<Item = Result<String, GenerateError>> {
+    let (tx, rx) = mpsc::channel(1);
+
+    let chunks = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    });
+
+    stream::select(
+        forward(tx)
+            .into_stream()
+            .filter_map(|_| future::ready(None)),
+        chunks,
+    )
+}
+
+/// Sends each chunk through `tx`, stopping once the receiving stream is dropped.
+pub(crate) async fn send_chunks(
+    chunks: impl Stream<Item = Result<String, GenerateError>>,
+    tx: &ChunkSender,
+) {
+    let mut chunks = pin!(chunks);
+
+    while let Some(chunk) = chunks.next().await {
+        if tx.send(chunk).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Object safe version of [`LlmBackend`], implemented for every backend.
+///
+/// Used to hold backends of different types behind the same pointer,
+/// such as `Arc<dyn DynLlmBackend>`.
+/// `Box<dyn DynLlmBackend>` and `Arc<dyn DynLlmBackend>` (and their `Send + Sync` variants)
+/// implement [`LlmBackend`],
+/// so they can be used with generic wrappers such as [`RetryBackend`](crate::RetryBackend)
+/// or [`LlmWeight`](crate::LlmWeight).
+///
+/// The returned futures are not `Send`, as [`LlmBackend`] does not require it.
+pub trait DynLlmBackend {
+    fn generate_boxed<'a>(
+        &'a self,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, Result<String, GenerateError>>;
+
+    fn generate_with_boxed<'a>(
+        &'a self,
+        prompt: &'a str,
+        options: &'a GenerateOptions,
+    ) -> BoxFuture<'a, Result<String, GenerateError>>;
+
+    fn generate_detailed_boxed<'a>(
+        &'a self,
+        prompt: &'a str,
+        options: &'a GenerateOptions,
+    ) -> BoxFuture<'a, Result<GenerateOutput, GenerateError>>;
+
+    fn generate_stream_boxed<'a>(
+        &'a self,
+        prompt: &'a str,
+        options: &'a GenerateOptions,
+    ) -> BoxStream<'a, Result<String, GenerateError>>;
+
+    fn generate_cancellable_boxed<'a>(
+        &'a self,
+        prompt: &'a str,
+        options: &'a GenerateOptions,
+        cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Result<GenerateOutput, GenerateError>>;
+
+    fn init_boxed(&self) -> BoxFuture<'_, Result<(), GenerateError>>;
+
+    /// See [`LlmBackend::cost_estimate`].
+    fn cost_estimate_dyn(&self, usage: &Usage) -> Option<f64>;
+}
+
+impl<T: LlmBackend> DynLlmBackend for T {
+    fn generate_boxed<'a>(
+        &'a self,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, Result<String, GenerateError>> {
+        Box::pin(self.generate(prompt))
+    }
+
+    fn generate_with_boxed<'a>(
+        &'a self,
+        prompt: &'a str,
+        options: &'a GenerateOptions,
+    ) -> BoxFuture<'a, Result<String, GenerateError>> {
+        Box::pin(self.generate_with(prompt, options))
+    }
+
+    fn generate_detailed_boxed<'a>(
+        &'a self,
+        prompt: &'a str,
+        options: &'a GenerateOptions,
+    ) -> BoxFuture<'a, Result<GenerateOutput, GenerateError>> {
+        Box::pin(self.generate_detailed(prompt, options))
+    }
+
+    fn generate_stream_boxed<'a>(
+        &'a self,
+        prompt: &'a str,
+        options: &'a GenerateOptions,
+    ) -> BoxStream<'a, Result<String, GenerateError>> {
+        Box::pin(self.generate_stream(prompt, options))
+    }
+
+    fn generate_cancellable_boxed<'a>(
+        &'a self,
+        prompt: &'a str,
+        options: &'a GenerateOptions,
+        cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Result<GenerateOutput, GenerateError>> {
+        Box::pin(self.generate_cancellable(prompt, options, cancel))
+    }
+
+    fn init_boxed(&self) -> BoxFuture<'_, Result<(), GenerateError>> {
+        Box::pin(self.init())
+    }
+
+    fn cost_estimate_dyn(&self, usage: &Usage) -> Option<f64> {
+        self.cost_estimate(usage)
+    }
+}
+
+macro_rules! impl_dyn_backend {
+    ($ty:ty) => {
+        impl LlmBackend for $ty {
+            async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+                (**self).generate_boxed(prompt).await
+            }
+
+            async fn generate_with(
+                &self,
+                prompt: &str,
+                options: &GenerateOptions,
+            ) -> Result<String, GenerateError> {
+                (**self).generate_with_boxed(prompt, options).await
+            }
+
+            async fn generate_detailed(
+                &self,
+                prompt: &str,
+                options: &GenerateOptions,
+            ) -> Result<GenerateOutput, GenerateError> {
+                (**self).generate_detailed_boxed(prompt, options).await
+            }
+
+            fn generate_stream(
+                &self,
+                prompt: &str,
+                options: &GenerateOptions,
+            ) -> impl Stream<Item = Result<String, GenerateError>> {
+                forwarded_stream(move |tx| async move {
+                    send_chunks((**self).generate_stream_boxed(prompt, options), &tx).await
+                })
+            }
+
+            async fn generate_cancellable(
+                &self,
+                prompt: &str,
+                options: &GenerateOptions,
+                cancel: &CancellationToken,
+            ) -> Result<GenerateOutput, GenerateError> {
+                (**self)
+                    .generate_cancellable_boxed(prompt, options, cancel)
+                    .await
+            }
+
+            async fn init(&self) -> Result<(), GenerateError> {
+                (**self).init_boxed().await
+            }
+
+            fn cost_estimate(&self, usage: &Usage) -> Option<f64> {
+                (**self).cost_estimate_dyn(usage)
+            }
+        }
+    };
+}
+
+impl_dyn_backend!(Box<dyn DynLlmBackend>);
+impl_dyn_backend!(Box<dyn DynLlmBackend + Send + Sync>);
+impl_dyn_backend!(Arc<dyn DynLlmBackend>);
+impl_dyn_backend!(Arc<dyn DynLlmBackend + Send + Sync>);
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use lemon_graph::{nodes::AsyncNode, Value};
+
+    use crate::{mock::MockBackend, CachingBackend, LlmWeight, RetryBackend};
+
+    use super::*;
+
+    struct Echo;
+
+    impl LlmBackend for Echo {
+        async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+            Ok(prompt.to_string())
+        }
+
+        fn cost_estimate(&self, _usage: &Usage) -> Option<f64> {
+            Some(1.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dyn_backends() {
+        let backends: Vec<Box<dyn DynLlmBackend>> =
+            vec![Box::new(MockBackend::fixed("mock")), Box::new(Echo)];
+
+        let mut responses = Vec::new();
+
+        for backend in &backends {
+            responses.push(backend.generate_boxed("hi").await.unwrap());
+        }
+
+        assert_eq!(responses, vec!["mock", "hi"]);
+
+        let usage = Usage {
+            prompt_tokens: 1,
+            completion_tokens: 1,
+            ..Default::default()
+        };
+        assert_eq!(backends[0].cost_estimate(&usage), None);
+        assert_eq!(backends[1].cost_estimate(&usage), Some(1.0));
+
+        let stream = backends[1]
+            .generate_stream_boxed("hi", &GenerateOptions::default())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(stream.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dyn_composition() {
+        let backend: Box<dyn DynLlmBackend + Send + Sync> = Box::new(RetryBackend::new(
+            Arc::new(Echo),
+            Default::default(),
+            Default::default(),
+        ));
+        let retry = RetryBackend::new(Arc::new(backend), Default::default(), Default::default());
+        assert_eq!(retry.generate("hi").await.unwrap(), "hi");
+
+        let backend: Arc<dyn DynLlmBackend + Send + Sync> = Arc::new(MockBackend::fixed("ok"));
+        let weight = LlmWeight::new(Arc::new(backend));
+        let outputs = weight.run(vec!["hi".to_string().into()]).await.unwrap();
+        assert_eq!(outputs[0], Value::String("ok".to_string()));
+
+        let backend: Arc<dyn DynLlmBackend + Send + Sync> =
+            Arc::new(CachingBackend::new(Arc::new(Echo)));
+        let cached = CachingBackend::new(Arc::new(backend));
+        assert_eq!(cached.generate("hi").await.unwrap(), "hi");
+    }
+
+    #[tokio::test]
+    async fn test_dyn_stream() {
+        let backend: Arc<dyn DynLlmBackend> =
+            Arc::new(MockBackend::fixed("hello there world").with_word_stream());
+
+        let chunks = backend
+            .generate_stream("hi", &GenerateOptions::default())
+            .collect::<Vec<_>>()
+            .await;
+
+        let chunks = chunks.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(chunks, vec!["hello ", "there ", "world"]);
+    }
+}
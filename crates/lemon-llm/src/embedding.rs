@@ -0,0 +1,120 @@
+use std::{future::Future, sync::Arc};
+
+use lemon_graph::{
+    nodes::{AsyncNode, GetStoreError, NodeError, NodeWrapper, StoreWrapper},
+    Graph, GraphEdge, GraphNode, Value,
+};
+use petgraph::graph::NodeIndex;
+
+use crate::GenerateError;
+
+pub trait LlmEmbeddingBackend {
+    fn embed(&self, text: &str) -> impl Future<Output = Result<Vec<f32>, GenerateError>>;
+}
+
+/// Embeds text as a vector.
+/// Outputs a [`Value::Vec`] of [`Value::F32`].
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddingNode(pub NodeIndex);
+
+impl From<EmbeddingNode> for NodeIndex {
+    fn from(value: EmbeddingNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for EmbeddingNode {}
+
+impl EmbeddingNode {
+    pub fn new<T: LlmEmbeddingBackend + 'static>(
+        graph: &mut Graph,
+        weight: EmbeddingWeight<T>,
+    ) -> Self {
+        let index = graph.add_node(GraphNode::AsyncNode(Box::new(weight)));
+
+        let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let output = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+pub struct EmbeddingWeight<T: LlmEmbeddingBackend> {
+    pub backend: Arc<T>,
+}
+
+impl<T: LlmEmbeddingBackend> EmbeddingWeight<T> {
+    pub fn new(backend: Arc<T>) -> Self {
+        Self { backend }
+    }
+}
+
+impl<T: LlmEmbeddingBackend + 'static> AsyncNode for EmbeddingWeight<T> {
+    fn run(
+        &self,
+        inputs: Vec<Value>,
+    ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+        let backend = self.backend.clone();
+
+        Box::new(Box::pin(async move {
+            let text = match inputs.first() {
+                Some(Value::String(text)) => text.clone(),
+                Some(v) => return Err(NodeError::ConversionError(v.clone())),
+                None => return Err(NodeError::MissingInput(0)),
+            };
+
+            let embedding = backend.embed(&text).await?;
+
+            Ok(vec![Value::Vec(
+                embedding.into_iter().map(Value::F32).collect(),
+            )])
+        }))
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.embedding"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lemon_graph::Executor;
+
+    use crate::mock::MockBackend;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_embedding() {
+        let mut graph = Graph::default();
+
+        let backend = Arc::new(MockBackend::fixed("").with_embedding(vec![0.5, -1.0]));
+        let embedding = EmbeddingNode::new(&mut graph, EmbeddingWeight::new(backend.clone()));
+
+        let input = embedding.input(&graph).unwrap();
+        input.set_value(&mut graph, "Hello, world!".to_string().into());
+
+        Executor::execute(&mut graph, embedding.0).await.unwrap();
+
+        let output = embedding.output(&graph).unwrap();
+        match &graph[output.0] {
+            GraphNode::Store(value) => {
+                assert_eq!(value, &Value::Vec(vec![Value::F32(0.5), Value::F32(-1.0)]))
+            }
+            _ => panic!(),
+        }
+
+        assert_eq!(backend.prompts(), vec!["Hello, world!"]);
+    }
+}
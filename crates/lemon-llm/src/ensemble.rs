@@ -0,0 +1,310 @@
+use std::{future::Future, sync::Arc};
+
+use futures_util::future::join_all;
+use lemon_graph::{
+    nodes::{AsyncNode, GetStoreError, NodeError, NodeWrapper, StoreWrapper},
+    Graph, GraphEdge, GraphNode, Value,
+};
+use petgraph::graph::NodeIndex;
+use tracing::warn;
+
+use crate::DynLlmBackend;
+
+/// Runs multiple backends on the same prompt, combining their responses.
+///
+/// Outputs the chosen response, and optionally every candidate as a [`Value::Vec`].
+#[derive(Debug, Clone, Copy)]
+pub struct EnsembleNode(pub NodeIndex);
+
+impl From<EnsembleNode> for NodeIndex {
+    fn from(value: EnsembleNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for EnsembleNode {}
+
+impl EnsembleNode {
+    pub fn new(graph: &mut Graph, weight: EnsembleWeight) -> Self {
+        let candidates = weight.candidates;
+
+        let index = graph.add_node(GraphNode::AsyncNode(Box::new(weight)));
+
+        let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(input, index, GraphEdge::DataMap(0));
+
+        let output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        if candidates {
+            let output = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+            graph.add_edge(index, output, GraphEdge::DataMap(1));
+        }
+
+        Self(index)
+    }
+
+    pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+
+    /// Returns the store of every candidate response,
+    /// if enabled with [`EnsembleWeight::with_candidates`].
+    pub fn candidates(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 1)
+    }
+}
+
+pub type CombineFn = Arc<dyn Fn(&[String]) -> String>;
+
+/// Chooses a response from the candidates of an [`EnsembleNode`].
+#[derive(Clone)]
+pub enum Combiner {
+    /// The response of the first backend to succeed.
+    First,
+    /// The longest response.
+    Longest,
+    /// The response with the most votes, ignoring surrounding whitespace.
+    /// Each backend votes with its weight, see [`EnsembleWeight::new_weighted`].
+    /// Ties go to the earliest backend.
+    Majority,
+    /// A custom function choosing from the candidates.
+    Custom(CombineFn),
+}
+
+impl Combiner {
+    /// Chooses a response, with every candidate weighted equally.
+    /// `candidates` must not be empty.
+    pub fn combine(&self, candidates: &[String]) -> String {
+        self.combine_weighted(candidates, &[])
+    }
+
+    /// Chooses a response, given the weight of each candidate's backend.
+    /// Candidates without a weight have a weight of 1.
+    /// `candidates` must not be empty.
+    pub fn combine_weighted(&self, candidates: &[String], weights: &[u32]) -> String {
+        match self {
+            Self::First => candidates[0].clone(),
+            Self::Longest => candidates
+                .iter()
+                .rev()
+                .max_by_key(|candidate| candidate.len())
+                .cloned()
+                .unwrap_or_default(),
+            Self::Majority => {
+                let votes = |candidate: &String| {
+                    candidates
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, other)| other.trim() == candidate.trim())
+                        .map(|(i, _)| u64::from(weights.get(i).copied().unwrap_or(1)))
+                        .sum::<u64>()
+                };
+
+                candidates
+                    .iter()
+                    .rev()
+                    .max_by_key(|candidate| votes(candidate))
+                    .cloned()
+                    .unwrap_or_default()
+            }
+            Self::Custom(f) => f(candidates),
+        }
+    }
+}
+
+pub struct EnsembleWeight {
+    /// Each backend, with its weight for [`Combiner::Majority`].
+    pub backends: Vec<(Arc<dyn DynLlmBackend>, u32)>,
+    pub combiner: Combiner,
+    /// Whether to output every candidate response.
+    pub candidates: bool,
+}
+
+impl EnsembleWeight {
+    /// Creates an ensemble with every backend weighted equally.
+    pub fn new(backends: Vec<Arc<dyn DynLlmBackend>>, combiner: Combiner) -> Self {
+        Self::new_weighted(
+            backends.into_iter().map(|backend| (backend, 1)).collect(),
+            combiner,
+        )
+    }
+
+    pub fn new_weighted(backends: Vec<(Arc<dyn DynLlmBackend>, u32)>, combiner: Combiner) -> Self {
+        Self {
+            backends,
+            combiner,
+            candidates: false,
+        }
+    }
+
+    pub fn with_candidates(mut self) -> Self {
+        self.candidates = true;
+        self
+    }
+}
+
+impl AsyncNode for EnsembleWeight {
+    fn run(
+        &self,
+        inputs: Vec<Value>,
+    ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
+        let backends = self.backends.clone();
+        let combiner = self.combiner.clone();
+
+        Box::new(Box::pin(async move {
+            let prompt = match inputs.first() {
+                Some(Value::String(prompt)) => prompt.clone(),
+                Some(v) => return Err(NodeError::ConversionError(v.clone())),
+                None => return Err(NodeError::MissingInput(0)),
+            };
+
+            let responses = join_all(
+                backends
+                    .iter()
+                    .map(|(backend, _)| backend.generate_boxed(&prompt)),
+            )
+            .await;
+
+            // Failed backends are left out, as long as one succeeds.
+            let mut candidates = Vec::new();
+            let mut weights = Vec::new();
+            let mut last_error = None;
+
+            for (response, (_, weight)) in responses.into_iter().zip(&backends) {
+                match response {
+                    Ok(response) => {
+                        candidates.push(response);
+                        weights.push(*weight);
+                    }
+                    Err(e) => {
+                        warn!("Ensemble backend failed: {}", e);
+                        last_error = Some(e);
+                    }
+                }
+            }
+
+            if candidates.is_empty() {
+                return Err(match last_error {
+                    Some(e) => e.into(),
+                    None => NodeError::InternalError("No backends".to_string()),
+                });
+            }
+
+            let response = combiner.combine_weighted(&candidates, &weights);
+
+            Ok(vec![
+                Value::String(response),
+                Value::Vec(candidates.into_iter().map(Value::String).collect()),
+            ])
+        }))
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.ensemble"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lemon_graph::Executor;
+
+    use crate::{mock::MockBackend, GenerateError};
+
+    use super::*;
+
+    fn candidates(candidates: &[&str]) -> Vec<String> {
+        candidates.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_combiners() {
+        let c = candidates(&["yes", "no", " no ", "maybe"]);
+
+        assert_eq!(Combiner::First.combine(&c), "yes");
+        assert_eq!(Combiner::Longest.combine(&c), "maybe");
+        assert_eq!(Combiner::Majority.combine(&c), "no");
+        assert_eq!(Combiner::Majority.combine(&candidates(&["a", "b"])), "a");
+
+        // One heavier vote outweighs two lighter ones.
+        let votes = candidates(&["no", "yes", "no"]);
+        let majority = |weights: &[u32]| Combiner::Majority.combine_weighted(&votes, weights);
+        assert_eq!(majority(&[1, 3, 1]), "yes");
+        assert_eq!(majority(&[1, 2, 1]), "no");
+        assert_eq!(majority(&[1, 3]), "yes");
+
+        let last = Combiner::Custom(Arc::new(|c: &[String]| c[c.len() - 1].clone()));
+        assert_eq!(last.combine(&c), "maybe");
+    }
+
+    #[tokio::test]
+    async fn test_ensemble() {
+        let mut graph = Graph::default();
+
+        let backends: Vec<Arc<dyn DynLlmBackend>> = vec![
+            Arc::new(MockBackend::fixed("cat")),
+            Arc::new(MockBackend::scripted([Err(GenerateError::BackendError(
+                "failed".to_string(),
+            ))])),
+            Arc::new(MockBackend::fixed("dog")),
+            Arc::new(MockBackend::fixed("dog")),
+        ];
+
+        let weight = EnsembleWeight::new(backends, Combiner::Majority).with_candidates();
+        let ensemble = EnsembleNode::new(&mut graph, weight);
+
+        let input = ensemble.input(&graph).unwrap();
+        input.set_value(&mut graph, "Cat or dog?".to_string().into());
+
+        Executor::execute(&mut graph, ensemble.0).await.unwrap();
+
+        let output = ensemble.output(&graph).unwrap();
+        match &graph[output.0] {
+            GraphNode::Store(value) => assert_eq!(value, &Value::String("dog".to_string())),
+            _ => panic!(),
+        }
+
+        let candidates = ensemble.candidates(&graph).unwrap();
+        match &graph[candidates.0] {
+            GraphNode::Store(Value::Vec(values)) => assert_eq!(values.len(), 3),
+            _ => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ensemble_weighted() {
+        let mut graph = Graph::default();
+
+        // A failed backend's weight is not counted.
+        let backends: Vec<(Arc<dyn DynLlmBackend>, u32)> = vec![
+            (Arc::new(MockBackend::fixed("cat")), 2),
+            (
+                Arc::new(MockBackend::scripted([Err(GenerateError::BackendError(
+                    "failed".to_string(),
+                ))])),
+                5,
+            ),
+            (Arc::new(MockBackend::fixed("dog")), 1),
+            (Arc::new(MockBackend::fixed("dog")), 1),
+            (Arc::new(MockBackend::fixed("cat")), 1),
+        ];
+
+        let weight = EnsembleWeight::new_weighted(backends, Combiner::Majority);
+        let ensemble = EnsembleNode::new(&mut graph, weight);
+
+        let input = ensemble.input(&graph).unwrap();
+        input.set_value(&mut graph, "Cat or dog?".to_string().into());
+
+        Executor::execute(&mut graph, ensemble.0).await.unwrap();
+
+        let output = ensemble.output(&graph).unwrap();
+        assert_eq!(
+            output.get(&graph).unwrap(),
+            Value::String("cat".to_string())
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::{future::Future, sync::Arc};
+
+use futures_util::{Stream, StreamExt};
+use tracing::warn;
+
+use crate::{
+    dynamic::{forwarded_stream, send_chunks},
+    DynLlmBackend, GenerateError, GenerateOptions, GenerateOutput, LlmBackend,
+};
+
+/// Tries each backend in order, returning the first successful generation.
+///
+/// If every backend fails, the error of the last backend is returned.
+pub struct FallbackBackend {
+    pub backends: Vec<Arc<dyn DynLlmBackend>>,
+}
+
+impl FallbackBackend {
+    pub fn new(backends: Vec<Arc<dyn DynLlmBackend>>) -> Self {
+        Self { backends }
+    }
+
+    async fn fallback<'a, R, F: Future<Output = Result<R, GenerateError>> + 'a>(
+        &'a self,
+        f: impl Fn(&'a dyn DynLlmBackend) -> F,
+    ) -> Result<R, GenerateError> {
+        let mut error = GenerateError::Permanent("No backends".to_string());
+
+        for (i, backend) in self.backends.iter().enumerate() {
+            match f(backend.as_ref()).await {
+                Ok(res) => return Ok(res),
+                Err(e) => {
+                    warn!("Backend {} failed, falling back: {}", i, e);
+                    error = e;
+                }
+            }
+        }
+
+        Err(error)
+    }
+}
+
+impl LlmBackend for FallbackBackend {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.fallback(|backend| backend.generate_boxed(prompt))
+            .await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        self.fallback(|backend| backend.generate_with_boxed(prompt, options))
+            .await
+    }
+
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        self.fallback(|backend| backend.generate_detailed_boxed(prompt, options))
+            .await
+    }
+
+    /// Falls back to the next backend if a stream fails before its first chunk.
+    /// Errors after the first chunk are yielded, as the chunks were already streamed.
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Stream<Item = Result<String, GenerateError>> {
+        forwarded_stream(move |tx| async move {
+            let mut error = GenerateError::Permanent("No backends".to_string());
+
+            for (i, backend) in self.backends.iter().enumerate() {
+                let mut chunks = backend.generate_stream_boxed(prompt, options);
+
+                match chunks.next().await {
+                    Some(Err(e)) => {
+                        warn!("Backend {} failed, falling back: {}", i, e);
+                        error = e;
+                    }
+                    first => {
+                        if let Some(first) = first {
+                            if tx.send(first).await.is_err() {
+                                return;
+                            }
+                        }
+
+                        send_chunks(chunks, &tx).await;
+                        return;
+                    }
+                }
+            }
+
+            // The stream is dropped if the receiver is.
+            let _ = tx.send(Err(error)).await;
+        })
+    }
+
+    /// Initializes every backend, succeeding if any of them do.
+    /// Backends that failed to initialize are still tried when generating.
+    async fn init(&self) -> Result<(), GenerateError> {
+        let mut res = Err(GenerateError::Permanent("No backends".to_string()));
+
+        for backend in &self.backends {
+            match backend.init_boxed().await {
+                Ok(()) => res = Ok(()),
+                Err(e) if res.is_err() => res = Err(e),
+                Err(e) => warn!("Backend failed to initialize: {}", e),
+            }
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::MockBackend;
+
+    use super::*;
+
+    /// Always fails with a transient error.
+    struct Unavailable;
+
+    impl LlmBackend for Unavailable {
+        async fn generate(&self, _prompt: &str) -> Result<String, GenerateError> {
+            Err(GenerateError::Transient("connection refused".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fallback() {
+        let first = Arc::new(MockBackend::fixed("first"));
+        let second = Arc::new(MockBackend::fixed("second"));
+
+        let backend =
+            FallbackBackend::new(vec![Arc::new(Unavailable), first.clone(), second.clone()]);
+
+        assert_eq!(backend.generate("hi").await.unwrap(), "first");
+        assert_eq!(first.prompts(), vec!["hi"]);
+        assert!(second.prompts().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fallback_stream() {
+        let backend = FallbackBackend::new(vec![
+            Arc::new(Unavailable),
+            Arc::new(MockBackend::fixed("hello there").with_word_stream()),
+        ]);
+
+        let chunks = backend
+            .generate_stream("hi", &GenerateOptions::default())
+            .collect::<Vec<_>>()
+            .await;
+
+        let chunks = chunks.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(chunks, vec!["hello ", "there"]);
+
+        let backend = FallbackBackend::new(vec![Arc::new(Unavailable)]);
+        let chunks = backend
+            .generate_stream("hi", &GenerateOptions::default())
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(&chunks[..], [Err(GenerateError::Transient(_))]));
+    }
+
+    #[tokio::test]
+    async fn test_fallback_all_fail() {
+        let backend = FallbackBackend::new(vec![
+            Arc::new(Unavailable),
+            Arc::new(MockBackend::scripted([Err(GenerateError::BackendError(
+                "last".to_string(),
+            ))])),
+        ]);
+
+        assert!(matches!(
+            backend.generate("hi").await,
+            Err(GenerateError::BackendError(e)) if e == "last"
+        ));
+
+        let backend = FallbackBackend::new(Vec::new());
+        assert!(backend.generate("hi").await.is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use lemon_graph::{
+    nodes::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode},
+    Graph, GraphEdge, GraphNode, Value,
+};
+use petgraph::graph::NodeIndex;
+
+use crate::PromptTemplateWeight;
+
+/// Renders a few-shot prompt from example pairs and a query.
+///
+/// Examples are a [`Value::Vec`] of [`Value::Map`]s, each with an `input` and `output` key,
+/// so they can be produced by other nodes. Each example is rendered with
+/// [`FewShotWeight::example_format`], followed by the query rendered with
+/// [`FewShotWeight::query_format`].
+#[derive(Debug, Clone, Copy)]
+pub struct FewShotNode(pub NodeIndex);
+
+impl From<FewShotNode> for NodeIndex {
+    fn from(value: FewShotNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for FewShotNode {}
+
+impl FewShotNode {
+    pub fn new(graph: &mut Graph, weight: FewShotWeight) -> Self {
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(weight)));
+
+        let examples = graph.add_node(GraphNode::Store(Value::Vec(Default::default())));
+        graph.add_edge(examples, index, GraphEdge::DataMap(0));
+
+        let query = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(query, index, GraphEdge::DataMap(1));
+
+        let output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    pub fn examples(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 0)
+    }
+
+    pub fn query(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 1)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FewShotWeight {
+    /// Text before the examples, such as instructions.
+    /// Separated from the first example by [`FewShotWeight::separator`] if not empty.
+    pub prefix: String,
+    /// Template for each example, with `{input}` and `{output}` placeholders.
+    /// See [`PromptTemplateWeight`].
+    pub example_format: String,
+    /// Template for the query, with an `{input}` placeholder.
+    pub query_format: String,
+    /// Placed between the prefix, each example, and the query.
+    pub separator: String,
+}
+
+impl Default for FewShotWeight {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            example_format: "Input: {input}\nOutput: {output}".to_string(),
+            query_format: "Input: {input}\nOutput:".to_string(),
+            separator: "\n\n".to_string(),
+        }
+    }
+}
+
+impl FewShotWeight {
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn with_example_format(mut self, format: impl Into<String>) -> Self {
+        self.example_format = format.into();
+        self
+    }
+
+    pub fn with_query_format(mut self, format: impl Into<String>) -> Self {
+        self.query_format = format.into();
+        self
+    }
+
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Renders the prompt from a [`Value::Vec`] of examples and a query.
+    pub fn render(&self, examples: &Value, query: &Value) -> Result<String, NodeError> {
+        let examples = match examples {
+            Value::Vec(examples) => examples,
+            v => return Err(NodeError::ConversionError(v.clone())),
+        };
+
+        let example_template = PromptTemplateWeight::new(
+            self.example_format.clone(),
+            vec!["input".to_string(), "output".to_string()],
+        );
+        let query_template =
+            PromptTemplateWeight::new(self.query_format.clone(), vec!["input".to_string()]);
+
+        let mut parts = Vec::with_capacity(examples.len() + 2);
+
+        if !self.prefix.is_empty() {
+            parts.push(self.prefix.clone());
+        }
+
+        for (i, example) in examples.iter().enumerate() {
+            let Value::Map(example) = example else {
+                return Err(NodeError::ConversionError(example.clone()));
+            };
+
+            let values = ["input", "output"]
+                .into_iter()
+                .map(|key| {
+                    example.get(key).cloned().ok_or_else(|| {
+                        NodeError::InternalError(format!(
+                            "Example {} is missing its `{}` key",
+                            i, key
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            parts.push(example_template.render(&values)?);
+        }
+
+        parts.push(query_template.render(std::slice::from_ref(query))?);
+
+        Ok(parts.join(&self.separator))
+    }
+}
+
+impl SyncNode for FewShotWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        let examples = inputs.first().ok_or(NodeError::MissingInput(0))?;
+        let query = inputs.get(1).ok_or(NodeError::MissingInput(1))?;
+
+        Ok(vec![Value::String(self.render(examples, query)?)])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.few_shot"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use lemon_graph::Executor;
+
+    use super::*;
+
+    fn example(input: &str, output: &str) -> Value {
+        Value::Map(BTreeMap::from([
+            ("input".to_string(), Value::String(input.to_string())),
+            ("output".to_string(), Value::String(output.to_string())),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn test_few_shot() {
+        let mut graph = Graph::default();
+
+        let weight = FewShotWeight::default().with_prefix("Translate to French.");
+        let few_shot = FewShotNode::new(&mut graph, weight);
+
+        few_shot.examples(&graph).unwrap().set_value(
+            &mut graph,
+            Value::Vec(vec![example("cat", "chat"), example("dog", "chien")]),
+        );
+        few_shot
+            .query(&graph)
+            .unwrap()
+            .set_value(&mut graph, "bird".to_string().into());
+
+        Executor::execute(&mut graph, few_shot.0).await.unwrap();
+
+        assert_eq!(
+            few_shot.output(&graph).unwrap().get(&graph).unwrap(),
+            Value::String(
+                "Translate to French.\n\n\
+                 Input: cat\nOutput: chat\n\n\
+                 Input: dog\nOutput: chien\n\n\
+                 Input: bird\nOutput:"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_few_shot_format() {
+        let weight = FewShotWeight::default()
+            .with_example_format("Q: {input} A: {output}")
+            .with_query_format("Q: {input} A:")
+            .with_separator("\n");
+
+        let rendered = weight
+            .render(
+                &Value::Vec(vec![example("1+1", "2"), example("2+2", "4")]),
+                &"3+3".to_string().into(),
+            )
+            .unwrap();
+
+        assert_eq!(rendered, "Q: 1+1 A: 2\nQ: 2+2 A: 4\nQ: 3+3 A:");
+    }
+
+    #[test]
+    fn test_few_shot_missing_key() {
+        let examples = Value::Vec(vec![
+            example("cat", "chat"),
+            Value::Map(BTreeMap::from([(
+                "input".to_string(),
+                Value::String("dog".to_string()),
+            )])),
+        ]);
+
+        match FewShotWeight::default().render(&examples, &"bird".to_string().into()) {
+            Err(NodeError::InternalError(e)) => {
+                assert_eq!(e, "Example 1 is missing its `output` key")
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+}
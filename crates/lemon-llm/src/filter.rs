@@ -0,0 +1,233 @@
+use std::sync::Arc;
+
+use futures_util::Stream;
+use regex::Regex;
+use tracing::debug;
+
+use crate::{
+    dynamic::{forwarded_stream, send_chunks},
+    GenerateError, GenerateOptions, GenerateOutput, LlmBackend, Usage,
+};
+
+/// Transforms prompts before they are sent to a backend.
+pub trait PromptFilter {
+    fn filter(&self, prompt: &str) -> String;
+}
+
+impl<F: Fn(&str) -> String> PromptFilter for F {
+    fn filter(&self, prompt: &str) -> String {
+        self(prompt)
+    }
+}
+
+/// Redacts text matching a set of regex patterns.
+pub struct RegexRedactor {
+    patterns: Vec<(Regex, String)>,
+}
+
+impl Default for RegexRedactor {
+    /// Redacts email addresses and credit card numbers.
+    fn default() -> Self {
+        Self::empty()
+            .with_pattern(
+                Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap(),
+                "[EMAIL]",
+            )
+            .with_pattern(Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").unwrap(), "[CARD]")
+    }
+}
+
+impl RegexRedactor {
+    /// Creates a redactor with no patterns.
+    pub fn empty() -> Self {
+        Self {
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Replaces every match of `pattern` with `replacement`.
+    pub fn with_pattern(mut self, pattern: Regex, replacement: impl Into<String>) -> Self {
+        self.patterns.push((pattern, replacement.into()));
+        self
+    }
+}
+
+impl PromptFilter for RegexRedactor {
+    fn filter(&self, prompt: &str) -> String {
+        self.patterns
+            .iter()
+            .fold(prompt.to_string(), |prompt, (pattern, replacement)| {
+                pattern
+                    .replace_all(&prompt, replacement.as_str())
+                    .into_owned()
+            })
+    }
+}
+
+/// Backend wrapper that filters prompts before delegating to the inner backend.
+pub struct FilteringBackend<T: LlmBackend, F: PromptFilter> {
+    pub backend: Arc<T>,
+    pub filter: F,
+    /// Logs each filtered prompt at the debug level.
+    /// The unfiltered prompt is never logged.
+    pub log_prompts: bool,
+}
+
+impl<T: LlmBackend, F: PromptFilter> FilteringBackend<T, F> {
+    pub fn new(backend: Arc<T>, filter: F) -> Self {
+        Self {
+            backend,
+            filter,
+            log_prompts: false,
+        }
+    }
+
+    fn filtered(&self, prompt: &str) -> String {
+        let prompt = self.filter.filter(prompt);
+
+        if self.log_prompts {
+            debug!("Filtered prompt: {}", prompt);
+        }
+
+        prompt
+    }
+
+    fn filtered_options(&self, options: &GenerateOptions) -> GenerateOptions {
+        GenerateOptions {
+            system: options
+                .system
+                .as_deref()
+                .map(|system| self.filtered(system)),
+            ..options.clone()
+        }
+    }
+}
+
+impl<T: LlmBackend, F: PromptFilter> LlmBackend for FilteringBackend<T, F> {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.backend.generate(&self.filtered(prompt)).await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        self.backend
+            .generate_with(&self.filtered(prompt), &self.filtered_options(options))
+            .await
+    }
+
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        self.backend
+            .generate_detailed(&self.filtered(prompt), &self.filtered_options(options))
+            .await
+    }
+
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Stream<Item = Result<String, GenerateError>> {
+        let prompt = self.filtered(prompt);
+        let options = self.filtered_options(options);
+
+        // The inner stream borrows the filtered prompt, so is driven by a future
+        // that owns it.
+        forwarded_stream(move |tx| async move {
+            send_chunks(self.backend.generate_stream(&prompt, &options), &tx).await
+        })
+    }
+
+    async fn init(&self) -> Result<(), GenerateError> {
+        self.backend.init().await
+    }
+
+    fn cost_estimate(&self, usage: &Usage) -> Option<f64> {
+        self.backend.cost_estimate(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures_util::StreamExt;
+
+    use crate::mock::MockBackend;
+
+    use super::*;
+
+    #[test]
+    fn test_redact_email() {
+        let redacted = RegexRedactor::default().filter("Contact me at jane.doe@example.com.");
+        assert_eq!(redacted, "Contact me at [EMAIL].");
+    }
+
+    #[test]
+    fn test_redact_card() {
+        let redactor = RegexRedactor::default();
+        assert_eq!(redactor.filter("Card: 4111 1111 1111 1111"), "Card: [CARD]");
+        assert_eq!(redactor.filter("Card: 4111-1111-1111-1111"), "Card: [CARD]");
+        assert_eq!(redactor.filter("Order 12345"), "Order 12345");
+    }
+
+    #[tokio::test]
+    async fn test_filtering_backend() {
+        let mock = Arc::new(MockBackend::fixed("ok"));
+
+        let backend =
+            FilteringBackend::new(mock.clone(), |prompt: &str| prompt.replace("secret", "***"));
+
+        backend.generate("my secret plan").await.unwrap();
+        assert_eq!(mock.prompts(), vec!["my *** plan"]);
+    }
+
+    #[tokio::test]
+    async fn test_filtering_backend_stream() {
+        let mock = Arc::new(MockBackend::fixed("ok"));
+
+        let backend =
+            FilteringBackend::new(mock.clone(), |prompt: &str| prompt.replace("secret", "***"));
+
+        let options = GenerateOptions {
+            system: Some("keep it secret".to_string()),
+            ..Default::default()
+        };
+        let chunks = backend
+            .generate_stream("my secret plan", &options)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].as_ref().unwrap(), "ok");
+        assert_eq!(mock.prompts(), vec!["keep it ***\n\nmy *** plan"]);
+    }
+
+    #[tokio::test]
+    async fn test_filtering_backend_init() {
+        #[derive(Default)]
+        struct TestInit(AtomicUsize);
+
+        impl LlmBackend for TestInit {
+            async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+                Ok(prompt.to_string())
+            }
+
+            async fn init(&self) -> Result<(), GenerateError> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+
+        let inner = Arc::new(TestInit::default());
+        let backend = FilteringBackend::new(inner.clone(), |prompt: &str| prompt.to_string());
+
+        backend.init().await.unwrap();
+        assert_eq!(inner.0.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use lemon_graph::{nodes::NodeError, Value};
+
+/// Parses the response of an [`LlmWeight`](crate::LlmWeight) as JSON,
+/// see [`LlmWeight::with_json`](crate::LlmWeight::with_json).
+///
+/// Objects are parsed into [`Value::Map`], arrays into [`Value::Vec`],
+/// and `null` into [`Value::Null`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct JsonMode {
+    /// JSON Schema the parsed response must match.
+    ///
+    /// Only a subset of JSON Schema is checked: `type`, `enum`, `properties`,
+    /// `required`, and `items`. Other keywords are ignored.
+    pub schema: Option<serde_json::Value>,
+}
+
+impl JsonMode {
+    pub fn with_schema(mut self, schema: serde_json::Value) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Parses and validates a response.
+    ///
+    /// Returns [`NodeError::ConversionError`] with the response if it is not valid JSON,
+    /// or [`NodeError::InternalError`] if it does not match the schema.
+    pub fn parse(&self, text: &str) -> Result<Value, NodeError> {
+        let conversion_error = || NodeError::ConversionError(Value::String(text.to_string()));
+
+        let json = serde_json::from_str::<serde_json::Value>(strip_code_fence(text))
+            .map_err(|_| conversion_error())?;
+
+        if let Some(schema) = &self.schema {
+            validate(schema, &json, "$").map_err(NodeError::InternalError)?;
+        }
+
+        to_value(json).ok_or_else(conversion_error)
+    }
+}
+
+/// Removes a Markdown code fence around the text, which models often add.
+fn strip_code_fence(text: &str) -> &str {
+    let text = text.trim();
+
+    match text
+        .strip_prefix("```")
+        .and_then(|text| text.strip_suffix("```"))
+    {
+        // Skip the language tag, such as "json".
+        Some(inner) => inner
+            .split_once('\n')
+            .map(|(_, body)| body)
+            .unwrap_or(inner)
+            .trim(),
+        None => text,
+    }
+}
+
+pub(crate) fn to_value(json: serde_json::Value) -> Option<Value> {
+    Some(match json {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(value) => Value::Bool(value),
+        serde_json::Value::Number(number) => {
+            if let Some(value) = number.as_u64() {
+                Value::USize(value.try_into().ok()?)
+            } else if let Some(value) = number.as_i64() {
+                Value::ISize(value.try_into().ok()?)
+            } else {
+                Value::F32(number.as_f64()? as f32)
+            }
+        }
+        serde_json::Value::String(value) => Value::String(value),
+        serde_json::Value::Array(values) => {
+            Value::Vec(values.into_iter().map(to_value).collect::<Option<_>>()?)
+        }
+        serde_json::Value::Object(map) => Value::Map(
+            map.into_iter()
+                .map(|(key, value)| Some((key, to_value(value)?)))
+                .collect::<Option<BTreeMap<_, _>>>()?,
+        ),
+    })
+}
+
+/// Checks `json` against the supported subset of `schema`,
+/// returning a description of the first mismatch.
+fn validate(
+    schema: &serde_json::Value,
+    json: &serde_json::Value,
+    path: &str,
+) -> Result<(), String> {
+    if let Some(expected) = schema.get("type").and_then(|t| t.as_str()) {
+        let matches = match expected {
+            "object" => json.is_object(),
+            "array" => json.is_array(),
+            "string" => json.is_string(),
+            "number" => json.is_number(),
+            "integer" => json.is_i64() || json.is_u64(),
+            "boolean" => json.is_boolean(),
+            "null" => json.is_null(),
+            _ => true,
+        };
+
+        if !matches {
+            return Err(format!("{}: expected {}, got {}", path, expected, json));
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(|e| e.as_array()) {
+        if !options.contains(json) {
+            return Err(format!("{}: {} is not one of {:?}", path, json, options));
+        }
+    }
+
+    if let Some(object) = json.as_object() {
+        if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+            for key in required.iter().filter_map(|key| key.as_str()) {
+                if !object.contains_key(key) {
+                    return Err(format!("{}: missing required property {:?}", path, key));
+                }
+            }
+        }
+
+        if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (key, property) in properties {
+                if let Some(value) = object.get(key) {
+                    validate(property, value, &format!("{}.{}", path, key))?;
+                }
+            }
+        }
+    }
+
+    if let (Some(items), Some(array)) = (schema.get("items"), json.as_array()) {
+        for (i, value) in array.iter().enumerate() {
+            validate(items, value, &format!("{}[{}]", path, i))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let value = JsonMode::default()
+            .parse(r#"{"name": "Lemon", "tags": ["sour", 2, -1, 0.5, true]}"#)
+            .unwrap();
+
+        assert_eq!(
+            value,
+            Value::Map(BTreeMap::from([
+                ("name".to_string(), Value::String("Lemon".to_string())),
+                (
+                    "tags".to_string(),
+                    Value::Vec(vec![
+                        Value::String("sour".to_string()),
+                        Value::USize(2),
+                        Value::ISize(-1),
+                        Value::F32(0.5),
+                        Value::Bool(true),
+                    ])
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_code_fence() {
+        let value = JsonMode::default().parse("```json\n[1, 2]\n```").unwrap();
+        assert_eq!(value, Value::Vec(vec![Value::USize(1), Value::USize(2)]));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(matches!(
+            JsonMode::default().parse("Sure! Here is your JSON:"),
+            Err(NodeError::ConversionError(Value::String(_)))
+        ));
+    }
+
+    #[test]
+    fn test_parse_null() {
+        assert_eq!(
+            JsonMode::default().parse(r#"{"a": null}"#).unwrap(),
+            Value::Map([("a".to_string(), Value::Null)].into())
+        );
+    }
+
+    #[test]
+    fn test_schema() {
+        let mode = JsonMode::default().with_schema(json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string" },
+                "size": { "enum": ["small", "large"] },
+                "scores": { "type": "array", "items": { "type": "integer" } }
+            }
+        }));
+
+        assert!(mode
+            .parse(r#"{"name": "Lemon", "size": "small", "scores": [1, 2]}"#)
+            .is_ok());
+
+        let error = |text: &str| match mode.parse(text) {
+            Err(NodeError::InternalError(error)) => error,
+            res => panic!("expected schema error, got {:?}", res),
+        };
+
+        assert!(error(r#"{"size": "small"}"#).contains("\"name\""));
+        assert!(error(r#"{"name": 1}"#).starts_with("$.name"));
+        assert!(error(r#"{"name": "Lemon", "size": "medium"}"#).starts_with("$.size"));
+        assert!(error(r#"{"name": "Lemon", "scores": [1, 2.5]}"#).starts_with("$.scores[1]"));
+        assert!(error("[]").starts_with("$:"));
+    }
+}
@@ -46,20 +46,67 @@
 //! }
 //! ```
 
-use std::{future::Future, sync::Arc};
+use std::{future::Future, pin::pin, sync::Arc, time::Duration};
 
+use futures_util::{
+    future::{join_all, try_join_all},
+    stream, Stream, StreamExt,
+};
 use lemon_graph::{
     nodes::{AsyncNode, GetStoreError, NodeError, NodeWrapper, StoreWrapper},
     Graph, GraphEdge, GraphNode, Value,
 };
 use petgraph::graph::NodeIndex;
 use thiserror::Error;
-use tracing::error;
+use tokio::sync::{watch, OnceCell};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
 
+#[cfg(feature = "anthropic")]
+pub mod anthropic;
+mod balance;
+mod batch;
+mod cache;
+mod chat;
+mod coalesce;
+mod dynamic;
+mod embedding;
+mod ensemble;
+mod fallback;
+mod few_shot;
+mod filter;
+mod json;
+#[cfg(any(feature = "ollama", feature = "openai", feature = "replicate"))]
+mod lines;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 #[cfg(feature = "ollama")]
 pub mod ollama;
+#[cfg(feature = "openai")]
+pub mod openai;
+mod rate_limit;
 #[cfg(feature = "replicate")]
 pub mod replicate;
+mod retry;
+mod template;
+mod usage;
+
+pub use balance::LoadBalancedBackend;
+pub use batch::{LlmBatchNode, LlmBatchWeight};
+pub use cache::CachingBackend;
+pub use chat::{ChatMessage, ChatNode, ChatWeight, LlmChatBackend, Role, Tool, ToolResponse};
+pub use coalesce::CoalescingBackend;
+pub use dynamic::{BoxFuture, BoxStream, DynLlmBackend};
+pub use embedding::{EmbeddingNode, EmbeddingWeight, LlmEmbeddingBackend};
+pub use ensemble::{CombineFn, Combiner, EnsembleNode, EnsembleWeight};
+pub use fallback::FallbackBackend;
+pub use few_shot::{FewShotNode, FewShotWeight};
+pub use filter::{FilteringBackend, PromptFilter, RegexRedactor};
+pub use json::JsonMode;
+pub use rate_limit::{RateLimitedBackend, RateLimiter};
+pub use retry::RetryBackend;
+pub use template::{PromptTemplateNode, PromptTemplateWeight, UnknownPlaceholder};
+pub use usage::{Pricing, Usage};
 
 #[derive(Debug, Clone, Copy)]
 pub struct LlmNode(pub NodeIndex);
@@ -72,49 +119,564 @@ impl From<LlmNode> for NodeIndex {
 
 impl NodeWrapper for LlmNode {}
 
+/// The stores of an [`LlmNode`], returned by [`LlmNode::new_with_ports`].
+#[derive(Debug, Clone, Copy)]
+pub struct LlmPorts {
+    pub input: StoreWrapper,
+    /// The system prompt input store, if enabled.
+    pub system_prompt: Option<StoreWrapper>,
+    /// The [`LlmOutput::Response`] store, if enabled.
+    pub response: Option<StoreWrapper>,
+    /// The [`LlmOutput::Prompt`] store, if enabled.
+    pub prompt: Option<StoreWrapper>,
+    /// The [`LlmOutput::Reasoning`] store, if enabled.
+    pub reasoning: Option<StoreWrapper>,
+    /// The [`LlmOutput::Usage`] store, if enabled.
+    pub usage: Option<StoreWrapper>,
+}
+
 impl LlmNode {
     pub fn new<T: LlmBackend>(graph: &mut Graph, weight: LlmWeight<T>) -> Self {
+        Self::new_with_ports(graph, weight).0
+    }
+
+    /// Creates the node, also returning its stores so they
+    /// do not need to be looked up again.
+    pub fn new_with_ports<T: LlmBackend>(
+        graph: &mut Graph,
+        weight: LlmWeight<T>,
+    ) -> (Self, LlmPorts) {
+        let outputs = weight.outputs.clone();
+        let system_prompt = weight.system_prompt;
+
         let index = graph.add_node(GraphNode::AsyncNode(Box::new(weight)));
 
         let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
         graph.add_edge(input, index, GraphEdge::DataMap(0));
 
-        let output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
-        graph.add_edge(index, output, GraphEdge::DataMap(0));
+        let system_prompt = system_prompt.then(|| {
+            let store = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+            graph.add_edge(store, index, GraphEdge::DataMap(1));
+            StoreWrapper(store)
+        });
+
+        let mut ports = LlmPorts {
+            input: StoreWrapper(input),
+            system_prompt,
+            response: None,
+            prompt: None,
+            reasoning: None,
+            usage: None,
+        };
 
-        Self(index)
+        for output in outputs {
+            let value = match output {
+                LlmOutput::Usage => Value::Vec(Default::default()),
+                _ => Value::String(Default::default()),
+            };
+
+            let store = graph.add_node(GraphNode::Store(value));
+            graph.add_edge(index, store, GraphEdge::DataMap(output.index()));
+
+            let port = match output {
+                LlmOutput::Response => &mut ports.response,
+                LlmOutput::Prompt => &mut ports.prompt,
+                LlmOutput::Reasoning => &mut ports.reasoning,
+                LlmOutput::Usage => &mut ports.usage,
+            };
+            *port = Some(StoreWrapper(store));
+        }
+
+        (Self(index), ports)
+    }
+
+    /// Creates the node, streaming its response.
+    /// Also returns a receiver of the partial response, updated as each chunk arrives.
+    ///
+    /// Stores are only written once a node finishes, so the receiver can be used to
+    /// show long generations as they are produced. See [`LlmWeight::with_partial_response`].
+    pub fn new_streaming<T: LlmBackend>(
+        graph: &mut Graph,
+        weight: LlmWeight<T>,
+    ) -> (Self, watch::Receiver<String>) {
+        let (partial, receiver) = watch::channel(String::new());
+        let node = Self::new(graph, weight.with_partial_response(partial));
+        (node, receiver)
     }
 
     pub fn input(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
-        self.input_stores(graph)
-            .next()
-            .ok_or(GetStoreError::NoStore)
+        self.input_store(graph, 0)
+    }
+
+    /// Returns the system prompt store,
+    /// if enabled with [`LlmWeight::with_system_prompt`].
+    pub fn system_prompt(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, 1)
     }
 
+    /// Returns the [`LlmOutput::Response`] store.
     pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
-        self.output_stores(graph)
-            .next()
-            .ok_or(GetStoreError::NoStore)
+        self.output_of(graph, LlmOutput::Response)
     }
+
+    /// Returns the store for the given output.
+    pub fn output_of(
+        &self,
+        graph: &Graph,
+        output: LlmOutput,
+    ) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, output.index())
+    }
+}
+
+/// An output of an [`LlmNode`].
+/// Each output is written to its own store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LlmOutput {
+    /// The generated response.
+    Response,
+    /// The prompt that was sent to the backend.
+    Prompt,
+    /// The reasoning of the model before its response.
+    /// Empty if the backend does not provide it.
+    Reasoning,
+    /// Tokens used, as a [`Value::Vec`] of prompt and completion token counts.
+    /// Empty if the backend does not report it.
+    Usage,
+}
+
+impl LlmOutput {
+    /// The data index of the output.
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Response => 0,
+            Self::Prompt => 1,
+            Self::Reasoning => 2,
+            Self::Usage => 3,
+        }
+    }
+}
+
+/// The result of reviewing a prompt before it is sent to the backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptDecision {
+    /// Send the prompt unchanged.
+    Approve,
+    /// Send this prompt instead.
+    Edit(String),
+    /// Do not send the prompt. The node returns an error.
+    Reject,
 }
 
-#[derive(Debug, Error)]
+pub type ChunkFn = Arc<dyn Fn(&str)>;
+
+pub type ApprovalFn = Arc<dyn Fn(&str) -> Box<dyn Future<Output = PromptDecision> + Unpin>>;
+
+pub type PostProcessFn = Arc<dyn Fn(String) -> Result<String, NodeError>>;
+
+pub type CountFn = Arc<dyn Fn(&str) -> usize>;
+
+pub type ValidateFn = Arc<dyn Fn(&str) -> Result<(), String>>;
+
+/// Parameters for a generation.
+/// Fields that are `None` or empty use the backend's defaults.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GenerateOptions {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    /// Maximum number of tokens to generate.
+    pub max_tokens: Option<u32>,
+    /// Sequences that stop generation when produced.
+    pub stop: Vec<String>,
+    /// Instructions for the model, separate from the prompt.
+    pub system: Option<String>,
+    /// Constrains the response to valid JSON, for backends that support it.
+    pub json: bool,
+}
+
+/// A generated response, with details beyond its text.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GenerateOutput {
+    pub text: String,
+    /// The reasoning of the model, separate from the final text.
+    /// `None` if the backend does not provide it.
+    pub reasoning: Option<String>,
+    /// Tokens used by the generation.
+    /// `None` if the backend does not report it.
+    pub usage: Option<Usage>,
+}
+
+#[derive(Debug, Clone, Error)]
 pub enum GenerateError {
     #[error("Backend error: {0}")]
     BackendError(String),
+    /// An error that may succeed if retried, such as a dropped connection.
+    #[error("Transient error: {0}")]
+    Transient(String),
+    /// An error that will fail again if retried, such as an invalid request.
+    #[error("Permanent error: {0}")]
+    Permanent(String),
+    /// The backend did not respond in time.
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    /// The backend rejected the request due to a rate limit.
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+    /// The backend rejected the request's credentials.
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+}
+
+impl GenerateError {
+    /// The error returned when a generation is cancelled.
+    pub(crate) fn cancelled() -> Self {
+        Self::BackendError("cancelled".to_string())
+    }
+
+    /// Creates an error from an unsuccessful HTTP response, categorized by status code.
+    /// Other client errors, such as a bad request, are [`GenerateError::Permanent`].
+    pub fn from_status(status: u16, message: String) -> Self {
+        match status {
+            401 | 403 => Self::Auth(message),
+            408 | 504 => Self::Timeout(message),
+            429 => Self::RateLimited(message),
+            400..=499 => Self::Permanent(message),
+            _ => Self::BackendError(message),
+        }
+    }
+
+    /// Whether retrying the generation could succeed.
+    /// [`GenerateError::Permanent`] and [`GenerateError::Auth`] errors are not retryable.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, Self::Permanent(_) | Self::Auth(_))
+    }
+}
+
+/// Keeps the category of the error, so nodes can be handled by the kind of failure.
+impl From<GenerateError> for NodeError {
+    fn from(value: GenerateError) -> Self {
+        match value {
+            GenerateError::BackendError(message) => NodeError::Backend(message),
+            GenerateError::Timeout(message) => NodeError::Timeout(message),
+            GenerateError::RateLimited(message) => NodeError::RateLimited(message),
+            GenerateError::Auth(message) => NodeError::Auth(message),
+            e @ (GenerateError::Transient(_) | GenerateError::Permanent(_)) => {
+                NodeError::Backend(e.to_string())
+            }
+        }
+    }
+}
+
+/// How a batch of generations handles individual failures.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BatchMode {
+    /// The first error aborts the batch.
+    /// Generations that are still in flight are dropped, cancelling them.
+    #[default]
+    FailFast,
+    /// Every generation runs to completion, and each result is collected.
+    CollectAll,
 }
 
 pub trait LlmBackend {
     fn generate(&self, prompt: &str) -> impl Future<Output = Result<String, GenerateError>>;
+
+    /// Generates a response using the given options.
+    ///
+    /// By default, options are ignored and [`LlmBackend::generate`] is called,
+    /// with the system prompt placed before the prompt.
+    fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Future<Output = Result<String, GenerateError>> {
+        async move {
+            match &options.system {
+                Some(system) => self.generate(&format!("{}\n\n{}", system, prompt)).await,
+                None => self.generate(prompt).await,
+            }
+        }
+    }
+
+    /// Generates a response, including details such as reasoning if the backend provides them.
+    fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Future<Output = Result<GenerateOutput, GenerateError>> {
+        async move {
+            Ok(GenerateOutput {
+                text: self.generate_with(prompt, options).await?,
+                ..Default::default()
+            })
+        }
+    }
+
+    /// Generates a response as a stream of text chunks, as they are produced.
+    /// Errors during generation are yielded by the stream.
+    ///
+    /// By default, the full response of [`LlmBackend::generate_with`] is yielded as a single chunk.
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Stream<Item = Result<String, GenerateError>> {
+        stream::once(self.generate_with(prompt, options))
+    }
+
+    /// Like [`LlmBackend::generate_detailed`], but stops once `cancel` is triggered,
+    /// returning a [`GenerateError::BackendError`] of `"cancelled"`.
+    ///
+    /// By default, the generation is dropped when cancelled, which aborts any in-flight request.
+    fn generate_cancellable(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<GenerateOutput, GenerateError>> {
+        until_cancelled(Some(cancel), self.generate_detailed(prompt, options))
+    }
+
+    /// Prepares expensive resources, such as loading a model.
+    /// [`LlmWeight`] calls this once, before its first generation.
+    ///
+    /// A backend may be shared between multiple nodes, so backends that need
+    /// initialization should guard against running it twice,
+    /// for example with a [`tokio::sync::OnceCell`].
+    fn init(&self) -> impl Future<Output = Result<(), GenerateError>> {
+        async { Ok(()) }
+    }
+
+    /// Estimated cost of a generation in dollars,
+    /// or `None` if the backend has no known pricing.
+    fn cost_estimate(&self, _usage: &Usage) -> Option<f64> {
+        None
+    }
+
+    /// Generates a response for each prompt concurrently, preserving order.
+    ///
+    /// With [`BatchMode::FailFast`] the first error is returned.
+    /// With [`BatchMode::CollectAll`] the result of every prompt is returned.
+    fn generate_batch(
+        &self,
+        prompts: &[String],
+        mode: BatchMode,
+    ) -> impl Future<Output = Result<Vec<Result<String, GenerateError>>, GenerateError>> {
+        async move {
+            let futures = prompts.iter().map(|prompt| self.generate(prompt));
+
+            match mode {
+                BatchMode::FailFast => {
+                    let responses = try_join_all(futures).await?;
+                    Ok(responses.into_iter().map(Ok).collect())
+                }
+                BatchMode::CollectAll => Ok(join_all(futures).await),
+            }
+        }
+    }
+}
+
+/// Runs `future` until it completes, or until `cancel` is triggered.
+pub(crate) async fn until_cancelled<T>(
+    cancel: Option<&CancellationToken>,
+    future: impl Future<Output = Result<T, GenerateError>>,
+) -> Result<T, GenerateError> {
+    match cancel {
+        Some(cancel) => tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(GenerateError::cancelled()),
+            res = future => res,
+        },
+        None => future.await,
+    }
 }
 
 pub struct LlmWeight<T: LlmBackend + 'static> {
     pub backend: Arc<T>,
+    /// Outputs to create stores for.
+    pub outputs: Vec<LlmOutput>,
+    /// Whether empty or whitespace-only prompts are sent to the backend.
+    /// If false, the default, they fail with [`NodeError::MissingInput`]
+    /// without calling the backend.
+    pub allow_empty_prompt: bool,
+    /// Maximum length of a prompt, including the system prompt.
+    /// Longer prompts fail without calling the backend.
+    pub max_prompt_length: Option<usize>,
+    /// Measures the length of a prompt for [`LlmWeight::max_prompt_length`],
+    /// such as by counting tokens. Counts characters if `None`.
+    pub count_prompt: Option<CountFn>,
+    /// Reviews each prompt before it is sent to the backend.
+    pub approval: Option<ApprovalFn>,
+    /// Receives response chunks as they are streamed from the backend.
+    pub on_chunk: Option<ChunkFn>,
+    /// Holds the response streamed so far, see [`LlmWeight::with_partial_response`].
+    pub partial: Option<watch::Sender<String>>,
+    /// Transforms the response before it is written to the response output.
+    pub post_process: Option<PostProcessFn>,
+    pub options: GenerateOptions,
+    /// Maximum time to wait for a generation. Unbounded if `None`.
+    pub timeout: Option<Duration>,
+    /// Cancels in-flight generations when triggered,
+    /// see [`LlmBackend::generate_cancellable`].
+    pub cancel: Option<CancellationToken>,
+    /// Whether to create a system prompt input.
+    /// If set and not empty, it replaces [`GenerateOptions::system`].
+    pub system_prompt: bool,
+    /// Parses the response as JSON before writing it to the response output.
+    pub json: Option<JsonMode>,
+    /// Checks each response, returning a description of the problem if it is invalid.
+    pub validate: Option<ValidateFn>,
+    /// Number of times to prompt before failing on an invalid response,
+    /// see [`LlmWeight::with_max_attempts`].
+    pub max_attempts: usize,
+    initialized: Arc<OnceCell<()>>,
 }
 
 impl<T: LlmBackend> LlmWeight<T> {
     pub fn new(backend: Arc<T>) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            outputs: vec![LlmOutput::Response],
+            allow_empty_prompt: false,
+            max_prompt_length: None,
+            count_prompt: None,
+            approval: None,
+            on_chunk: None,
+            partial: None,
+            post_process: None,
+            options: GenerateOptions::default(),
+            system_prompt: false,
+            timeout: None,
+            cancel: None,
+            json: None,
+            validate: None,
+            max_attempts: 1,
+            initialized: Default::default(),
+        }
+    }
+
+    pub fn with_outputs(mut self, outputs: Vec<LlmOutput>) -> Self {
+        self.outputs = outputs;
+        self
+    }
+
+    pub fn with_allow_empty_prompt(mut self, allow_empty_prompt: bool) -> Self {
+        self.allow_empty_prompt = allow_empty_prompt;
+        self
+    }
+
+    /// Fails prompts longer than `max` characters before sending them.
+    /// The system prompt is included in the length.
+    pub fn with_max_prompt_length(mut self, max: usize) -> Self {
+        self.max_prompt_length = Some(max);
+        self
+    }
+
+    /// Measures prompt length using `count`, such as a tokenizer,
+    /// instead of counting characters. See [`LlmWeight::with_max_prompt_length`].
+    pub fn with_prompt_counter(mut self, count: impl Fn(&str) -> usize + 'static) -> Self {
+        self.count_prompt = Some(Arc::new(count));
+        self
+    }
+
+    pub fn with_options(mut self, options: GenerateOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Cancels generations once `token` is triggered, failing the node.
+    /// Pass [`Executor::cancellation_token`](lemon_graph::Executor::cancellation_token)
+    /// to cancel generations along with the executor.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Requests a JSON response, setting [`GenerateOptions::json`], and parses it
+    /// into a [`Value`] for the response output. See [`JsonMode`].
+    pub fn with_json(mut self, json: JsonMode) -> Self {
+        self.json = Some(json);
+        self
+    }
+
+    /// Calls `validate` with each response, after post-processing.
+    /// Returning an error fails the node, or re-prompts if
+    /// [`LlmWeight::max_attempts`] allows it.
+    pub fn with_validator(
+        mut self,
+        validate: impl Fn(&str) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.validate = Some(Arc::new(validate));
+        self
+    }
+
+    /// Re-prompts when a response is invalid, up to `max_attempts` prompts in total.
+    /// A response is invalid if it is not valid JSON or does not match the schema
+    /// in JSON mode, or if it fails the validator.
+    ///
+    /// Each retry sends the original prompt with the error appended,
+    /// so the model can correct itself.
+    /// If every attempt fails, the last error is returned.
+    /// Backend errors are not retried, see [`RetryBackend`] for that.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Adds a system prompt input, see [`LlmNode::system_prompt`].
+    pub fn with_system_prompt(mut self) -> Self {
+        self.system_prompt = true;
+        self
+    }
+
+    /// Streams the response using [`LlmBackend::generate_stream`], calling `on_chunk`
+    /// with each chunk as it arrives.
+    ///
+    /// Stores are written once the node finishes, so the response output
+    /// still holds the full response.
+    /// Streamed responses do not include reasoning.
+    pub fn with_stream(mut self, on_chunk: impl Fn(&str) + 'static) -> Self {
+        self.on_chunk = Some(Arc::new(on_chunk));
+        self
+    }
+
+    /// Streams the response using [`LlmBackend::generate_stream`], sending the
+    /// response so far to `partial` as each chunk arrives.
+    ///
+    /// The partial response is cleared when each generation starts,
+    /// including when re-prompting after an invalid response.
+    /// Can be combined with [`LlmWeight::with_stream`].
+    pub fn with_partial_response(mut self, partial: watch::Sender<String>) -> Self {
+        self.partial = Some(partial);
+        self
+    }
+
+    /// Calls `post_process` with each response, writing its result to the response output
+    /// instead. Use it to clean up responses, such as by trimming markdown code fences.
+    /// Returning an error fails the node.
+    ///
+    /// Runs before the response is parsed as JSON, see [`LlmWeight::with_json`].
+    pub fn with_post_process(
+        mut self,
+        post_process: impl Fn(String) -> Result<String, NodeError> + 'static,
+    ) -> Self {
+        self.post_process = Some(Arc::new(post_process));
+        self
+    }
+
+    /// Calls `approval` with each prompt before it is sent, such as to let a user
+    /// review it. The prompt output holds the prompt that was actually sent.
+    pub fn with_approval<F>(mut self, approval: impl Fn(&str) -> F + 'static) -> Self
+    where
+        F: Future<Output = PromptDecision> + 'static,
+    {
+        self.approval = Some(Arc::new(move |prompt| Box::new(Box::pin(approval(prompt)))));
+        self
     }
 }
 
@@ -124,20 +686,823 @@ impl<T: LlmBackend> AsyncNode for LlmWeight<T> {
         inputs: Vec<Value>,
     ) -> Box<dyn Future<Output = Result<Vec<Value>, NodeError>> + Unpin> {
         let backend = self.backend.clone();
+        let initialized = self.initialized.clone();
+        let allow_empty_prompt = self.allow_empty_prompt;
+        let max_prompt_length = self.max_prompt_length;
+        let count_prompt = self.count_prompt.clone();
+        let approval = self.approval.clone();
+        let on_chunk = self.on_chunk.clone();
+        let partial = self.partial.clone();
+        let post_process = self.post_process.clone();
+        let mut options = self.options.clone();
+        let timeout = self.timeout;
+        let cancel = self.cancel.clone();
+        let json = self.json.clone();
+        let validate = self.validate.clone();
+        let max_attempts = self.max_attempts.max(1);
+
+        if json.is_some() {
+            options.json = true;
+        }
 
         Box::new(Box::pin(async move {
-            let prompt = match inputs.first() {
-                Some(Value::String(prompt)) => prompt.clone(),
+            let mut prompt = inputs
+                .first()
+                .ok_or(NodeError::MissingInput(0))?
+                .as_str()?
+                .to_string();
+
+            match inputs.get(1) {
+                Some(Value::String(system)) if !system.is_empty() => {
+                    options.system = Some(system.clone())
+                }
+                Some(Value::String(_) | Value::Null) | None => {}
                 Some(v) => return Err(NodeError::ConversionError(v.clone())),
-                None => return Err(NodeError::MissingInput(0)),
-            };
+            }
+
+            if !allow_empty_prompt && prompt.trim().is_empty() {
+                return Err(NodeError::MissingInput(0));
+            }
+
+            if let Some(approval) = approval {
+                match approval(&prompt).await {
+                    PromptDecision::Approve => {}
+                    PromptDecision::Edit(edited) => prompt = edited,
+                    PromptDecision::Reject => {
+                        return Err(NodeError::InternalError("prompt rejected".to_string()))
+                    }
+                }
+            }
 
-            let response = backend
-                .generate(&prompt)
+            if let Some(max) = max_prompt_length {
+                let count = |text: &str| match &count_prompt {
+                    Some(count_prompt) => count_prompt(text),
+                    None => text.chars().count(),
+                };
+
+                let length = count(&prompt) + options.system.as_deref().map_or(0, count);
+
+                if length > max {
+                    return Err(NodeError::InternalError(format!(
+                        "Prompt length {} exceeds the limit of {}",
+                        length, max
+                    )));
+                }
+            }
+
+            initialized
+                .get_or_try_init(|| backend.init())
                 .await
-                .map_err(|e| NodeError::InternalError(format!("Failed to generate: {}", e)))?;
+                .map_err(NodeError::from)?;
+
+            let mut sent = prompt.clone();
+            let mut attempt = 1;
+
+            let (response, output) = loop {
+                let generation = async {
+                    if on_chunk.is_some() || partial.is_some() {
+                        until_cancelled(cancel.as_ref(), async {
+                            if let Some(partial) = &partial {
+                                partial.send_replace(String::new());
+                            }
+
+                            let mut stream = pin!(backend.generate_stream(&sent, &options));
+                            let mut text = String::new();
+
+                            while let Some(chunk) = stream.next().await {
+                                let chunk = chunk?;
+
+                                if let Some(on_chunk) = &on_chunk {
+                                    on_chunk(&chunk);
+                                }
+
+                                text.push_str(&chunk);
 
-            Ok(vec![Value::String(response)])
+                                if let Some(partial) = &partial {
+                                    partial.send_modify(|partial| partial.push_str(&chunk));
+                                }
+                            }
+
+                            Ok(GenerateOutput {
+                                text,
+                                ..Default::default()
+                            })
+                        })
+                        .await
+                    } else {
+                        match &cancel {
+                            Some(cancel) => {
+                                backend.generate_cancellable(&sent, &options, cancel).await
+                            }
+                            None => backend.generate_detailed(&sent, &options).await,
+                        }
+                    }
+                };
+
+                // On timeout the generation is dropped, cancelling it.
+                let res = match timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, generation)
+                        .await
+                        .unwrap_or_else(|_| {
+                            Err(GenerateError::Timeout(format!(
+                                "timed out after {:?}",
+                                timeout
+                            )))
+                        }),
+                    None => generation.await,
+                };
+
+                let mut output = res?;
+
+                if let Some(post_process) = &post_process {
+                    output.text = post_process(output.text)?;
+                }
+
+                match check_response(&output.text, json.as_ref(), validate.as_ref()) {
+                    Ok(response) => break (response, output),
+                    Err((error, _)) if attempt >= max_attempts => return Err(error),
+                    Err((_, problem)) => {
+                        debug!("Invalid response on attempt {}: {}", attempt, problem);
+                        attempt += 1;
+                        sent = correction_prompt(&prompt, &problem);
+                    }
+                }
+            };
+
+            // Ordered by output index.
+            Ok(vec![
+                response,
+                Value::String(sent),
+                Value::String(output.reasoning.unwrap_or_default()),
+                Value::Vec(output.usage.map(Usage::to_values).unwrap_or_default()),
+            ])
         }))
     }
+
+    fn type_name(&self) -> &str {
+        "lemon.llm"
+    }
+}
+
+/// Parses and validates a response, returning the error and a description
+/// of the problem to show the model if it is invalid.
+fn check_response(
+    text: &str,
+    json: Option<&JsonMode>,
+    validate: Option<&ValidateFn>,
+) -> Result<Value, (NodeError, String)> {
+    let response = match json {
+        Some(json) => json.parse(text).map_err(|e| {
+            let problem = match &e {
+                NodeError::InternalError(problem) => problem.clone(),
+                _ => "The response is not valid JSON".to_string(),
+            };
+            (e, problem)
+        })?,
+        None => Value::String(text.to_string()),
+    };
+
+    if let Some(validate) = validate {
+        validate(text).map_err(|problem| (NodeError::InternalError(problem.clone()), problem))?;
+    }
+
+    Ok(response)
+}
+
+/// Appends the problem with the previous response to the prompt.
+fn correction_prompt(prompt: &str, problem: &str) -> String {
+    format!(
+        "{}\n\nYour previous response was invalid: {}\nRespond again, correcting the problem.",
+        prompt, problem
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use lemon_graph::Executor;
+
+    use crate::mock::MockBackend;
+
+    use super::*;
+
+    /// Uppercases prompts, failing if the prompt contains "fail".
+    fn test_backend() -> MockBackend {
+        MockBackend::from_fn(|prompt| {
+            if prompt.contains("fail") {
+                Err(GenerateError::BackendError(prompt.to_string()))
+            } else {
+                Ok(prompt.to_uppercase())
+            }
+        })
+    }
+
+    /// Streams each word of the prompt, failing at the word "fail".
+    struct TestStreamBackend;
+
+    impl LlmBackend for TestStreamBackend {
+        async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+            Ok(prompt.to_string())
+        }
+
+        fn generate_stream(
+            &self,
+            prompt: &str,
+            _options: &GenerateOptions,
+        ) -> impl Stream<Item = Result<String, GenerateError>> {
+            let chunks = prompt
+                .split_inclusive(' ')
+                .map(|word| match word.trim() {
+                    "fail" => Err(GenerateError::BackendError("failed".to_string())),
+                    _ => Ok(word.to_string()),
+                })
+                .collect::<Vec<_>>();
+
+            // Yields between chunks, as a network stream would.
+            stream::iter(chunks).then(|chunk| async {
+                tokio::task::yield_now().await;
+                chunk
+            })
+        }
+    }
+
+    /// Responds with the max tokens option.
+    struct TestOptionsBackend;
+
+    impl LlmBackend for TestOptionsBackend {
+        async fn generate(&self, _prompt: &str) -> Result<String, GenerateError> {
+            Ok("default".to_string())
+        }
+
+        async fn generate_with(
+            &self,
+            _prompt: &str,
+            options: &GenerateOptions,
+        ) -> Result<String, GenerateError> {
+            Ok(format!("{:?}", options.max_tokens))
+        }
+    }
+
+    struct TestReasoningBackend;
+
+    impl LlmBackend for TestReasoningBackend {
+        async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+            Ok(prompt.to_uppercase())
+        }
+
+        async fn generate_detailed(
+            &self,
+            prompt: &str,
+            _options: &GenerateOptions,
+        ) -> Result<GenerateOutput, GenerateError> {
+            Ok(GenerateOutput {
+                text: prompt.to_uppercase(),
+                reasoning: Some("thinking".to_string()),
+                usage: Some(Usage {
+                    prompt_tokens: 1,
+                    completion_tokens: 2,
+                    ..Default::default()
+                }),
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct TestInitBackend {
+        inits: std::sync::atomic::AtomicUsize,
+    }
+
+    impl LlmBackend for TestInitBackend {
+        async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+            Ok(prompt.to_string())
+        }
+
+        async fn init(&self) -> Result<(), GenerateError> {
+            self.inits.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_init_once() {
+        let backend = Arc::new(TestInitBackend::default());
+        let weight = LlmWeight::new(backend.clone());
+
+        weight.run(vec!["a".to_string().into()]).await.unwrap();
+        weight.run(vec!["b".to_string().into()]).await.unwrap();
+
+        assert_eq!(backend.inits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_llm_empty_prompt() {
+        let backend = Arc::new(test_backend());
+
+        let weight = LlmWeight::new(backend.clone());
+        let res = weight.run(vec![String::new().into()]).await;
+        assert!(matches!(res, Err(NodeError::MissingInput(0))));
+        let res = weight.run(vec![" \n".to_string().into()]).await;
+        assert!(matches!(res, Err(NodeError::MissingInput(0))));
+        assert!(weight.run(vec!["hi".to_string().into()]).await.is_ok());
+
+        let weight = weight.with_allow_empty_prompt(true);
+        assert!(weight.run(vec![" ".to_string().into()]).await.is_ok());
+
+        // Empty prompts are only sent when allowed.
+        assert_eq!(backend.prompts(), vec!["hi", " "]);
+    }
+
+    #[tokio::test]
+    async fn test_llm_max_prompt_length() {
+        let backend = Arc::new(test_backend());
+        let weight = LlmWeight::new(backend.clone()).with_max_prompt_length(5);
+
+        assert!(weight.run(vec!["hello".to_string().into()]).await.is_ok());
+
+        let res = weight.run(vec!["hello!".to_string().into()]).await;
+        assert!(matches!(
+            res,
+            Err(NodeError::InternalError(e)) if e == "Prompt length 6 exceeds the limit of 5"
+        ));
+
+        // Counts words instead of characters.
+        let weight = weight.with_prompt_counter(|text| text.split_whitespace().count());
+        assert!(weight
+            .run(vec!["a much longer prompt".to_string().into()])
+            .await
+            .is_ok());
+        assert!(weight
+            .run(vec!["one two three four five six".to_string().into()])
+            .await
+            .is_err());
+
+        // Rejected prompts are not sent.
+        assert_eq!(backend.prompts(), vec!["hello", "a much longer prompt"]);
+    }
+
+    #[tokio::test]
+    async fn test_llm_approval() {
+        let weight = LlmWeight::new(Arc::new(test_backend())).with_approval(|prompt| {
+            let decision = match prompt {
+                "secret" => PromptDecision::Reject,
+                "hi" => PromptDecision::Edit("hello".to_string()),
+                _ => PromptDecision::Approve,
+            };
+
+            async move { decision }
+        });
+
+        let outputs = weight.run(vec!["hi".to_string().into()]).await.unwrap();
+        assert_eq!(outputs[0], "HELLO".to_string().into());
+        assert_eq!(outputs[1], "hello".to_string().into());
+
+        let outputs = weight.run(vec!["bye".to_string().into()]).await.unwrap();
+        assert_eq!(outputs[0], "BYE".to_string().into());
+
+        let res = weight.run(vec!["secret".to_string().into()]).await;
+        assert!(matches!(res, Err(NodeError::InternalError(e)) if e == "prompt rejected"));
+    }
+
+    fn store_value(graph: &Graph, store: StoreWrapper) -> Value {
+        match &graph[store.0] {
+            GraphNode::Store(value) => value.clone(),
+            _ => panic!("Not a store"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_outputs() {
+        let mut graph = Graph::default();
+
+        let weight = LlmWeight::new(Arc::new(test_backend()))
+            .with_outputs(vec![LlmOutput::Response, LlmOutput::Prompt]);
+        let llm = LlmNode::new(&mut graph, weight);
+
+        let input = llm.input(&graph).unwrap();
+        input.set_value(&mut graph, "hello".to_string().into());
+
+        Executor::execute(&mut graph, llm.0).await.unwrap();
+
+        let response = llm.output(&graph).unwrap();
+        assert_eq!(store_value(&graph, response), "HELLO".to_string().into());
+
+        let prompt = llm.output_of(&graph, LlmOutput::Prompt).unwrap();
+        assert_eq!(store_value(&graph, prompt), "hello".to_string().into());
+    }
+
+    #[tokio::test]
+    async fn test_llm_options() {
+        let weight = LlmWeight::new(Arc::new(TestOptionsBackend)).with_options(GenerateOptions {
+            max_tokens: Some(8),
+            ..Default::default()
+        });
+
+        let outputs = weight.run(vec!["hi".to_string().into()]).await.unwrap();
+        assert_eq!(outputs[0], "Some(8)".to_string().into());
+
+        // Options are ignored by backends that do not support them.
+        let outputs = LlmWeight::new(Arc::new(test_backend()))
+            .with_options(weight.options.clone())
+            .run(vec!["hi".to_string().into()])
+            .await
+            .unwrap();
+        assert_eq!(outputs[0], "HI".to_string().into());
+    }
+
+    #[tokio::test]
+    async fn test_llm_system_prompt() {
+        let mut graph = Graph::default();
+
+        let backend = Arc::new(MockBackend::fixed("ok"));
+        let weight = LlmWeight::new(backend.clone()).with_system_prompt();
+        let llm = LlmNode::new(&mut graph, weight);
+
+        let input = llm.input(&graph).unwrap();
+        input.set_value(&mut graph, "hello".to_string().into());
+
+        Executor::execute(&mut graph, llm.0).await.unwrap();
+
+        let system_prompt = llm.system_prompt(&graph).unwrap();
+        system_prompt.set_value(&mut graph, "Be brief.".to_string().into());
+
+        Executor::execute(&mut graph, llm.0).await.unwrap();
+
+        system_prompt.set_value(&mut graph, Value::Null);
+
+        Executor::execute(&mut graph, llm.0).await.unwrap();
+
+        // An empty or null system prompt is not sent.
+        assert_eq!(
+            backend.prompts(),
+            vec!["hello", "Be brief.\n\nhello", "hello"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_llm_timeout() {
+        let backend = Arc::new(MockBackend::fixed("ok").with_delay(Duration::from_secs(10)));
+        let weight = LlmWeight::new(backend).with_timeout(Duration::from_millis(10));
+
+        let start = std::time::Instant::now();
+        let res = weight.run(vec!["hi".to_string().into()]).await;
+
+        assert!(matches!(res, Err(NodeError::Timeout(e)) if e.contains("timed out")));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_llm_error_category() {
+        let backend = Arc::new(MockBackend::scripted([Err(GenerateError::RateLimited(
+            "slow down".to_string(),
+        ))]));
+        let res = LlmWeight::new(backend)
+            .run(vec!["hi".to_string().into()])
+            .await;
+        assert!(matches!(res, Err(NodeError::RateLimited(e)) if e == "slow down"));
+
+        let error = |e: GenerateError| NodeError::from(e);
+        assert!(matches!(
+            error(GenerateError::Auth("invalid key".to_string())),
+            NodeError::Auth(e) if e == "invalid key"
+        ));
+        assert!(matches!(
+            error(GenerateError::BackendError("failed".to_string())),
+            NodeError::Backend(e) if e == "failed"
+        ));
+        assert!(matches!(
+            error(GenerateError::Transient("busy".to_string())),
+            NodeError::Backend(e) if e == "Transient error: busy"
+        ));
+    }
+
+    #[test]
+    fn test_error_from_status() {
+        let error = |status| GenerateError::from_status(status, "message".to_string());
+
+        assert!(matches!(error(429), GenerateError::RateLimited(e) if e == "message"));
+        assert!(matches!(error(401), GenerateError::Auth(_)));
+        assert!(matches!(error(504), GenerateError::Timeout(_)));
+        assert!(matches!(error(500), GenerateError::BackendError(_)));
+        assert!(!error(403).is_retryable());
+        assert!(error(429).is_retryable());
+        assert!(error(408).is_retryable());
+        assert!(error(503).is_retryable());
+
+        for status in [400, 404, 422] {
+            assert!(matches!(error(status), GenerateError::Permanent(_)));
+            assert!(!error(status).is_retryable());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_cancellable() {
+        let backend = MockBackend::fixed("ok").with_delay(Duration::from_secs(10));
+        let options = GenerateOptions::default();
+        let cancel = CancellationToken::new();
+
+        let start = std::time::Instant::now();
+        let (res, _) = tokio::join!(
+            backend.generate_cancellable("hi", &options, &cancel),
+            async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                cancel.cancel();
+            }
+        );
+
+        assert!(matches!(res, Err(GenerateError::BackendError(e)) if e == "cancelled"));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_llm_cancel() {
+        let backend = Arc::new(MockBackend::fixed("ok").with_delay(Duration::from_secs(10)));
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let weight = LlmWeight::new(backend.clone()).with_cancellation(cancel.clone());
+        let res = weight.run(vec!["hi".to_string().into()]).await;
+        assert!(matches!(res, Err(NodeError::Backend(e)) if e == "cancelled"));
+
+        let weight = LlmWeight::new(backend)
+            .with_stream(|_| {})
+            .with_cancellation(cancel);
+        let res = weight.run(vec!["hi".to_string().into()]).await;
+        assert!(matches!(res, Err(NodeError::Backend(e)) if e == "cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_llm_json() {
+        let backend = Arc::new(MockBackend::scripted([
+            Ok(r#"{"answer": 42}"#.to_string()),
+            Ok("The answer is 42.".to_string()),
+        ]));
+        let weight = LlmWeight::new(backend).with_json(JsonMode::default());
+
+        let outputs = weight.run(vec!["hi".to_string().into()]).await.unwrap();
+        assert_eq!(
+            outputs[0],
+            Value::Map([("answer".to_string(), Value::USize(42))].into())
+        );
+
+        let res = weight.run(vec!["hi".to_string().into()]).await;
+        assert!(matches!(
+            res,
+            Err(NodeError::ConversionError(Value::String(text))) if text == "The answer is 42."
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_llm_json_correction() {
+        let backend = Arc::new(MockBackend::scripted([
+            Ok("The answer is 42.".to_string()),
+            Ok(r#"{"answer": 42}"#.to_string()),
+        ]));
+        let weight = LlmWeight::new(backend.clone())
+            .with_json(JsonMode::default())
+            .with_max_attempts(2);
+
+        let outputs = weight.run(vec!["hi".to_string().into()]).await.unwrap();
+        assert_eq!(
+            outputs[0],
+            Value::Map([("answer".to_string(), Value::USize(42))].into())
+        );
+
+        // The second prompt includes the problem, and is the prompt output.
+        let prompts = backend.prompts();
+        assert_eq!(prompts.len(), 2);
+        assert_eq!(prompts[0], "hi");
+        assert!(prompts[1].starts_with("hi\n\n"));
+        assert!(prompts[1].contains("not valid JSON"));
+        assert_eq!(outputs[1], Value::String(prompts[1].clone()));
+    }
+
+    #[tokio::test]
+    async fn test_llm_validator() {
+        let backend = Arc::new(MockBackend::fixed("maybe"));
+        let weight = LlmWeight::new(backend.clone())
+            .with_validator(|text| match text {
+                "yes" | "no" => Ok(()),
+                _ => Err("answer yes or no".to_string()),
+            })
+            .with_max_attempts(3);
+
+        let res = weight.run(vec!["hi".to_string().into()]).await;
+        assert!(matches!(res, Err(NodeError::InternalError(e)) if e == "answer yes or no"));
+        assert_eq!(backend.prompts().len(), 3);
+        assert!(backend.prompts()[2].contains("answer yes or no"));
+    }
+
+    /// Removes a markdown code fence around the text, if there is one.
+    fn strip_fence(text: String) -> Result<String, NodeError> {
+        let trimmed = text.trim();
+
+        match trimmed
+            .strip_prefix("```")
+            .and_then(|rest| rest.strip_suffix("```"))
+        {
+            // Skip the language tag on the opening line.
+            Some(inner) => Ok(inner
+                .split_once('\n')
+                .map_or(inner, |(_, body)| body)
+                .trim()
+                .to_string()),
+            None => Ok(text),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_post_process() {
+        let mut graph = Graph::default();
+
+        let backend = Arc::new(MockBackend::fixed("```json\n{\"answer\": 42}\n```"));
+        let weight = LlmWeight::new(backend).with_post_process(strip_fence);
+        let llm = LlmNode::new(&mut graph, weight);
+
+        let input = llm.input(&graph).unwrap();
+        input.set_value(&mut graph, "hi".to_string().into());
+
+        Executor::execute(&mut graph, llm.0).await.unwrap();
+
+        let response = llm.output(&graph).unwrap();
+        assert_eq!(
+            store_value(&graph, response),
+            r#"{"answer": 42}"#.to_string().into()
+        );
+
+        // Errors fail the node.
+        let weight = LlmWeight::new(Arc::new(MockBackend::fixed("")))
+            .with_post_process(|_| Err(NodeError::InternalError("empty".to_string())));
+        let res = weight.run(vec!["hi".to_string().into()]).await;
+        assert!(matches!(res, Err(NodeError::InternalError(e)) if e == "empty"));
+    }
+
+    #[tokio::test]
+    async fn test_default_stream() {
+        let chunks = test_backend()
+            .generate_stream("hi", &GenerateOptions::default())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].as_deref().ok(), Some("HI"));
+    }
+
+    #[tokio::test]
+    async fn test_llm_stream() {
+        let chunks = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let weight = LlmWeight::new(Arc::new(TestStreamBackend)).with_stream({
+            let chunks = chunks.clone();
+            move |chunk| chunks.lock().unwrap().push(chunk.to_string())
+        });
+
+        let outputs = weight
+            .run(vec!["one two three".to_string().into()])
+            .await
+            .unwrap();
+        assert_eq!(outputs[0], "one two three".to_string().into());
+        assert_eq!(*chunks.lock().unwrap(), vec!["one ", "two ", "three"]);
+
+        // Errors mid-stream fail the node, instead of truncating the response.
+        chunks.lock().unwrap().clear();
+        let res = weight.run(vec!["one fail three".to_string().into()]).await;
+        assert!(res.is_err());
+        assert_eq!(*chunks.lock().unwrap(), vec!["one "]);
+    }
+
+    #[tokio::test]
+    async fn test_llm_partial_response() {
+        let mut graph = Graph::default();
+
+        let weight = LlmWeight::new(Arc::new(TestStreamBackend));
+        let (llm, mut partial) = LlmNode::new_streaming(&mut graph, weight);
+        llm.input(&graph)
+            .unwrap()
+            .set_value(&mut graph, "one two three".to_string().into());
+
+        let (res, updates) = tokio::join!(Executor::execute(&mut graph, llm.0), async {
+            let mut updates = Vec::new();
+
+            while partial.changed().await.is_ok() {
+                updates.push(partial.borrow_and_update().clone());
+
+                if updates
+                    .last()
+                    .is_some_and(|update| update == "one two three")
+                {
+                    break;
+                }
+            }
+
+            updates
+        });
+        res.unwrap();
+
+        // The partial response grows as chunks arrive.
+        assert!(updates.contains(&"one two ".to_string()), "{:?}", updates);
+        assert_eq!(updates.last().map(String::as_str), Some("one two three"));
+
+        let output = llm.output(&graph).unwrap();
+        assert_eq!(
+            store_value(&graph, output),
+            "one two three".to_string().into()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_llm_reasoning() {
+        let mut graph = Graph::default();
+
+        let weight = LlmWeight::new(Arc::new(TestReasoningBackend)).with_outputs(vec![
+            LlmOutput::Response,
+            LlmOutput::Reasoning,
+            LlmOutput::Usage,
+        ]);
+        let (llm, ports) = LlmNode::new_with_ports(&mut graph, weight);
+        ports
+            .input
+            .set_value(&mut graph, "hello".to_string().into());
+
+        Executor::execute(&mut graph, llm.0).await.unwrap();
+
+        let response = ports.response.unwrap();
+        assert_eq!(store_value(&graph, response), "HELLO".to_string().into());
+
+        let reasoning = ports.reasoning.unwrap();
+        assert_eq!(
+            store_value(&graph, reasoning),
+            "thinking".to_string().into()
+        );
+
+        let usage = ports.usage.unwrap();
+        assert_eq!(
+            store_value(&graph, usage),
+            Value::Vec(vec![Value::USize(1), Value::USize(2)])
+        );
+
+        // Backends without reasoning output an empty string.
+        let outputs = LlmWeight::new(Arc::new(test_backend()))
+            .run(vec!["hi".to_string().into()])
+            .await
+            .unwrap();
+        assert_eq!(outputs[2], String::new().into());
+        assert_eq!(outputs[3], Value::Vec(Vec::new()));
+    }
+
+    #[test]
+    fn test_llm_ports() {
+        let mut graph = Graph::default();
+        let (llm, ports) =
+            LlmNode::new_with_ports(&mut graph, LlmWeight::new(Arc::new(test_backend())));
+
+        assert_eq!(ports.input.0, llm.input(&graph).unwrap().0);
+        assert_eq!(ports.response.unwrap().0, llm.output(&graph).unwrap().0);
+        assert!(ports.prompt.is_none());
+    }
+
+    #[test]
+    fn test_llm_default_outputs() {
+        let mut graph = Graph::default();
+        let llm = LlmNode::new(&mut graph, LlmWeight::new(Arc::new(test_backend())));
+
+        assert!(llm.output(&graph).is_ok());
+        assert!(llm.output_of(&graph, LlmOutput::Prompt).is_err());
+    }
+
+    fn prompts(prompts: &[&str]) -> Vec<String> {
+        prompts.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_batch_order() {
+        let responses = test_backend()
+            .generate_batch(&prompts(&["a", "b", "c"]), BatchMode::FailFast)
+            .await
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(responses, vec!["A", "B", "C"]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_fail_fast() {
+        let res = test_backend()
+            .generate_batch(&prompts(&["a", "fail", "c"]), BatchMode::FailFast)
+            .await;
+
+        assert!(matches!(res, Err(GenerateError::BackendError(e)) if e == "fail"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_collect_all() {
+        let responses = test_backend()
+            .generate_batch(&prompts(&["a", "fail", "c"]), BatchMode::CollectAll)
+            .await
+            .unwrap();
+
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0].as_deref().ok(), Some("A"));
+        assert!(responses[1].is_err());
+        assert_eq!(responses[2].as_deref().ok(), Some("C"));
+    }
 }
@@ -0,0 +1,192 @@
+//! Parsing of streamed responses, as newline-delimited JSON or server-sent events.
+
+use std::fmt::Display;
+
+use futures_util::{stream, Stream, StreamExt};
+
+use crate::GenerateError;
+
+/// Splits a stream of bytes into lines, without their line endings.
+///
+/// The last line is yielded even if it is not terminated.
+/// An error from the stream is yielded, and ends the lines.
+pub(crate) fn lines<B: AsRef<[u8]>, E: Display>(
+    bytes: impl Stream<Item = Result<B, E>> + Unpin,
+) -> impl Stream<Item = Result<String, GenerateError>> {
+    stream::unfold(
+        (bytes, Vec::new(), false),
+        |(mut bytes, mut buffer, done)| async move {
+            loop {
+                if done {
+                    return None;
+                }
+
+                let Some(end) = buffer.iter().position(|b| *b == b'\n') else {
+                    match bytes.next().await {
+                        Some(Ok(chunk)) => buffer.extend_from_slice(chunk.as_ref()),
+                        Some(Err(e)) => {
+                            let error = GenerateError::BackendError(e.to_string());
+                            return Some((Err(error), (bytes, buffer, true)));
+                        }
+                        // Yield the last line, if it was not terminated.
+                        None if !buffer.is_empty() => buffer.push(b'\n'),
+                        None => return None,
+                    }
+                    continue;
+                };
+
+                let line = buffer.drain(..=end).collect::<Vec<_>>();
+                let line = String::from_utf8_lossy(&line)
+                    .trim_end_matches(['\n', '\r'])
+                    .to_string();
+
+                return Some((Ok(line), (bytes, buffer, false)));
+            }
+        },
+    )
+}
+
+/// A server-sent event.
+#[cfg(any(feature = "openai", feature = "replicate"))]
+#[derive(Debug, Default)]
+pub(crate) struct Event {
+    pub name: Option<String>,
+    /// Each `data` line, joined by newlines.
+    pub data: String,
+}
+
+/// Parses lines of server-sent events, yielding each event once an empty line
+/// dispatches it. Comments and unknown fields are ignored.
+#[cfg(any(feature = "openai", feature = "replicate"))]
+pub(crate) fn events(
+    lines: impl Stream<Item = Result<String, GenerateError>>,
+) -> impl Stream<Item = Result<Event, GenerateError>> {
+    stream::unfold(
+        (Box::pin(lines.fuse()), None, Vec::new()),
+        |(mut lines, mut name, mut data)| async move {
+            loop {
+                let line = match lines.next().await {
+                    Some(Ok(line)) => line,
+                    Some(Err(e)) => return Some((Err(e), (lines, name, data))),
+                    // Dispatch the last event, if it was not terminated.
+                    None if name.is_some() || !data.is_empty() => String::new(),
+                    None => return None,
+                };
+
+                // Comments start with a colon.
+                if line.starts_with(':') {
+                    continue;
+                }
+
+                if !line.is_empty() {
+                    let (field, value) = line.split_once(':').unwrap_or((&line, ""));
+                    let value = value.strip_prefix(' ').unwrap_or(value);
+
+                    match field {
+                        "event" => name = Some(value.to_string()),
+                        "data" => data.push(value.to_string()),
+                        _ => {}
+                    }
+
+                    continue;
+                }
+
+                let event = Event {
+                    name: name.take(),
+                    data: std::mem::take(&mut data).join("\n"),
+                };
+
+                return Some((Ok(event), (lines, name, data)));
+            }
+        },
+    )
+}
+
+/// What a parsed stream item means for the stream.
+pub(crate) enum Parsed<T> {
+    /// A chunk to yield.
+    Item(T),
+    /// The last chunk to yield.
+    Last(T),
+    /// Nothing to yield.
+    Skip,
+    /// The response is done.
+    Done,
+}
+
+/// Parses each item of a stream with `parse`, until it returns [`Parsed::Done`] or
+/// [`Parsed::Last`], or an error.
+///
+/// If the stream ends before then, an error of `unfinished` is yielded.
+pub(crate) fn parse_until_done<T, U>(
+    items: impl Stream<Item = Result<T, GenerateError>>,
+    parse: impl FnMut(T) -> Result<Parsed<U>, GenerateError>,
+    unfinished: &'static str,
+) -> impl Stream<Item = Result<U, GenerateError>> {
+    stream::unfold(
+        (Box::pin(items), parse, false),
+        move |(mut items, mut parse, done)| async move {
+            loop {
+                if done {
+                    return None;
+                }
+
+                let item = match items.next().await {
+                    Some(Ok(item)) => item,
+                    Some(Err(e)) => return Some((Err(e), (items, parse, true))),
+                    None => {
+                        let error = GenerateError::BackendError(unfinished.to_string());
+                        return Some((Err(error), (items, parse, true)));
+                    }
+                };
+
+                match parse(item) {
+                    Ok(Parsed::Item(chunk)) => return Some((Ok(chunk), (items, parse, false))),
+                    Ok(Parsed::Last(chunk)) => return Some((Ok(chunk), (items, parse, true))),
+                    Ok(Parsed::Skip) => continue,
+                    Ok(Parsed::Done) => return None,
+                    Err(e) => return Some((Err(e), (items, parse, true))),
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(chunks: &[&'static str]) -> impl Stream<Item = Result<&'static str, String>> + Unpin {
+        stream::iter(chunks.iter().map(|chunk| Ok(*chunk)).collect::<Vec<_>>())
+    }
+
+    #[tokio::test]
+    async fn test_lines() {
+        let lines = lines(bytes(&["one\r\ntw", "o\n\nthr", "ee"]))
+            .collect::<Vec<_>>()
+            .await;
+
+        let lines = lines.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(lines, vec!["one", "two", "", "three"]);
+    }
+
+    #[cfg(any(feature = "openai", feature = "replicate"))]
+    #[tokio::test]
+    async fn test_events() {
+        let events = events(lines(bytes(&[
+            ": comment\nevent: output\ndata: a\ndata: b\n\n",
+            "data: c",
+        ])))
+        .collect::<Vec<_>>()
+        .await;
+
+        let events = events.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].name.as_deref(), Some("output"));
+        assert_eq!(events[0].data, "a\nb");
+
+        // The last event is dispatched, even if it was not terminated.
+        assert_eq!(events[1].name, None);
+        assert_eq!(events[1].data, "c");
+    }
+}
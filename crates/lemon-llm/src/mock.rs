@@ -0,0 +1,204 @@
+//! A scriptable backend for testing graphs without a model.
+
+use std::{collections::VecDeque, sync::Mutex, time::Duration};
+
+use futures_util::{stream, Stream, StreamExt};
+
+use crate::{
+    ChatMessage, GenerateError, GenerateOptions, LlmBackend, LlmChatBackend, LlmEmbeddingBackend,
+    Tool, ToolResponse,
+};
+
+pub type MockFn = Box<dyn Fn(&str) -> Result<String, GenerateError> + Send + Sync>;
+
+/// Backend returning pre-programmed responses.
+/// Every prompt it receives is recorded, see [`MockBackend::prompts`].
+pub struct MockBackend {
+    responder: Responder,
+    embedding: Option<Vec<f32>>,
+    delay: Option<Duration>,
+    stream_words: bool,
+    prompts: Mutex<Vec<String>>,
+}
+
+enum Responder {
+    Fixed(String),
+    Fn(MockFn),
+    Script(Mutex<VecDeque<Result<String, GenerateError>>>),
+}
+
+impl MockBackend {
+    /// Responds to every prompt with `response`.
+    pub fn fixed(response: impl Into<String>) -> Self {
+        Self::new(Responder::Fixed(response.into()))
+    }
+
+    /// Responds to each prompt by calling `f`.
+    pub fn from_fn(
+        f: impl Fn(&str) -> Result<String, GenerateError> + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(Responder::Fn(Box::new(f)))
+    }
+
+    /// Responds with each result in order, regardless of the prompt.
+    /// Once the script runs out, generation returns a [`GenerateError::Permanent`] error.
+    pub fn scripted(script: impl IntoIterator<Item = Result<String, GenerateError>>) -> Self {
+        Self::new(Responder::Script(Mutex::new(script.into_iter().collect())))
+    }
+
+    fn new(responder: Responder) -> Self {
+        Self {
+            responder,
+            embedding: None,
+            delay: None,
+            stream_words: false,
+            prompts: Mutex::default(),
+        }
+    }
+
+    /// Embeds all text as `embedding`.
+    /// Without an embedding, embedding returns a [`GenerateError::Permanent`] error.
+    pub fn with_embedding(mut self, embedding: Vec<f32>) -> Self {
+        self.embedding = Some(embedding);
+        self
+    }
+
+    /// Waits for `delay` before each response.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Streams each response word by word, instead of as a single chunk.
+    pub fn with_word_stream(mut self) -> Self {
+        self.stream_words = true;
+        self
+    }
+
+    /// Returns every prompt received, in order.
+    pub fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().unwrap().clone()
+    }
+}
+
+impl LlmBackend for MockBackend {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+
+        match &self.responder {
+            Responder::Fixed(response) => Ok(response.clone()),
+            Responder::Fn(f) => f(prompt),
+            Responder::Script(script) => {
+                script
+                    .lock()
+                    .unwrap()
+                    .pop_front()
+                    .unwrap_or(Err(GenerateError::Permanent(
+                        "No scripted responses left".to_string(),
+                    )))
+            }
+        }
+    }
+
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Stream<Item = Result<String, GenerateError>> {
+        let stream_words = self.stream_words;
+
+        stream::once(self.generate_with(prompt, options)).flat_map(move |res| {
+            let chunks = match res {
+                Ok(text) if stream_words => text
+                    .split_inclusive(' ')
+                    .map(|word| Ok(word.to_string()))
+                    .collect(),
+                res => vec![res],
+            };
+
+            stream::iter(chunks)
+        })
+    }
+}
+
+/// Responds to the content of the last message.
+impl LlmChatBackend for MockBackend {
+    async fn generate_chat(&self, messages: &[ChatMessage]) -> Result<String, GenerateError> {
+        let last = messages
+            .last()
+            .map(|message| message.content.as_str())
+            .unwrap_or_default();
+
+        self.generate(last).await
+    }
+
+    /// Responses of the form `{"name": ..., "arguments": ...}`, naming one of `tools`,
+    /// are returned as a tool call.
+    async fn generate_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[Tool],
+    ) -> Result<ToolResponse, GenerateError> {
+        let text = self.generate_chat(messages).await?;
+
+        let call = serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|json| {
+                let name = json.get("name")?.as_str()?.to_string();
+                let arguments = json.get("arguments")?.clone();
+                Some((name, arguments))
+            })
+            .filter(|(name, _)| tools.iter().any(|tool| &tool.name == name));
+
+        match call {
+            Some((name, arguments)) => ToolResponse::tool_call(name, arguments),
+            None => Ok(ToolResponse::Text(text)),
+        }
+    }
+}
+
+impl LlmEmbeddingBackend for MockBackend {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, GenerateError> {
+        self.prompts.lock().unwrap().push(text.to_string());
+
+        self.embedding
+            .clone()
+            .ok_or(GenerateError::Permanent("No embedding".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_fixed() {
+        let backend = MockBackend::fixed("hi");
+
+        assert_eq!(backend.generate("a").await.unwrap(), "hi");
+        assert_eq!(backend.generate("b").await.unwrap(), "hi");
+        assert_eq!(backend.prompts(), vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_scripted() {
+        let backend = MockBackend::scripted([
+            Ok("one".to_string()),
+            Err(GenerateError::Transient("two".to_string())),
+        ]);
+
+        assert_eq!(backend.generate("a").await.unwrap(), "one");
+        assert!(matches!(
+            backend.generate("b").await,
+            Err(GenerateError::Transient(_))
+        ));
+        assert!(matches!(
+            backend.generate("c").await,
+            Err(GenerateError::Permanent(_))
+        ));
+    }
+}
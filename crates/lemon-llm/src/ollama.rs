@@ -1,14 +1,47 @@
-use futures_util::StreamExt;
-use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Debug, Display},
+    pin::pin,
+    time::Duration,
+};
+
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize, Serializer};
 use tracing::{debug, info};
 
-use crate::{GenerateError, LlmBackend};
+use crate::{
+    lines::{lines, parse_until_done, Parsed},
+    ChatMessage, GenerateError, GenerateOptions, GenerateOutput, LlmBackend, LlmChatBackend,
+    LlmEmbeddingBackend, Tool, ToolResponse, Usage,
+};
 
 const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
 
+/// Seed used when generating deterministically.
+const DETERMINISTIC_SEED: i64 = 0;
+
+/// How long the model stays loaded after a request, by default.
+/// Matches Ollama's own default, but is sent explicitly so it does not depend
+/// on the server's configuration.
+const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(5 * 60);
+
+/// First Ollama version that accepts a JSON schema in the `format` field.
+const STRUCTURED_OUTPUT_VERSION: (u32, u32, u32) = (0, 5, 0);
+
 pub struct OllamaBackend {
     pub model: OllamaModel,
     pub url: String,
+    /// Uses a fixed seed and a temperature of 0, so the same prompt
+    /// produces the same response.
+    ///
+    /// Ollama only guarantees this for the same model on the same hardware,
+    /// as results can still differ between CPU and GPU inference.
+    pub deterministic: bool,
+    /// How long Ollama keeps the model loaded after each request,
+    /// avoiding a reload between calls.
+    /// [`Duration::MAX`] keeps it loaded indefinitely, and `None` uses the server's default.
+    pub keep_alive: Option<Duration>,
+    /// Client used for every request, so connections are reused.
+    pub client: reqwest::Client,
 }
 
 impl Default for OllamaBackend {
@@ -16,6 +49,9 @@ impl Default for OllamaBackend {
         Self {
             model: OllamaModel::default(),
             url: DEFAULT_OLLAMA_URL.to_string(),
+            deterministic: false,
+            keep_alive: Some(DEFAULT_KEEP_ALIVE),
+            client: reqwest::Client::new(),
         }
     }
 }
@@ -33,88 +69,561 @@ pub enum OllamaModel {
     Mixtral,
 }
 
+impl OllamaBackend {
+    pub fn with_deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
+
+    pub fn with_keep_alive(mut self, keep_alive: Option<Duration>) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    /// Deterministic mode takes precedence over the temperature in `options`.
+    fn request(&self, prompt: &str, options: &GenerateOptions) -> OllamaGenerate {
+        let mut ollama_options = OllamaOptions {
+            seed: None,
+            temperature: options.temperature,
+            top_p: options.top_p,
+            num_predict: options.max_tokens,
+            stop: options.stop.clone(),
+        };
+
+        if self.deterministic {
+            ollama_options.seed = Some(DETERMINISTIC_SEED);
+            ollama_options.temperature = Some(0.0);
+        }
+
+        OllamaGenerate {
+            model: self.model,
+            prompt: prompt.to_string(),
+            system: options.system.clone(),
+            format: options.json.then(|| "json".into()),
+            options: (ollama_options != OllamaOptions::default()).then_some(ollama_options),
+            keep_alive: self.keep_alive,
+            stream: true,
+        }
+    }
+
+    /// Returns the version of the Ollama server.
+    pub async fn version(&self) -> Result<String, GenerateError> {
+        let response = self
+            .client
+            .get(format!("{}/api/version", self.url))
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        let version = response
+            .json::<OllamaVersion>()
+            .await
+            .map_err(|e| GenerateError::BackendError(e.to_string()))?;
+
+        Ok(version.version)
+    }
+
+    /// Returns the names of the models available locally, such as `mistral:latest`.
+    pub async fn list_models(&self) -> Result<Vec<String>, GenerateError> {
+        let response = self
+            .client
+            .get(format!("{}/api/tags", self.url))
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        let tags = response
+            .json::<OllamaTags>()
+            .await
+            .map_err(|e| GenerateError::BackendError(e.to_string()))?;
+
+        Ok(tags.models.into_iter().map(|model| model.name).collect())
+    }
+
+    /// Pulls a model by name, waiting for the download to finish.
+    pub async fn pull_model(&self, name: &str) -> Result<(), GenerateError> {
+        pull_ollama(&self.client, &self.url, name).await
+    }
+
+    /// Generates a response constrained to the given JSON schema,
+    /// and deserializes it into `T`.
+    ///
+    /// Requires Ollama 0.5.0 or later.
+    pub async fn generate_structured<T: DeserializeOwned>(
+        &self,
+        prompt: &str,
+        schema: serde_json::Value,
+    ) -> Result<T, GenerateError> {
+        let version = self.version().await?;
+
+        if !supports_structured_output(&version) {
+            return Err(GenerateError::Permanent(format!(
+                "Ollama {} does not support JSON schema output, please upgrade to {}.{}.{} or later",
+                version,
+                STRUCTURED_OUTPUT_VERSION.0,
+                STRUCTURED_OUTPUT_VERSION.1,
+                STRUCTURED_OUTPUT_VERSION.2,
+            )));
+        }
+
+        let client = &self.client;
+
+        let request = OllamaGenerate {
+            format: Some(schema),
+            stream: false,
+            ..self.request(prompt, &GenerateOptions::default())
+        };
+
+        let mut text = post_generate(client, &self.url, &request).await?;
+
+        if let Ok(error) = serde_json::from_str::<OllamaError>(&text) {
+            if !error.error.contains("try pulling it first") {
+                return Err(GenerateError::BackendError(error.error));
+            }
+
+            pull_ollama(client, &self.url, self.model).await?;
+            text = post_generate(client, &self.url, &request).await?;
+        }
+
+        let response = serde_json::from_str::<OllamaResponse>(&text)
+            .map_err(|e| GenerateError::BackendError(e.to_string()))?;
+
+        debug!("Ollama structured response: {}", response.response);
+
+        serde_json::from_str(&response.response)
+            .map_err(|e| GenerateError::BackendError(format!("Invalid structured output: {}", e)))
+    }
+}
+
 impl LlmBackend for OllamaBackend {
     async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
-        generate_ollama(&self.url, self.model, prompt).await
+        self.generate_with(prompt, &GenerateOptions::default())
+            .await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        Ok(self.generate_detailed(prompt, options).await?.text)
+    }
+
+    /// Reasoning is read from Ollama's `thinking` field, or from a leading
+    /// `<think>` block for models that include it in the response.
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        let mut output =
+            generate_ollama(&self.client, &self.url, &self.request(prompt, options)).await?;
+
+        if output.reasoning.is_none() {
+            let (reasoning, text) = split_think_tags(&output.text);
+            output.reasoning = reasoning;
+            output.text = text;
+        }
+
+        Ok(output)
+    }
+
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Stream<Item = Result<String, GenerateError>> {
+        let client = self.client.clone();
+        let url = self.url.clone();
+        let request = self.request(prompt, options);
+
+        stream::once(async move { stream_ollama(&client, &url, &request).await }).try_flatten()
+    }
+
+    /// Loads the model into memory, pulling it if needed.
+    async fn init(&self) -> Result<(), GenerateError> {
+        let client = &self.client;
+
+        // Generating with an empty prompt loads the model.
+        let request = OllamaGenerate {
+            stream: false,
+            ..self.request("", &GenerateOptions::default())
+        };
+
+        let text = post_generate(client, &self.url, &request).await?;
+
+        if let Ok(error) = serde_json::from_str::<OllamaError>(&text) {
+            if !error.error.contains("try pulling it first") {
+                return Err(GenerateError::BackendError(error.error));
+            }
+
+            pull_ollama(client, &self.url, self.model).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl OllamaBackend {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[Tool],
+    ) -> Result<OllamaChatResponseMessage, GenerateError> {
+        let request = OllamaChat {
+            model: self.model,
+            messages: messages
+                .iter()
+                .map(|message| OllamaMessage {
+                    role: message.role.as_str(),
+                    content: &message.content,
+                })
+                .collect(),
+            tools: tools.iter().map(OllamaTool::from).collect(),
+            options: self.request("", &GenerateOptions::default()).options,
+            keep_alive: self.keep_alive,
+            stream: false,
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/api/chat", self.url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        let text = response
+            .text()
+            .await
+            .map_err(|e| GenerateError::BackendError(e.to_string()))?;
+
+        if let Ok(error) = serde_json::from_str::<OllamaError>(&text) {
+            return Err(GenerateError::BackendError(error.error));
+        }
+
+        let response = serde_json::from_str::<OllamaChatResponse>(&text)
+            .map_err(|e| GenerateError::BackendError(format!("Invalid response: {}", e)))?;
+
+        debug!("Ollama chat response: {}", response.message.content);
+
+        Ok(response.message)
+    }
+}
+
+impl LlmChatBackend for OllamaBackend {
+    async fn generate_chat(&self, messages: &[ChatMessage]) -> Result<String, GenerateError> {
+        Ok(self.chat(messages, &[]).await?.content)
+    }
+
+    /// Only the first tool call of the response is returned.
+    async fn generate_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[Tool],
+    ) -> Result<ToolResponse, GenerateError> {
+        let message = self.chat(messages, tools).await?;
+
+        match message.tool_calls.into_iter().next() {
+            Some(call) => ToolResponse::tool_call(call.function.name, call.function.arguments),
+            None => Ok(ToolResponse::Text(message.content)),
+        }
+    }
+}
+
+impl LlmEmbeddingBackend for OllamaBackend {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, GenerateError> {
+        let response = self
+            .client
+            .post(format!("{}/api/embeddings", self.url))
+            .json(&OllamaEmbeddings {
+                model: self.model,
+                prompt: text,
+                keep_alive: self.keep_alive,
+            })
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        let text = response
+            .text()
+            .await
+            .map_err(|e| GenerateError::BackendError(e.to_string()))?;
+
+        if let Ok(error) = serde_json::from_str::<OllamaError>(&text) {
+            return Err(GenerateError::BackendError(error.error));
+        }
+
+        serde_json::from_str::<OllamaEmbedding>(&text)
+            .map(|response| response.embedding)
+            .map_err(|e| GenerateError::BackendError(format!("Invalid embedding: {}", e)))
     }
 }
 
-#[async_recursion::async_recursion]
 async fn generate_ollama(
+    client: &reqwest::Client,
     url: &str,
-    model: OllamaModel,
-    prompt: &str,
-) -> Result<String, GenerateError> {
-    let client = reqwest::Client::new();
+    request: &OllamaGenerate,
+) -> Result<GenerateOutput, GenerateError> {
+    let mut responses = Box::pin(start_ollama(client, url, request).await?);
+
+    let mut text = String::new();
+    let mut thinking = String::new();
+    let mut usage = None;
+
+    while let Some(response) = responses.next().await {
+        let response = response?;
+
+        text.push_str(&response.response);
+
+        if let Some(chunk) = response.thinking {
+            thinking.push_str(&chunk);
+        }
+
+        // Counts are sent with the final response.
+        if let (Some(prompt_tokens), Some(completion_tokens)) =
+            (response.prompt_eval_count, response.eval_count)
+        {
+            usage = Some(Usage {
+                prompt_tokens,
+                completion_tokens,
+                ..Default::default()
+            });
+        }
+    }
+
+    debug!("Ollama response: {}", text);
+
+    Ok(GenerateOutput {
+        text,
+        reasoning: (!thinking.is_empty()).then_some(thinking),
+        usage,
+    })
+}
+
+/// Separates a leading `<think>...</think>` block from the response,
+/// returning the reasoning and the remaining text.
+fn split_think_tags(text: &str) -> (Option<String>, String) {
+    let split = text
+        .trim_start()
+        .strip_prefix("<think>")
+        .and_then(|rest| rest.split_once("</think>"));
+
+    match split {
+        Some((reasoning, text)) => (Some(reasoning.trim().to_string()), text.trim().to_string()),
+        None => (None, text.to_string()),
+    }
+}
+
+/// Starts a streamed generation, pulling the model if needed.
+async fn stream_ollama(
+    client: &reqwest::Client,
+    url: &str,
+    request: &OllamaGenerate,
+) -> Result<impl Stream<Item = Result<String, GenerateError>>, GenerateError> {
+    Ok(response_chunks(start_ollama(client, url, request).await?))
+}
+
+/// Sends a generate request, pulling the model and retrying once if needed,
+/// and returns the stream of responses.
+async fn start_ollama(
+    client: &reqwest::Client,
+    url: &str,
+    request: &OllamaGenerate,
+) -> Result<impl Stream<Item = Result<OllamaResponse, GenerateError>>, GenerateError> {
+    let mut pulled = false;
+
+    loop {
+        let response = client
+            .post(format!("{}/api/generate", url))
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        if response.status().is_success() {
+            return Ok(parse_responses(Box::pin(response.bytes_stream())));
+        }
+
+        let text = response
+            .text()
+            .await
+            .map_err(|e| GenerateError::BackendError(e.to_string()))?;
+
+        let error = serde_json::from_str::<OllamaError>(&text)
+            .map(|error| error.error)
+            .unwrap_or(text);
+
+        if !pulled && error.contains("try pulling it first") {
+            pull_ollama(client, url, request.model).await?;
+            pulled = true;
+            continue;
+        }
+
+        return Err(GenerateError::BackendError(error));
+    }
+}
+
+/// Parses a stream of newline-delimited Ollama responses into response chunks.
+#[cfg(test)]
+fn parse_stream<B: AsRef<[u8]>, E: Display>(
+    bytes: impl Stream<Item = Result<B, E>> + Unpin,
+) -> impl Stream<Item = Result<String, GenerateError>> {
+    response_chunks(parse_responses(bytes))
+}
+
+/// Keeps the non-empty text of each response.
+fn response_chunks(
+    responses: impl Stream<Item = Result<OllamaResponse, GenerateError>>,
+) -> impl Stream<Item = Result<String, GenerateError>> {
+    responses.try_filter_map(|response| async move {
+        Ok((!response.response.is_empty()).then_some(response.response))
+    })
+}
+
+/// Parses a stream of newline-delimited Ollama responses.
+///
+/// Errors reported by Ollama mid-stream are yielded, as is an error if the stream
+/// ends before Ollama marks the response as done.
+fn parse_responses<B: AsRef<[u8]>, E: Display>(
+    bytes: impl Stream<Item = Result<B, E>> + Unpin,
+) -> impl Stream<Item = Result<OllamaResponse, GenerateError>> {
+    parse_until_done(
+        lines(bytes),
+        |line| {
+            if line.trim().is_empty() {
+                return Ok(Parsed::Skip);
+            }
+
+            if let Ok(error) = serde_json::from_str::<OllamaError>(&line) {
+                return Err(GenerateError::BackendError(error.error));
+            }
+
+            let response = serde_json::from_str::<OllamaResponse>(&line)
+                .map_err(|e| GenerateError::BackendError(e.to_string()))?;
 
-    // Generate response from Ollama.
-    let response = client
+            Ok(match response.done {
+                true => Parsed::Last(response),
+                false => Parsed::Item(response),
+            })
+        },
+        "Stream ended before the response was done",
+    )
+}
+
+async fn post_generate(
+    client: &reqwest::Client,
+    url: &str,
+    request: &OllamaGenerate,
+) -> Result<String, GenerateError> {
+    client
         .post(format!("{}/api/generate", url))
-        .json(&OllamaGenerate {
-            model,
-            prompt: prompt.to_string(),
-        })
+        .json(request)
         .send()
         .await
-        .map_err(|e| GenerateError::BackendError(e.to_string()))?;
+        .map_err(|e| GenerateError::Transient(e.to_string()))?
+        .text()
+        .await
+        .map_err(|e| GenerateError::BackendError(e.to_string()))
+}
 
-    let mut stream = response.bytes_stream();
+/// Pulls the model, reading the progress stream until the download finishes.
+async fn pull_ollama<N: Serialize + Debug>(
+    client: &reqwest::Client,
+    url: &str,
+    name: N,
+) -> Result<(), GenerateError> {
+    let res = client
+        .post(format!("{}/api/pull", url))
+        .json(&OllamaPull { name: &name })
+        .send()
+        .await
+        .map_err(|e| GenerateError::Transient(e.to_string()))?;
 
-    let mut text = String::new();
+    let mut lines = pin!(lines(res.bytes_stream()));
+    let mut last_status = String::new();
 
-    while let Some(res) = stream.next().await {
-        let chunk = res.map_err(|e| GenerateError::BackendError(e.to_string()))?;
-        let text_chunk = String::from_utf8_lossy(&chunk);
-
-        if let Ok(error) = serde_json::from_str::<OllamaError>(&text_chunk) {
-            // If model needs to be pulled, pull it and try again.
-            // Example error: "model 'mistral' not found, try pulling it first"
-            if error.error.contains("try pulling it first") {
-                let res = client
-                    .post(format!("{}/api/pull", url))
-                    .json(&OllamaPull { name: model })
-                    .send()
-                    .await
-                    .map_err(|e| GenerateError::BackendError(e.to_string()))?;
-
-                let mut stream = res.bytes_stream();
-                let mut last_status = String::new();
-
-                while let Some(res) = stream.next().await {
-                    if let Ok(bytes) = res {
-                        let text = String::from_utf8_lossy(&bytes);
-
-                        if let Ok(status) = serde_json::from_str::<OllamaStatus>(&text) {
-                            if status.status == "success" {
-                                return generate_ollama(url, model, prompt).await;
-                            }
-
-                            if status.status == last_status {
-                                continue;
-                            }
-                            info!("Ollama status: {}", status.status);
-                            last_status = status.status.clone();
-                        }
-                    }
-                }
-            } else {
-                return Err(GenerateError::BackendError(error.error));
-            }
+    while let Some(line) = lines.next().await {
+        let line = match line {
+            Ok(line) => line,
+            // Reading the progress failed, such as from a dropped connection.
+            Err(GenerateError::BackendError(e)) => return Err(GenerateError::Transient(e)),
+            Err(e) => return Err(e),
+        };
+
+        if let Ok(error) = serde_json::from_str::<OllamaError>(&line) {
+            return Err(GenerateError::BackendError(error.error));
         }
 
-        if let Ok(response) = serde_json::from_str::<OllamaResponse>(&text_chunk) {
-            text.push_str(&response.response);
+        if let Ok(status) = serde_json::from_str::<OllamaStatus>(&line) {
+            if status.status == "success" {
+                return Ok(());
+            }
+
+            if status.status != last_status {
+                info!("Ollama status: {}", status.status);
+                last_status = status.status;
+            }
         }
     }
 
-    debug!("Ollama response: {}", text);
+    Err(GenerateError::BackendError(format!(
+        "Failed to pull model {:?}",
+        name
+    )))
+}
+
+/// Parses the numeric components of a version string, such as `0.5.1` or `0.5.0-rc1`.
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| {
+            let digits = part
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect::<String>();
+            digits.parse::<u32>().ok()
+        });
+
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or_default();
+    let patch = parts.next().flatten().unwrap_or_default();
+
+    Some((major, minor, patch))
+}
+
+fn supports_structured_output(version: &str) -> bool {
+    parse_version(version).is_some_and(|v| v >= STRUCTURED_OUTPUT_VERSION)
+}
 
-    Ok(text)
+/// Serializes a keep alive duration as Ollama expects, such as `"300s"`,
+/// or `-1` to keep the model loaded indefinitely.
+fn serialize_keep_alive<S: Serializer>(
+    keep_alive: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match keep_alive {
+        Some(Duration::MAX) => serializer.serialize_i64(-1),
+        Some(duration) => serializer.serialize_str(&format!("{}s", duration.as_secs())),
+        None => serializer.serialize_none(),
+    }
 }
 
 #[derive(Debug, Serialize)]
-struct OllamaPull {
-    name: OllamaModel,
+struct OllamaPull<'a, N> {
+    name: &'a N,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaTags {
+    models: Vec<OllamaTag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaTag {
+    name: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -127,20 +636,156 @@ struct OllamaError {
     error: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct OllamaVersion {
+    version: String,
+}
+
 #[derive(Debug, Serialize)]
 struct OllamaGenerate {
     model: OllamaModel,
     prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<OllamaOptions>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_keep_alive"
+    )]
+    keep_alive: Option<Duration>,
+    stream: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct OllamaOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaChat<'a> {
+    model: OllamaModel,
+    messages: Vec<OllamaMessage<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<OllamaTool<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<OllamaOptions>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_keep_alive"
+    )]
+    keep_alive: Option<Duration>,
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaTool<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    function: OllamaFunction<'a>,
+}
+
+impl<'a> From<&'a Tool> for OllamaTool<'a> {
+    fn from(tool: &'a Tool) -> Self {
+        Self {
+            kind: "function",
+            function: OllamaFunction {
+                name: &tool.name,
+                description: &tool.description,
+                parameters: &tool.parameters_schema,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaFunction<'a> {
+    name: &'a str,
+    description: &'a str,
+    parameters: &'a serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    message: OllamaChatResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponseMessage {
+    content: String,
+    #[serde(default)]
+    tool_calls: Vec<OllamaToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaToolCall {
+    function: OllamaToolCallFunction,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaToolCallFunction {
+    name: String,
+    arguments: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaEmbeddings<'a> {
+    model: OllamaModel,
+    prompt: &'a str,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_keep_alive"
+    )]
+    keep_alive: Option<Duration>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbedding {
+    embedding: Vec<f32>,
 }
 
 #[derive(Debug, Deserialize)]
 struct OllamaResponse {
     response: String,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    thinking: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: Option<u32>,
+    #[serde(default)]
+    eval_count: Option<u32>,
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use lemon_graph::{Executor, Graph, GraphNode, Value};
     use tracing_test::traced_test;
+    use wiremock::{
+        matchers::{body_json, body_partial_json, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::{LlmNode, LlmOutput, LlmWeight};
 
     use super::*;
 
@@ -156,4 +801,454 @@ mod tests {
 
         assert!(response.contains('b'));
     }
+
+    #[tokio::test]
+    async fn test_weight_options() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .and(body_partial_json(serde_json::json!({
+                "prompt": TEST_PROMPT,
+                "options": { "temperature": 0.0 }
+            })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string("{\"response\":\"Hi\",\"done\":true}\n"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = OllamaBackend {
+            url: server.uri(),
+            ..Default::default()
+        };
+
+        let weight = LlmWeight::new(Arc::new(backend)).with_options(GenerateOptions {
+            temperature: Some(0.0),
+            ..Default::default()
+        });
+
+        let mut graph = Graph::default();
+        let (llm, ports) = LlmNode::new_with_ports(&mut graph, weight);
+        ports
+            .input
+            .set_value(&mut graph, TEST_PROMPT.to_string().into());
+
+        Executor::execute(&mut graph, llm.0).await.unwrap();
+
+        match &graph[ports.response.unwrap().0] {
+            GraphNode::Store(value) => assert_eq!(value, &Value::String("Hi".to_string())),
+            _ => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_usage() {
+        let server = MockServer::start().await;
+
+        let body = [
+            r#"{"response":"","thinking":"Greet.","done":false}"#,
+            r#"{"response":"Hello","done":false}"#,
+            r#"{"response":" world","done":true,"prompt_eval_count":12,"eval_count":3}"#,
+        ]
+        .map(|line| format!("{}\n", line))
+        .concat();
+
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .mount(&server)
+            .await;
+
+        let backend = OllamaBackend {
+            url: server.uri(),
+            ..Default::default()
+        };
+
+        let output = backend
+            .generate_detailed(TEST_PROMPT, &GenerateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(output.text, "Hello world");
+        assert_eq!(output.reasoning.as_deref(), Some("Greet."));
+
+        let mut graph = Graph::default();
+
+        let weight = LlmWeight::new(Arc::new(backend))
+            .with_outputs(vec![LlmOutput::Response, LlmOutput::Usage]);
+        let (llm, ports) = LlmNode::new_with_ports(&mut graph, weight);
+        ports
+            .input
+            .set_value(&mut graph, TEST_PROMPT.to_string().into());
+
+        Executor::execute(&mut graph, llm.0).await.unwrap();
+
+        match &graph[ports.usage.unwrap().0] {
+            GraphNode::Store(value) => {
+                assert_eq!(value, &Value::Vec(vec![Value::USize(12), Value::USize(3)]))
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_pulls_once() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+                "error": "model 'mistral' not found, try pulling it first"
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/pull"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"status\":\"success\"}\n"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = OllamaBackend {
+            url: server.uri(),
+            ..Default::default()
+        };
+
+        assert!(backend.generate(TEST_PROMPT).await.is_err());
+    }
+
+    #[test]
+    fn test_options_request() {
+        let backend = OllamaBackend::default();
+
+        let options = GenerateOptions {
+            temperature: Some(0.5),
+            max_tokens: Some(64),
+            stop: vec!["\n".to_string()],
+            system: Some("Be brief.".to_string()),
+            ..Default::default()
+        };
+
+        let request = serde_json::to_value(backend.request(TEST_PROMPT, &options)).unwrap();
+        assert_eq!(request["system"], "Be brief.");
+        assert_eq!(
+            request["options"],
+            serde_json::json!({ "temperature": 0.5, "num_predict": 64, "stop": ["\n"] })
+        );
+    }
+
+    #[test]
+    fn test_json_request() {
+        let options = GenerateOptions {
+            json: true,
+            ..Default::default()
+        };
+
+        let request = OllamaBackend::default().request("hi", &options);
+        assert_eq!(request.format, Some(serde_json::json!("json")));
+    }
+
+    #[test]
+    fn test_deterministic_request() {
+        let backend = OllamaBackend::default();
+        let request =
+            serde_json::to_value(backend.request(TEST_PROMPT, &Default::default())).unwrap();
+        assert!(request.get("options").is_none());
+
+        let backend = OllamaBackend::default().with_deterministic();
+        let request =
+            serde_json::to_value(backend.request(TEST_PROMPT, &Default::default())).unwrap();
+        assert_eq!(
+            request["options"],
+            serde_json::json!({ "seed": 0, "temperature": 0.0 })
+        );
+    }
+
+    #[test]
+    fn test_keep_alive_request() {
+        let request = |backend: OllamaBackend| {
+            serde_json::to_value(backend.request(TEST_PROMPT, &Default::default())).unwrap()
+        };
+
+        assert_eq!(request(OllamaBackend::default())["keep_alive"], "300s");
+
+        let backend = OllamaBackend::default().with_keep_alive(Some(Duration::from_secs(30 * 60)));
+        assert_eq!(request(backend)["keep_alive"], "1800s");
+
+        let backend = OllamaBackend::default().with_keep_alive(Some(Duration::MAX));
+        assert_eq!(request(backend)["keep_alive"], -1);
+
+        let backend = OllamaBackend::default().with_keep_alive(None);
+        assert!(request(backend).get("keep_alive").is_none());
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("0.5.1"), Some((0, 5, 1)));
+        assert_eq!(parse_version("0.5.0-rc1"), Some((0, 5, 0)));
+        assert_eq!(parse_version("1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_version("unknown"), None);
+    }
+
+    #[test]
+    fn test_supports_structured_output() {
+        assert!(supports_structured_output("0.5.0"));
+        assert!(supports_structured_output("0.12.3"));
+        assert!(!supports_structured_output("0.4.7"));
+        assert!(!supports_structured_output(""));
+    }
+
+    #[test]
+    fn test_split_think_tags() {
+        let (reasoning, text) = split_think_tags("<think>\nA, then B.\n</think>\n\nB");
+        assert_eq!(text, "B");
+        assert_eq!(reasoning.as_deref(), Some("A, then B."));
+
+        let (reasoning, text) = split_think_tags("B <think>");
+        assert_eq!(text, "B <think>");
+        assert!(reasoning.is_none());
+    }
+
+    fn bytes(lines: &[&str]) -> impl Stream<Item = Result<Vec<u8>, String>> + Unpin {
+        stream::iter(
+            lines
+                .iter()
+                .map(|line| Ok(line.as_bytes().to_vec()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_parse_stream() {
+        // Lines may be split across, or share, chunks.
+        let chunks = parse_stream(bytes(&[
+            "{\"response\":\"Hel",
+            "lo\",\"done\":false}\n{\"response\":\",\",\"done\":false}\n",
+            "{\"response\":\" world\",\"done\":false}\n",
+            "{\"response\":\"\",\"done\":true}",
+        ]))
+        .collect::<Vec<_>>()
+        .await;
+
+        let chunks = chunks.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(chunks, vec!["Hello", ",", " world"]);
+    }
+
+    #[tokio::test]
+    async fn test_parse_stream_error() {
+        let chunks = parse_stream(bytes(&[
+            "{\"response\":\"Hello\",\"done\":false}\n",
+            "{\"error\":\"out of memory\"}\n",
+            "{\"response\":\" world\",\"done\":false}\n",
+        ]))
+        .collect::<Vec<_>>()
+        .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_deref().ok(), Some("Hello"));
+        assert!(matches!(&chunks[1], Err(GenerateError::BackendError(e)) if e == "out of memory"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_stream_truncated() {
+        let chunks = parse_stream(bytes(&["{\"response\":\"Hello\",\"done\":false}\n"]))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[1].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_embed() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/embeddings"))
+            .and(body_json(
+                serde_json::json!({ "model": "mistral", "prompt": "Hello", "keep_alive": "300s" }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "embedding": [0.25, -0.5] })),
+            )
+            .mount(&server)
+            .await;
+
+        let backend = OllamaBackend {
+            url: server.uri(),
+            ..Default::default()
+        };
+
+        assert_eq!(backend.embed("Hello").await.unwrap(), vec![0.25, -0.5]);
+    }
+
+    #[tokio::test]
+    async fn test_chat() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_json(serde_json::json!({
+                "model": "mistral",
+                "messages": [
+                    { "role": "system", "content": "Be brief." },
+                    { "role": "user", "content": "Hello" }
+                ],
+                "keep_alive": "300s",
+                "stream": false
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "model": "mistral",
+                "message": { "role": "assistant", "content": "Hi!" },
+                "done": true
+            })))
+            .mount(&server)
+            .await;
+
+        let backend = OllamaBackend {
+            url: server.uri(),
+            ..Default::default()
+        };
+
+        let messages = [ChatMessage::system("Be brief."), ChatMessage::user("Hello")];
+        assert_eq!(backend.generate_chat(&messages).await.unwrap(), "Hi!");
+    }
+
+    #[tokio::test]
+    async fn test_chat_tools() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_json(serde_json::json!({
+                "model": "mistral",
+                "messages": [{ "role": "user", "content": "Weather in Paris?" }],
+                "tools": [{
+                    "type": "function",
+                    "function": {
+                        "name": "get_weather",
+                        "description": "Gets the weather.",
+                        "parameters": { "type": "object" }
+                    }
+                }],
+                "keep_alive": "300s",
+                "stream": false
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "model": "mistral",
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{
+                        "function": {
+                            "name": "get_weather",
+                            "arguments": { "city": "Paris" }
+                        }
+                    }]
+                },
+                "done": true
+            })))
+            .mount(&server)
+            .await;
+
+        let backend = OllamaBackend {
+            url: server.uri(),
+            ..Default::default()
+        };
+
+        let tools = [Tool::new(
+            "get_weather",
+            "Gets the weather.",
+            serde_json::json!({ "type": "object" }),
+        )];
+
+        let response = backend
+            .generate_with_tools(&[ChatMessage::user("Weather in Paris?")], &tools)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response,
+            ToolResponse::ToolCall {
+                name: "get_weather".to_string(),
+                arguments: lemon_graph::Value::Map(
+                    [(
+                        "city".to_string(),
+                        lemon_graph::Value::String("Paris".to_string())
+                    )]
+                    .into()
+                ),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "models": [
+                    { "name": "mistral:latest", "size": 4109865159u64 },
+                    { "name": "llama2:7b", "size": 3826793677u64 }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let backend = OllamaBackend {
+            url: server.uri(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            backend.list_models().await.unwrap(),
+            vec!["mistral:latest", "llama2:7b"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pull_model() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/pull"))
+            .and(body_json(serde_json::json!({ "name": "llama2:7b" })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(concat!(
+                "{\"status\":\"pulling manifest\"}\n",
+                "{\"status\":\"downloading\",\"completed\":1,\"total\":2}\n",
+                "{\"status\":\"downloading\",\"completed\":2,\"total\":2}\n",
+                "{\"status\":\"success\"}\n",
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/pull"))
+            .and(body_json(serde_json::json!({ "name": "missing" })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("{\"error\":\"pull model manifest: file does not exist\"}\n"),
+            )
+            .mount(&server)
+            .await;
+
+        let backend = OllamaBackend {
+            url: server.uri(),
+            ..Default::default()
+        };
+
+        backend.pull_model("llama2:7b").await.unwrap();
+
+        assert!(matches!(
+            backend.pull_model("missing").await,
+            Err(GenerateError::BackendError(e)) if e.contains("does not exist")
+        ));
+    }
 }
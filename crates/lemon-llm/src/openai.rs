@@ -0,0 +1,874 @@
+use std::fmt::Display;
+
+use futures_util::{stream, Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+use crate::{
+    lines::{events, lines, parse_until_done, Parsed},
+    ChatMessage, GenerateError, GenerateOptions, GenerateOutput, LlmBackend, LlmChatBackend,
+    Pricing, Tool, ToolResponse, Usage,
+};
+
+const DEFAULT_OPENAI_URL: &str = "https://api.openai.com";
+
+/// Backend for OpenAI, or any server with an OpenAI-compatible
+/// chat completions API, such as vLLM, LM Studio, or LocalAI.
+pub struct OpenAiBackend {
+    /// URL of the server, without the `/v1` path.
+    pub base_url: String,
+    /// Sent as a bearer token, if set.
+    pub api_key: Option<String>,
+    /// If unset, the first model listed by the server's `/v1/models` is used.
+    pub model: Option<String>,
+    /// Pricing of the model, used by [`LlmBackend::cost_estimate`].
+    pub pricing: Option<Pricing>,
+    discovered: OnceCell<String>,
+}
+
+impl OpenAiBackend {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            base_url: DEFAULT_OPENAI_URL.to_string(),
+            api_key: None,
+            model: Some(model.into()),
+            pricing: None,
+            discovered: OnceCell::new(),
+        }
+    }
+
+    /// Creates a backend for an OpenAI-compatible server, which may not need an API key.
+    /// The server's default model is used, unless one is set with [`OpenAiBackend::with_model`].
+    pub fn compatible(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            api_key: None,
+            model: None,
+            pricing: None,
+            discovered: OnceCell::new(),
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
+    /// The model to request, discovering the server's default model if none is set.
+    async fn model(&self) -> Result<&str, GenerateError> {
+        if let Some(model) = &self.model {
+            return Ok(model);
+        }
+
+        self.discovered
+            .get_or_try_init(|| self.discover_model())
+            .await
+            .map(String::as_str)
+    }
+
+    /// Lists the server's models, returning the first.
+    async fn discover_model(&self) -> Result<String, GenerateError> {
+        let request = reqwest::Client::new().get(self.url("models"));
+
+        let response = self
+            .authorize(request)
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        let status = response.status();
+
+        let text = response
+            .text()
+            .await
+            .map_err(|e| GenerateError::BackendError(e.to_string()))?;
+
+        if !status.is_success() {
+            return Err(GenerateError::from_status(
+                status.as_u16(),
+                format!("{}: {}", status, text),
+            ));
+        }
+
+        let models = serde_json::from_str::<ModelList>(&text)
+            .map_err(|e| GenerateError::BackendError(format!("Invalid model list: {}", e)))?;
+
+        let model = models
+            .data
+            .into_iter()
+            .next()
+            .ok_or(GenerateError::BackendError("No models".to_string()))?
+            .id;
+
+        debug!("Discovered OpenAI model: {}", model);
+
+        Ok(model)
+    }
+
+    fn request<'a>(
+        &'a self,
+        model: &'a str,
+        prompt: &'a str,
+        options: &'a GenerateOptions,
+    ) -> ChatRequest<'a> {
+        let mut messages = Vec::new();
+
+        if let Some(system) = &options.system {
+            messages.push(RequestMessage {
+                role: "system",
+                content: system,
+            });
+        }
+
+        messages.push(RequestMessage {
+            role: "user",
+            content: prompt,
+        });
+
+        Self::chat_request(model, messages, options)
+    }
+
+    fn chat_request<'a>(
+        model: &'a str,
+        messages: Vec<RequestMessage<'a>>,
+        options: &'a GenerateOptions,
+    ) -> ChatRequest<'a> {
+        ChatRequest {
+            model,
+            messages,
+            temperature: options.temperature,
+            top_p: options.top_p,
+            max_tokens: options.max_tokens,
+            stop: &options.stop,
+            response_format: options.json.then_some(ResponseFormat {
+                kind: "json_object",
+            }),
+            tools: Vec::new(),
+            stream: false,
+        }
+    }
+
+    fn chat_messages(messages: &[ChatMessage]) -> Vec<RequestMessage<'_>> {
+        messages
+            .iter()
+            .map(|message| RequestMessage {
+                role: message.role.as_str(),
+                content: &message.content,
+            })
+            .collect()
+    }
+
+    async fn send(&self, request: &ChatRequest<'_>) -> Result<GenerateOutput, GenerateError> {
+        let (message, usage) = self.complete(request).await?;
+
+        debug!("OpenAI response: {:?}", message.content);
+
+        Ok(GenerateOutput {
+            text: message.content.unwrap_or_default(),
+            reasoning: message.reasoning_content,
+            usage,
+        })
+    }
+
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}/v1/{}", self.base_url.trim_end_matches('/'), endpoint)
+    }
+
+    fn post(&self, request: &ChatRequest<'_>) -> reqwest::RequestBuilder {
+        self.authorize(
+            reqwest::Client::new()
+                .post(self.url("chat/completions"))
+                .json(request),
+        )
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+
+    /// Sends a chat completion request, returning the first choice.
+    async fn complete(
+        &self,
+        request: &ChatRequest<'_>,
+    ) -> Result<(ChatResponseMessage, Option<Usage>), GenerateError> {
+        let response = self
+            .post(request)
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        let status = response.status();
+
+        let text = response
+            .text()
+            .await
+            .map_err(|e| GenerateError::BackendError(e.to_string()))?;
+
+        if !status.is_success() {
+            return Err(GenerateError::from_status(
+                status.as_u16(),
+                format!("{}: {}", status, text),
+            ));
+        }
+
+        let response = serde_json::from_str::<ChatResponse>(&text)
+            .map_err(|e| GenerateError::BackendError(format!("Invalid response: {}", e)))?;
+
+        let usage = response.usage.map(|usage| Usage {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            ..Default::default()
+        });
+
+        let message = response
+            .choices
+            .into_iter()
+            .next()
+            .ok_or(GenerateError::BackendError("No choices".to_string()))?
+            .message;
+
+        Ok((message, usage))
+    }
+
+    /// Sends a streaming chat completion request, returning a stream of its content.
+    async fn stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<impl Stream<Item = Result<String, GenerateError>>, GenerateError> {
+        let model = self.model().await?;
+        let request = ChatRequest {
+            stream: true,
+            ..self.request(model, prompt, options)
+        };
+
+        let response = self
+            .post(&request)
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(GenerateError::from_status(
+                status.as_u16(),
+                format!("{}: {}", status, text),
+            ));
+        }
+
+        Ok(parse_stream(Box::pin(response.bytes_stream())))
+    }
+}
+
+impl LlmBackend for OpenAiBackend {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.generate_with(prompt, &GenerateOptions::default())
+            .await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        Ok(self.generate_detailed(prompt, options).await?.text)
+    }
+
+    /// Reasoning is read from the `reasoning_content` field of the message,
+    /// which some compatible servers provide.
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        let model = self.model().await?;
+        self.send(&self.request(model, prompt, options)).await
+    }
+
+    /// Streams the content of the response. Streamed responses do not include reasoning.
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Stream<Item = Result<String, GenerateError>> {
+        stream::once(self.stream(prompt, options)).try_flatten()
+    }
+
+    fn cost_estimate(&self, usage: &Usage) -> Option<f64> {
+        self.pricing.map(|pricing| pricing.cost(usage))
+    }
+}
+
+impl LlmChatBackend for OpenAiBackend {
+    async fn generate_chat(&self, messages: &[ChatMessage]) -> Result<String, GenerateError> {
+        let options = GenerateOptions::default();
+        let model = self.model().await?;
+        let request = Self::chat_request(model, Self::chat_messages(messages), &options);
+
+        Ok(self.send(&request).await?.text)
+    }
+
+    /// Only the first tool call of the response is returned.
+    async fn generate_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[Tool],
+    ) -> Result<ToolResponse, GenerateError> {
+        let options = GenerateOptions::default();
+        let model = self.model().await?;
+        let mut request = Self::chat_request(model, Self::chat_messages(messages), &options);
+        request.tools = tools.iter().map(RequestTool::from).collect();
+
+        let (message, _) = self.complete(&request).await?;
+
+        match message.tool_calls.into_iter().next() {
+            Some(call) => {
+                let arguments = serde_json::from_str(&call.function.arguments).map_err(|e| {
+                    GenerateError::BackendError(format!("Invalid tool call arguments: {}", e))
+                })?;
+
+                ToolResponse::tool_call(call.function.name, arguments)
+            }
+            None => Ok(ToolResponse::Text(message.content.unwrap_or_default())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<RequestMessage<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<RequestTool<'a>>,
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct RequestTool<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    function: RequestFunction<'a>,
+}
+
+impl<'a> From<&'a Tool> for RequestTool<'a> {
+    fn from(tool: &'a Tool) -> Self {
+        Self {
+            kind: "function",
+            function: RequestFunction {
+                name: &tool.name,
+                description: &tool.description,
+                parameters: &tool.parameters_schema,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RequestFunction<'a> {
+    name: &'a str,
+    description: &'a str,
+    parameters: &'a serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct RequestMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+/// Response of `/v1/models`.
+#[derive(Debug, Deserialize)]
+struct ModelList {
+    #[serde(default)]
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+// Compatible servers may leave out fields, so most are optional.
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    #[serde(default)]
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    #[serde(default)]
+    message: ChatResponseMessage,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChatResponseMessage {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    reasoning_content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ResponseToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseToolCall {
+    function: ResponseFunction,
+}
+
+/// Arguments are a JSON encoded string.
+#[derive(Debug, Deserialize)]
+struct ResponseFunction {
+    name: String,
+    arguments: String,
+}
+
+/// A chunk of a streamed chat completion.
+#[derive(Debug, Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    /// Sent by some compatible servers if generation fails mid-stream.
+    #[serde(default)]
+    error: Option<StreamError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChoice {
+    delta: StreamDelta,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamError {
+    message: String,
+}
+
+/// Parses the server-sent events of a streamed chat completion into content chunks.
+///
+/// The stream ends at the `[DONE]` event.
+/// Errors are yielded if an event is invalid, or if the stream ends before it.
+fn parse_stream<B: AsRef<[u8]>, E: Display>(
+    bytes: impl Stream<Item = Result<B, E>> + Unpin,
+) -> impl Stream<Item = Result<String, GenerateError>> {
+    parse_until_done(
+        events(lines(bytes)),
+        |event| {
+            // Each chunk is a single `data` event.
+            if event.data.is_empty() {
+                return Ok(Parsed::Skip);
+            }
+
+            if event.data == "[DONE]" {
+                return Ok(Parsed::Done);
+            }
+
+            let chunk = serde_json::from_str::<StreamChunk>(&event.data)
+                .map_err(|e| GenerateError::BackendError(format!("Invalid response: {}", e)))?;
+
+            if let Some(error) = chunk.error {
+                return Err(GenerateError::BackendError(error.message));
+            }
+
+            let content = chunk
+                .choices
+                .into_iter()
+                .next()
+                .and_then(|choice| choice.delta.content)
+                .unwrap_or_default();
+
+            // The first chunk usually only holds the role.
+            Ok(match content.is_empty() {
+                true => Parsed::Skip,
+                false => Parsed::Item(content),
+            })
+        },
+        "Stream ended before the response was done",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use futures_util::StreamExt;
+    use serde_json::json;
+    use wiremock::{
+        matchers::{body_partial_json, header, header_exists, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::RetryBackend;
+
+    use super::*;
+
+    fn chat_response(content: &str) -> serde_json::Value {
+        json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "created": 1677652288,
+            "model": "gpt-4o-mini",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": content },
+                "finish_reason": "stop"
+            }],
+            "usage": { "prompt_tokens": 9, "completion_tokens": 12, "total_tokens": 21 }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_openai_backend() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("authorization", "Bearer test-key"))
+            .and(body_partial_json(json!({
+                "model": "gpt-4o-mini",
+                "messages": [
+                    { "role": "system", "content": "Be brief." },
+                    { "role": "user", "content": "Hello?" }
+                ],
+                "temperature": 0.0
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chat_response("Hi!")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = OpenAiBackend::new("gpt-4o-mini")
+            .with_base_url(server.uri())
+            .with_api_key("test-key");
+
+        let options = GenerateOptions {
+            temperature: Some(0.0),
+            system: Some("Be brief.".to_string()),
+            ..Default::default()
+        };
+
+        let output = backend.generate_detailed("Hello?", &options).await.unwrap();
+        assert_eq!(output.text, "Hi!");
+        assert_eq!(
+            output.usage,
+            Some(Usage {
+                prompt_tokens: 9,
+                completion_tokens: 12,
+                ..Default::default()
+            })
+        );
+    }
+
+    /// Formats chunks of content as a streamed chat completion.
+    fn stream_body(chunks: &[&str], done: bool) -> String {
+        let mut body = "data: {\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\"}}]}\n\n"
+            .to_string();
+
+        for chunk in chunks {
+            let data = json!({ "choices": [{ "index": 0, "delta": { "content": chunk } }] });
+            body.push_str(&format!("data: {}\n\n", data));
+        }
+
+        if done {
+            body.push_str("data: [DONE]\n\n");
+        }
+
+        body
+    }
+
+    #[tokio::test]
+    async fn test_openai_stream() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({ "stream": true })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(stream_body(&["Hel", "lo", "!"], true)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = OpenAiBackend::new("gpt-4o-mini").with_base_url(server.uri());
+
+        let chunks = backend
+            .generate_stream("Hello?", &GenerateOptions::default())
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(chunks, vec!["Hel", "lo", "!"]);
+    }
+
+    #[tokio::test]
+    async fn test_openai_stream_truncated() {
+        let chunks = parse_stream(Box::pin(stream::iter([Ok::<_, String>(stream_body(
+            &["Hel"],
+            false,
+        ))])))
+        .collect::<Vec<_>>()
+        .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_deref().ok(), Some("Hel"));
+        assert!(matches!(chunks[1], Err(GenerateError::BackendError(_))));
+
+        // Errors sent by the server are yielded.
+        let body = "data: {\"error\":{\"message\":\"overloaded\"}}\n\n";
+        let chunks = parse_stream(Box::pin(stream::iter([Ok::<_, String>(body)])))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert!(matches!(&chunks[..], [Err(GenerateError::BackendError(e))] if e == "overloaded"));
+    }
+
+    #[tokio::test]
+    async fn test_openai_chat() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({
+                "messages": [
+                    { "role": "user", "content": "My name is Bob." },
+                    { "role": "assistant", "content": "Hi Bob!" },
+                    { "role": "user", "content": "What is my name?" }
+                ]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chat_response("Bob.")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = OpenAiBackend::new("gpt-4o-mini").with_base_url(server.uri());
+
+        let messages = [
+            ChatMessage::user("My name is Bob."),
+            ChatMessage::assistant("Hi Bob!"),
+            ChatMessage::user("What is my name?"),
+        ];
+
+        assert_eq!(backend.generate_chat(&messages).await.unwrap(), "Bob.");
+    }
+
+    #[tokio::test]
+    async fn test_openai_tools() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({
+                "tools": [{
+                    "type": "function",
+                    "function": {
+                        "name": "get_weather",
+                        "description": "Gets the weather.",
+                        "parameters": { "type": "object" }
+                    }
+                }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{
+                    "index": 0,
+                    "message": {
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": [{
+                            "id": "call_abc",
+                            "type": "function",
+                            "function": {
+                                "name": "get_weather",
+                                "arguments": "{\"city\": \"Paris\"}"
+                            }
+                        }]
+                    },
+                    "finish_reason": "tool_calls"
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = OpenAiBackend::new("gpt-4o-mini").with_base_url(server.uri());
+        let tools = [Tool::new(
+            "get_weather",
+            "Gets the weather.",
+            json!({ "type": "object" }),
+        )];
+
+        let response = backend
+            .generate_with_tools(&[ChatMessage::user("Weather in Paris?")], &tools)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response,
+            ToolResponse::ToolCall {
+                name: "get_weather".to_string(),
+                arguments: lemon_graph::Value::Map(
+                    [(
+                        "city".to_string(),
+                        lemon_graph::Value::String("Paris".to_string())
+                    )]
+                    .into()
+                ),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_openai_backend_no_auth() {
+        let server = MockServer::start().await;
+
+        Mock::given(header_exists("authorization"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        Mock::given(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chat_response("Hi!")))
+            .mount(&server)
+            .await;
+
+        let backend = OpenAiBackend::new("local").with_base_url(server.uri());
+        assert_eq!(backend.generate("Hello?").await.unwrap(), "Hi!");
+    }
+
+    #[tokio::test]
+    async fn test_openai_compatible() {
+        let server = MockServer::start().await;
+
+        Mock::given(header_exists("authorization"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": [{ "id": "local-model" }, { "id": "other-model" }]
+            })))
+            // The discovered model is reused.
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // The response leaves out optional fields.
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({ "model": "local-model" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "content": "Hi!" } }]
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let backend = OpenAiBackend::compatible(server.uri());
+
+        let output = backend
+            .generate_detailed("Hello?", &GenerateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(output.text, "Hi!");
+        assert_eq!(output.usage, None);
+
+        assert_eq!(backend.generate("Hello?").await.unwrap(), "Hi!");
+    }
+
+    #[tokio::test]
+    async fn test_openai_compatible_no_models() {
+        let server = MockServer::start().await;
+
+        Mock::given(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+
+        let backend = OpenAiBackend::compatible(server.uri());
+
+        let res = backend.generate("Hello?").await;
+        assert!(matches!(res, Err(GenerateError::BackendError(e)) if e == "No models"));
+    }
+
+    #[tokio::test]
+    async fn test_openai_backend_error() {
+        let server = MockServer::start().await;
+
+        Mock::given(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("model not found"))
+            // A bad request is not retried.
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = OpenAiBackend::new("missing").with_base_url(server.uri());
+        let backend = RetryBackend::new(
+            Arc::new(backend),
+            Duration::from_millis(1),
+            Duration::from_millis(4),
+        );
+
+        let res = backend.generate("Hello?").await;
+        assert!(
+            matches!(res, Err(GenerateError::Permanent(e)) if e.contains("404") && e.contains("model not found"))
+        );
+    }
+}
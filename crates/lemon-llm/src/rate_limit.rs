@@ -0,0 +1,188 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_util::{stream, Stream, StreamExt};
+use tokio::time::Instant;
+
+use crate::{GenerateError, GenerateOptions, GenerateOutput, LlmBackend, Usage};
+
+/// Token bucket limiting how often requests may start.
+///
+/// Requests over the limit are delayed until a token is available, rather than rejected.
+/// A limiter can be shared between backends with an [`Arc`],
+/// so they stay under the same quota.
+pub struct RateLimiter {
+    /// Time for one token to refill.
+    interval: Duration,
+    /// Number of requests that may start at once.
+    burst: u32,
+    /// When the bucket will be full again, if no more requests start.
+    full_at: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Allows up to `requests_per_second` requests per second, one at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` is not positive and finite.
+    pub fn new(requests_per_second: f64) -> Self {
+        assert!(
+            requests_per_second > 0.0 && requests_per_second.is_finite(),
+            "requests per second must be positive"
+        );
+
+        Self {
+            interval: Duration::from_secs_f64(1.0 / requests_per_second),
+            burst: 1,
+            full_at: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Allows up to `burst` requests to start at once, while keeping the same average rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `burst` is 0.
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        assert!(burst > 0, "burst must be greater than 0");
+        self.burst = burst;
+        self
+    }
+
+    /// Waits until a request may start.
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut full_at = self.full_at.lock().unwrap();
+            let now = Instant::now();
+
+            let start = (*full_at).max(now);
+            *full_at = start + self.interval;
+
+            // The bucket holds `burst` tokens, so requests only wait
+            // once it is more than `burst - 1` intervals from full.
+            (start - now).saturating_sub(self.interval * (self.burst - 1))
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Limits the rate of generations using a [`RateLimiter`].
+///
+/// Cloning the backend shares its limiter, so multiple nodes can use the same quota.
+/// Only the start of each generation is limited, not how many run at once.
+pub struct RateLimitedBackend<T: LlmBackend> {
+    pub backend: Arc<T>,
+    pub limiter: Arc<RateLimiter>,
+}
+
+impl<T: LlmBackend> RateLimitedBackend<T> {
+    pub fn new(backend: Arc<T>, limiter: Arc<RateLimiter>) -> Self {
+        Self { backend, limiter }
+    }
+}
+
+impl<T: LlmBackend> Clone for RateLimitedBackend<T> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+impl<T: LlmBackend> LlmBackend for RateLimitedBackend<T> {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.limiter.acquire().await;
+        self.backend.generate(prompt).await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        self.limiter.acquire().await;
+        self.backend.generate_with(prompt, options).await
+    }
+
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        self.limiter.acquire().await;
+        self.backend.generate_detailed(prompt, options).await
+    }
+
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Stream<Item = Result<String, GenerateError>> {
+        stream::once(self.limiter.acquire())
+            .flat_map(move |_| self.backend.generate_stream(prompt, options))
+    }
+
+    async fn init(&self) -> Result<(), GenerateError> {
+        self.backend.init().await
+    }
+
+    fn cost_estimate(&self, usage: &Usage) -> Option<f64> {
+        self.backend.cost_estimate(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::future::join_all;
+
+    use crate::mock::MockBackend;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limit() {
+        let limiter = Arc::new(RateLimiter::new(50.0));
+        let backend = RateLimitedBackend::new(Arc::new(MockBackend::fixed("ok")), limiter);
+        let other = backend.clone();
+
+        let start = Instant::now();
+
+        let responses = join_all((0..6).map(|i| {
+            let backend = if i % 2 == 0 { &backend } else { &other };
+            backend.generate("hi")
+        }))
+        .await;
+
+        assert!(responses
+            .iter()
+            .all(|res| res.as_deref().ok() == Some("ok")));
+
+        // The first request starts immediately, and each after it waits 20ms.
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limit_burst() {
+        let limiter = Arc::new(RateLimiter::new(1.0).with_burst(3));
+        let backend = RateLimitedBackend::new(Arc::new(MockBackend::fixed("ok")), limiter);
+
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            backend.generate("hi").await.unwrap();
+        }
+
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // The bucket is empty, so the next request waits for a token.
+        backend.generate("hi").await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+}
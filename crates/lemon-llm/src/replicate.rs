@@ -1,11 +1,45 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
 
-use replicate_rust::{config::Config, Replicate};
+use futures_util::{
+    future::{self, Either},
+    stream, Stream, TryStreamExt,
+};
+use replicate_rust::config::Config;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
 
-use crate::{GenerateError, LlmBackend};
+use crate::{
+    lines::{events, lines, parse_until_done, Event, Parsed},
+    until_cancelled, GenerateError, GenerateOptions, GenerateOutput, LlmBackend, Pricing, Usage,
+};
+
+/// Seed used when generating deterministically.
+const DETERMINISTIC_SEED: u64 = 0;
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 pub struct ReplicateBackend {
     pub model: ReplicateModel,
+    /// Version of the model to run, for reproducible runs.
+    /// Defaults to the version of [`ReplicateModel`].
+    pub version: Option<String>,
+    /// Passes a fixed seed and a temperature of 0 to the model.
+    ///
+    /// Replicate does not guarantee determinism: these inputs are only honored
+    /// by models that accept them, and hardware differences between runs can
+    /// still change the output.
+    pub deterministic: bool,
+    /// Pricing of the model, used by [`LlmBackend::cost_estimate`].
+    pub pricing: Option<Pricing>,
+    /// How often to poll the status of a running prediction.
+    pub poll_interval: Duration,
+    /// How long to poll a prediction before giving up.
+    /// Polls until the prediction finishes by default.
+    pub poll_timeout: Option<Duration>,
     config: Config,
 }
 
@@ -23,26 +57,268 @@ impl ReplicateModel {
             Self::Mistral7B => "mistralai/mistral-7b-instruct-v0.1:83b6a56e7c828e667f21fd596c338fd4f0039b46bcfa18d973e8e70e455fda70",
         }
     }
+
+    /// The version id of the model.
+    pub fn version(&self) -> &str {
+        self.as_str()
+            .split_once(':')
+            .map(|(_, version)| version)
+            .unwrap_or_default()
+    }
 }
 
 impl ReplicateBackend {
     pub fn new(model: ReplicateModel, config: Config) -> Self {
-        Self { model, config }
+        Self {
+            model,
+            version: None,
+            deterministic: false,
+            pricing: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            poll_timeout: None,
+            config,
+        }
     }
-}
 
-impl LlmBackend for ReplicateBackend {
-    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
-        let replicate = Replicate::new(self.config.clone());
+    /// Pins the version of the model to run.
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn with_deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
 
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn with_poll_timeout(mut self, poll_timeout: Duration) -> Self {
+        self.poll_timeout = Some(poll_timeout);
+        self
+    }
+
+    fn version(&self) -> &str {
+        self.version
+            .as_deref()
+            .unwrap_or_else(|| self.model.version())
+    }
+
+    /// Deterministic mode takes precedence over the temperature in `options`.
+    fn inputs(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> HashMap<&'static str, serde_json::Value> {
         let mut inputs = HashMap::new();
-        inputs.insert("prompt", prompt);
+        inputs.insert("prompt", prompt.into());
 
-        let result = replicate
-            .run(self.model.as_str(), inputs)
+        if let Some(system) = &options.system {
+            inputs.insert("system_prompt", system.as_str().into());
+        }
+
+        if let Some(temperature) = options.temperature {
+            inputs.insert("temperature", temperature.into());
+        }
+
+        if let Some(top_p) = options.top_p {
+            inputs.insert("top_p", top_p.into());
+        }
+
+        if let Some(max_tokens) = options.max_tokens {
+            inputs.insert("max_new_tokens", max_tokens.into());
+        }
+
+        if !options.stop.is_empty() {
+            inputs.insert("stop_sequences", options.stop.join(",").into());
+        }
+
+        if self.deterministic {
+            inputs.insert("seed", DETERMINISTIC_SEED.into());
+            inputs.insert("temperature", 0.into());
+        }
+
+        inputs
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request
+            .header("Authorization", format!("Token {}", self.config.auth))
+            .header("User-Agent", &self.config.user_agent)
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Prediction, GenerateError> {
+        let response = self
+            .authorize(request)
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        let status = response.status();
+
+        let text = response
+            .text()
+            .await
             .map_err(|e| GenerateError::BackendError(e.to_string()))?;
 
-        let output = result
+        if !status.is_success() {
+            return Err(GenerateError::from_status(
+                status.as_u16(),
+                format!("{}: {}", status, text),
+            ));
+        }
+
+        serde_json::from_str(&text)
+            .map_err(|e| GenerateError::BackendError(format!("Invalid response: {}", e)))
+    }
+
+    fn base_url(&self) -> &str {
+        self.config.base_url.trim_end_matches('/')
+    }
+
+    async fn create(
+        &self,
+        client: &reqwest::Client,
+        prompt: &str,
+        options: &GenerateOptions,
+        stream: bool,
+    ) -> Result<Prediction, GenerateError> {
+        self.send(
+            client
+                .post(format!("{}/predictions", self.base_url()))
+                .json(&PredictionRequest {
+                    version: self.version(),
+                    input: self.inputs(prompt, options),
+                    stream,
+                }),
+        )
+        .await
+    }
+
+    /// Creates a prediction, then polls it until it finishes.
+    /// If `cancel` is triggered while polling, the prediction is cancelled.
+    async fn run(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Prediction, GenerateError> {
+        let client = reqwest::Client::new();
+        let create = self.create(&client, prompt, options, false);
+        let prediction = until_cancelled(cancel, create).await?;
+        self.poll(&client, prediction, cancel).await
+    }
+
+    async fn poll(
+        &self,
+        client: &reqwest::Client,
+        mut prediction: Prediction,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Prediction, GenerateError> {
+        let base_url = self.base_url();
+        let start = Instant::now();
+
+        loop {
+            match prediction.status {
+                PredictionStatus::Starting | PredictionStatus::Processing => {}
+                PredictionStatus::Succeeded => return Ok(prediction),
+                PredictionStatus::Failed => {
+                    return Err(GenerateError::BackendError(format!(
+                        "Prediction failed: {}",
+                        prediction.error.unwrap_or_default()
+                    )))
+                }
+                PredictionStatus::Canceled => {
+                    return Err(GenerateError::BackendError(
+                        "Prediction was canceled".to_string(),
+                    ))
+                }
+            }
+
+            if self
+                .poll_timeout
+                .is_some_and(|timeout| start.elapsed() >= timeout)
+            {
+                return Err(GenerateError::Timeout(format!(
+                    "Prediction {} timed out",
+                    prediction.id
+                )));
+            }
+
+            let poll = async {
+                tokio::time::sleep(self.poll_interval).await;
+                self.send(client.get(format!("{}/predictions/{}", base_url, prediction.id)))
+                    .await
+            };
+
+            prediction = match until_cancelled(cancel, poll).await {
+                Err(e) if cancel.is_some_and(CancellationToken::is_cancelled) => {
+                    // Cancelling is best effort, the generation fails either way.
+                    let url = format!("{}/predictions/{}/cancel", base_url, prediction.id);
+                    if let Err(e) = self.send(client.post(url)).await {
+                        tracing::warn!("Failed to cancel prediction {}: {}", prediction.id, e);
+                    }
+
+                    return Err(e);
+                }
+                res => res?,
+            };
+        }
+    }
+
+    /// Creates a streaming prediction, returning a stream of its output.
+    /// Models that do not support streaming are polled instead,
+    /// and their output is yielded as a single chunk.
+    async fn stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<impl Stream<Item = Result<String, GenerateError>>, GenerateError> {
+        let client = reqwest::Client::new();
+        let prediction = self.create(&client, prompt, options, true).await?;
+
+        let Some(url) = prediction
+            .urls
+            .as_ref()
+            .and_then(|urls| urls.stream.clone())
+        else {
+            let prediction = self.poll(&client, prediction, None).await?;
+            let text = Self::output(prediction).map(|output| output.text);
+            return Ok(Either::Right(stream::once(future::ready(text))));
+        };
+
+        let response = self
+            .authorize(client.get(url))
+            .header("Accept", "text/event-stream")
+            .send()
+            .await
+            .map_err(|e| GenerateError::Transient(e.to_string()))?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(GenerateError::from_status(
+                status.as_u16(),
+                format!("{}: {}", status, text),
+            ));
+        }
+
+        Ok(Either::Left(parse_events(Box::pin(
+            response.bytes_stream(),
+        ))))
+    }
+
+    fn output(prediction: Prediction) -> Result<GenerateOutput, GenerateError> {
+        let output = prediction
             .output
             .ok_or(GenerateError::BackendError("No output".to_string()))?;
 
@@ -50,11 +326,488 @@ impl LlmBackend for ReplicateBackend {
             "Output is not an array".to_string(),
         ))?;
 
-        Ok(array
+        let text = array
             .iter()
             .map(|x| x.as_str().unwrap_or_default())
             .collect::<String>()
             .trim()
-            .to_string())
+            .to_string();
+
+        Ok(GenerateOutput {
+            text,
+            usage: prediction.metrics.as_ref().and_then(metrics_usage),
+            ..Default::default()
+        })
+    }
+}
+
+impl LlmBackend for ReplicateBackend {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.generate_with(prompt, &GenerateOptions::default())
+            .await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        Ok(self.generate_detailed(prompt, options).await?.text)
+    }
+
+    /// Usage is read from the prediction metrics, for models that report token counts.
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        Self::output(self.run(prompt, options, None).await?)
+    }
+
+    /// Streams output events of the prediction, for models that support streaming.
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Stream<Item = Result<String, GenerateError>> {
+        stream::once(self.stream(prompt, options)).try_flatten()
+    }
+
+    /// Cancels the prediction on Replicate, so it stops running.
+    async fn generate_cancellable(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+        cancel: &CancellationToken,
+    ) -> Result<GenerateOutput, GenerateError> {
+        Self::output(self.run(prompt, options, Some(cancel)).await?)
+    }
+
+    fn cost_estimate(&self, usage: &Usage) -> Option<f64> {
+        self.pricing.map(|pricing| pricing.cost(usage))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PredictionRequest<'a> {
+    version: &'a str,
+    input: HashMap<&'static str, serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Prediction {
+    id: String,
+    status: PredictionStatus,
+    output: Option<serde_json::Value>,
+    error: Option<String>,
+    metrics: Option<HashMap<String, serde_json::Value>>,
+    #[serde(default)]
+    urls: Option<PredictionUrls>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PredictionUrls {
+    /// Server-sent events URL, for models that support streaming.
+    #[serde(default)]
+    stream: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum PredictionStatus {
+    Starting,
+    Processing,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+/// Parses a stream of server-sent events from Replicate into output chunks.
+///
+/// `error` events are yielded as errors, as is an error if the stream
+/// ends before a `done` event.
+fn parse_events<B: AsRef<[u8]>, E: Display>(
+    bytes: impl Stream<Item = Result<B, E>> + Unpin,
+) -> impl Stream<Item = Result<String, GenerateError>> {
+    parse_until_done(
+        events(lines(bytes)),
+        |Event { name, data }| match name.as_deref() {
+            Some("output") => Ok(Parsed::Item(data)),
+            Some("error") => {
+                let detail = serde_json::from_str::<EventError>(&data)
+                    .map(|error| error.detail)
+                    .unwrap_or(data);
+                Err(GenerateError::BackendError(format!(
+                    "Prediction failed: {}",
+                    detail
+                )))
+            }
+            Some("done") => {
+                let reason = serde_json::from_str::<EventDone>(&data)
+                    .ok()
+                    .and_then(|done| done.reason);
+
+                match reason.as_deref() {
+                    Some("canceled") => Err(GenerateError::BackendError(
+                        "Prediction was canceled".to_string(),
+                    )),
+                    _ => Ok(Parsed::Done),
+                }
+            }
+            _ => Ok(Parsed::Skip),
+        },
+        "Stream ended before the prediction was done",
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct EventError {
+    detail: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventDone {
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+fn metrics_usage(metrics: &HashMap<String, serde_json::Value>) -> Option<Usage> {
+    let count = |key: &str| metrics.get(key)?.as_u64().map(|count| count as u32);
+
+    Some(Usage {
+        prompt_tokens: count("input_token_count")?,
+        completion_tokens: count("output_token_count")?,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use serde_json::json;
+    use wiremock::{
+        matchers::{body_partial_json, header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    fn prediction(status: &str) -> serde_json::Value {
+        json!({
+            "id": "abc",
+            "status": status,
+            "output": (status == "succeeded").then(|| json!(["Hello", ", world!"])),
+            "error": (status == "failed").then_some("Out of memory"),
+            "metrics": { "input_token_count": 3, "output_token_count": 4 }
+        })
+    }
+
+    async fn backend(server: &MockServer, statuses: &[&str]) -> ReplicateBackend {
+        Mock::given(method("POST"))
+            .and(path("/predictions"))
+            .and(header("Authorization", "Token test-token"))
+            .and(body_partial_json(json!({
+                "version": "v1",
+                "input": { "prompt": "Hi" }
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(prediction("starting")))
+            .expect(1)
+            .mount(server)
+            .await;
+
+        for status in statuses {
+            Mock::given(method("GET"))
+                .and(path("/predictions/abc"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(prediction(status)))
+                .up_to_n_times(1)
+                .mount(server)
+                .await;
+        }
+
+        new_backend(server)
+    }
+
+    fn new_backend(server: &MockServer) -> ReplicateBackend {
+        let config = Config {
+            auth: "test-token".to_string(),
+            base_url: server.uri(),
+            ..Default::default()
+        };
+
+        ReplicateBackend::new(ReplicateModel::default(), config)
+            .with_version("v1")
+            .with_poll_interval(Duration::from_millis(1))
+    }
+
+    async fn stream_backend(server: &MockServer, events: &str) -> ReplicateBackend {
+        let mut prediction = prediction("starting");
+        prediction["urls"] = json!({ "stream": format!("{}/stream/abc", server.uri()) });
+
+        Mock::given(method("POST"))
+            .and(path("/predictions"))
+            .and(body_partial_json(json!({ "stream": true })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(prediction))
+            .expect(1)
+            .mount(server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/stream/abc"))
+            .and(header("Accept", "text/event-stream"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-Type", "text/event-stream")
+                    .set_body_string(events),
+            )
+            .expect(1)
+            .mount(server)
+            .await;
+
+        new_backend(server)
+    }
+
+    #[tokio::test]
+    async fn test_polling() {
+        let server = MockServer::start().await;
+        let backend = backend(&server, &["processing", "succeeded"]).await;
+
+        let output = backend
+            .generate_detailed("Hi", &Default::default())
+            .await
+            .unwrap();
+
+        assert_eq!(output.text, "Hello, world!");
+        assert_eq!(
+            output.usage,
+            Some(Usage {
+                prompt_tokens: 3,
+                completion_tokens: 4,
+                ..Default::default()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed() {
+        let server = MockServer::start().await;
+        let backend = backend(&server, &["processing", "failed"]).await;
+
+        let res = backend.generate("Hi").await;
+        assert!(matches!(res, Err(GenerateError::BackendError(e)) if e.contains("Out of memory")));
+    }
+
+    #[tokio::test]
+    async fn test_canceled() {
+        let server = MockServer::start().await;
+        let backend = backend(&server, &["canceled"]).await;
+
+        assert!(matches!(
+            backend.generate("Hi").await,
+            Err(GenerateError::BackendError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_poll_timeout() {
+        let server = MockServer::start().await;
+        let backend = backend(&server, &[])
+            .await
+            .with_poll_timeout(Duration::from_millis(20));
+
+        Mock::given(method("GET"))
+            .and(path("/predictions/abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(prediction("processing")))
+            .mount(&server)
+            .await;
+
+        let res = backend.generate("Hi").await;
+        assert!(matches!(res, Err(GenerateError::Timeout(e)) if e.contains("timed out")));
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let server = MockServer::start().await;
+        let backend = backend(&server, &[])
+            .await
+            .with_poll_interval(Duration::from_millis(10));
+
+        Mock::given(method("GET"))
+            .and(path("/predictions/abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(prediction("processing")))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/predictions/abc/cancel"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(prediction("canceled")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = GenerateOptions::default();
+        let cancel = CancellationToken::new();
+
+        let (res, _) = tokio::join!(
+            backend.generate_cancellable("Hi", &options, &cancel),
+            async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                cancel.cancel();
+            }
+        );
+
+        assert!(matches!(res, Err(GenerateError::BackendError(e)) if e == "cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_stream() {
+        let server = MockServer::start().await;
+        let backend = stream_backend(
+            &server,
+            "event: output\nid: 1\ndata: Hello\n\n\
+             : keep-alive\n\n\
+             event: output\nid: 2\ndata: , world!\n\n\
+             event: done\ndata: {}\n\n",
+        )
+        .await;
+
+        let chunks = backend
+            .generate_stream("Hi", &Default::default())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_deref().ok(), Some("Hello"));
+        assert_eq!(chunks[1].as_deref().ok(), Some(", world!"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_error() {
+        let server = MockServer::start().await;
+        let backend = stream_backend(
+            &server,
+            "event: output\ndata: Hello\n\n\
+             event: error\ndata: {\"detail\": \"Out of memory\"}\n\n",
+        )
+        .await;
+
+        let chunks = backend
+            .generate_stream("Hi", &Default::default())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_deref().ok(), Some("Hello"));
+        assert!(
+            matches!(&chunks[1], Err(GenerateError::BackendError(e)) if e.contains("Out of memory"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_truncated() {
+        let server = MockServer::start().await;
+        let backend = stream_backend(&server, "event: output\ndata: Hello\n\n").await;
+
+        let chunks = backend
+            .generate_stream("Hi", &Default::default())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert!(matches!(&chunks[1], Err(GenerateError::BackendError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_stream_fallback() {
+        let server = MockServer::start().await;
+        let backend = backend(&server, &["processing", "succeeded"]).await;
+
+        let chunks = backend
+            .generate_stream("Hi", &Default::default())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].as_deref().ok(), Some("Hello, world!"));
+    }
+
+    #[test]
+    fn test_version() {
+        let backend = ReplicateBackend::new(ReplicateModel::Llama2, Config::default());
+        assert_eq!(
+            backend.version(),
+            "73001d654114dad81ec65da3b834e2f691af1e1526453189b7bf36fb3f32d0f9"
+        );
+
+        let backend = backend.with_version("v2");
+        assert_eq!(backend.version(), "v2");
+    }
+
+    #[test]
+    fn test_deterministic_inputs() {
+        let backend = ReplicateBackend::new(ReplicateModel::default(), Config::default());
+        assert_eq!(backend.inputs("hi", &Default::default()).len(), 1);
+
+        let backend = backend.with_deterministic();
+        let inputs = backend.inputs("hi", &Default::default());
+        assert_eq!(inputs["seed"], serde_json::json!(0));
+        assert_eq!(inputs["temperature"], serde_json::json!(0));
+    }
+
+    #[test]
+    fn test_options_inputs() {
+        let backend = ReplicateBackend::new(ReplicateModel::default(), Config::default());
+
+        let options = GenerateOptions {
+            top_p: Some(0.5),
+            max_tokens: Some(64),
+            stop: vec!["a".to_string(), "b".to_string()],
+            ..Default::default()
+        };
+
+        let inputs = backend.inputs("hi", &options);
+        assert_eq!(inputs.len(), 4);
+        assert_eq!(inputs["top_p"], serde_json::json!(0.5));
+        assert_eq!(inputs["max_new_tokens"], serde_json::json!(64));
+        assert_eq!(inputs["stop_sequences"], serde_json::json!("a,b"));
+    }
+
+    #[test]
+    fn test_metrics_usage() {
+        let metrics = serde_json::from_value(serde_json::json!({
+            "predict_time": 1.5,
+            "input_token_count": 12,
+            "output_token_count": 34
+        }))
+        .unwrap();
+
+        assert_eq!(
+            metrics_usage(&metrics),
+            Some(Usage {
+                prompt_tokens: 12,
+                completion_tokens: 34,
+                ..Default::default()
+            })
+        );
+        assert_eq!(metrics_usage(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_cost_estimate() {
+        let usage = Usage {
+            prompt_tokens: 1000,
+            completion_tokens: 1000,
+            ..Default::default()
+        };
+
+        let backend = ReplicateBackend::new(ReplicateModel::default(), Config::default());
+        assert_eq!(backend.cost_estimate(&usage), None);
+
+        let backend = backend.with_pricing(Pricing {
+            input_per_1k: 0.05,
+            output_per_1k: 0.25,
+        });
+        assert_eq!(backend.cost_estimate(&usage), Some(0.3));
     }
 }
@@ -0,0 +1,188 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+    time::Duration,
+};
+
+use futures_util::Stream;
+use tracing::warn;
+
+use crate::{GenerateError, GenerateOptions, GenerateOutput, LlmBackend, Usage};
+
+/// Retries failed generations with exponential backoff.
+///
+/// [`GenerateError::Permanent`] errors are returned without retrying.
+/// Streamed generations are not retried, as chunks may already have been consumed.
+pub struct RetryBackend<T: LlmBackend> {
+    pub backend: Arc<T>,
+    /// Number of retries after the first attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubling with each retry.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl<T: LlmBackend> RetryBackend<T> {
+    pub fn new(backend: Arc<T>, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            backend,
+            max_retries: 3,
+            base_delay,
+            max_delay,
+        }
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// The delay before the given retry, with up to half of it randomized.
+    fn delay(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+
+        let jitter = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+
+        delay.mul_f64(1.0 - jitter / 2.0)
+    }
+
+    async fn retry<R, F: Future<Output = Result<R, GenerateError>>>(
+        &self,
+        f: impl Fn() -> F,
+    ) -> Result<R, GenerateError> {
+        let mut retry = 0;
+
+        loop {
+            match f().await {
+                Err(e) if e.is_retryable() && retry < self.max_retries => {
+                    let delay = self.delay(retry);
+                    warn!("Generation failed, retrying in {:?}: {}", delay, e);
+
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+impl<T: LlmBackend> LlmBackend for RetryBackend<T> {
+    async fn generate(&self, prompt: &str) -> Result<String, GenerateError> {
+        self.retry(|| self.backend.generate(prompt)).await
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, GenerateError> {
+        self.retry(|| self.backend.generate_with(prompt, options))
+            .await
+    }
+
+    async fn generate_detailed(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<GenerateOutput, GenerateError> {
+        self.retry(|| self.backend.generate_detailed(prompt, options))
+            .await
+    }
+
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> impl Stream<Item = Result<String, GenerateError>> {
+        self.backend.generate_stream(prompt, options)
+    }
+
+    async fn init(&self) -> Result<(), GenerateError> {
+        self.retry(|| self.backend.init()).await
+    }
+
+    fn cost_estimate(&self, usage: &Usage) -> Option<f64> {
+        self.backend.cost_estimate(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::MockBackend;
+
+    use super::*;
+
+    fn retry_backend(backend: &Arc<MockBackend>) -> RetryBackend<MockBackend> {
+        RetryBackend::new(
+            backend.clone(),
+            Duration::from_millis(1),
+            Duration::from_millis(4),
+        )
+    }
+
+    fn failures(
+        n: usize,
+        error: fn(String) -> GenerateError,
+    ) -> Vec<Result<String, GenerateError>> {
+        (0..n)
+            .map(|i| Err(error(format!("attempt {}", i))))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let mut script = failures(2, GenerateError::Transient);
+        script.push(Ok("hi".to_string()));
+
+        let backend = Arc::new(MockBackend::scripted(script));
+        let retry = retry_backend(&backend);
+
+        assert_eq!(retry.generate("hi").await.unwrap(), "hi");
+        assert_eq!(backend.prompts().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausted() {
+        let backend = Arc::new(MockBackend::scripted(failures(
+            10,
+            GenerateError::BackendError,
+        )));
+        let retry = retry_backend(&backend).with_max_retries(2);
+
+        assert!(retry.generate("hi").await.is_err());
+        assert_eq!(backend.prompts().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_permanent() {
+        let backend = Arc::new(MockBackend::scripted(failures(2, GenerateError::Permanent)));
+        let retry = retry_backend(&backend);
+
+        assert!(matches!(
+            retry.generate("hi").await,
+            Err(GenerateError::Permanent(_))
+        ));
+        assert_eq!(backend.prompts().len(), 1);
+    }
+
+    #[test]
+    fn test_delay() {
+        let retry = RetryBackend::new(
+            Arc::new(MockBackend::fixed("")),
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+        );
+
+        for (n, max) in [(0, 100), (1, 200), (2, 300), (10, 300)] {
+            let delay = retry.delay(n);
+            assert!(delay <= Duration::from_millis(max));
+            assert!(delay >= Duration::from_millis(max / 2));
+        }
+    }
+}
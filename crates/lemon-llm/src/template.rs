@@ -0,0 +1,220 @@
+use lemon_graph::{
+    nodes::{GetStoreError, NodeError, NodeWrapper, StoreWrapper, SyncNode},
+    Graph, GraphEdge, GraphNode, Value,
+};
+use petgraph::graph::NodeIndex;
+
+/// Renders a prompt from a template, such as `"Summarize {text} in {n} words"`.
+///
+/// Each variable has its own input, in the order given to [`PromptTemplateWeight::new`],
+/// which is substituted for its `{name}` placeholders.
+/// Non-string values are rendered using their [`Display`](std::fmt::Display) implementation.
+/// Use `{{` and `}}` for literal braces.
+#[derive(Debug, Clone, Copy)]
+pub struct PromptTemplateNode(pub NodeIndex);
+
+impl From<PromptTemplateNode> for NodeIndex {
+    fn from(value: PromptTemplateNode) -> Self {
+        value.0
+    }
+}
+
+impl NodeWrapper for PromptTemplateNode {}
+
+impl PromptTemplateNode {
+    pub fn new(graph: &mut Graph, weight: PromptTemplateWeight) -> Self {
+        let variables = weight.variables.len();
+
+        let index = graph.add_node(GraphNode::SyncNode(Box::new(weight)));
+
+        for i in 0..variables {
+            let input = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+            graph.add_edge(input, index, GraphEdge::DataMap(i));
+        }
+
+        let output = graph.add_node(GraphNode::Store(Value::String(Default::default())));
+        graph.add_edge(index, output, GraphEdge::DataMap(0));
+
+        Self(index)
+    }
+
+    /// Returns the input store of the variable at `index`.
+    pub fn input(&self, graph: &Graph, index: usize) -> Result<StoreWrapper, GetStoreError> {
+        self.input_store(graph, index)
+    }
+
+    pub fn output(&self, graph: &Graph) -> Result<StoreWrapper, GetStoreError> {
+        self.output_store(graph, 0)
+    }
+}
+
+/// How placeholders that are not a variable are rendered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownPlaceholder {
+    /// Return an error.
+    #[default]
+    Error,
+    /// Render the placeholder as written, including its braces.
+    Literal,
+}
+
+#[derive(Debug, Clone)]
+pub struct PromptTemplateWeight {
+    pub template: String,
+    pub variables: Vec<String>,
+    pub unknown: UnknownPlaceholder,
+}
+
+impl PromptTemplateWeight {
+    pub fn new(template: impl Into<String>, variables: Vec<String>) -> Self {
+        Self {
+            template: template.into(),
+            variables,
+            unknown: UnknownPlaceholder::default(),
+        }
+    }
+
+    pub fn with_unknown(mut self, unknown: UnknownPlaceholder) -> Self {
+        self.unknown = unknown;
+        self
+    }
+
+    /// Renders the template, with `values` ordered by variable.
+    pub fn render(&self, values: &[Value]) -> Result<String, NodeError> {
+        let mut out = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+
+        while let Some(i) = rest.find(['{', '}']) {
+            out.push_str(&rest[..i]);
+            let brace = &rest[i..i + 1];
+            rest = &rest[i + 1..];
+
+            // Escaped brace.
+            if rest.starts_with(brace) {
+                out.push_str(brace);
+                rest = &rest[1..];
+                continue;
+            }
+
+            // Unmatched braces are rendered literally.
+            let end = match (brace, rest.find('}')) {
+                ("{", Some(end)) => end,
+                _ => {
+                    out.push_str(brace);
+                    continue;
+                }
+            };
+
+            let name = &rest[..end];
+            rest = &rest[end + 1..];
+
+            match self.variables.iter().position(|v| v == name) {
+                Some(index) => {
+                    let value = values.get(index).ok_or(NodeError::MissingInput(index))?;
+
+                    match value {
+                        Value::String(value) => out.push_str(value),
+                        value => out.push_str(&value.to_string()),
+                    }
+                }
+                None => match self.unknown {
+                    UnknownPlaceholder::Error => {
+                        return Err(NodeError::InternalError(format!(
+                            "Unknown placeholder {{{}}}",
+                            name
+                        )))
+                    }
+                    UnknownPlaceholder::Literal => {
+                        out.push('{');
+                        out.push_str(name);
+                        out.push('}');
+                    }
+                },
+            }
+        }
+
+        out.push_str(rest);
+
+        Ok(out)
+    }
+}
+
+impl SyncNode for PromptTemplateWeight {
+    fn run(&self, inputs: Vec<Value>) -> Result<Vec<Value>, NodeError> {
+        Ok(vec![Value::String(self.render(&inputs)?)])
+    }
+
+    fn type_name(&self) -> &str {
+        "lemon.prompt_template"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lemon_graph::Executor;
+
+    use super::*;
+
+    fn weight(template: &str, variables: &[&str]) -> PromptTemplateWeight {
+        PromptTemplateWeight::new(template, variables.iter().map(|v| v.to_string()).collect())
+    }
+
+    #[tokio::test]
+    async fn test_template() {
+        let mut graph = Graph::default();
+
+        let template = PromptTemplateNode::new(
+            &mut graph,
+            weight("Summarize {text} in {n} words.", &["text", "n"]),
+        );
+
+        let text = template.input(&graph, 0).unwrap();
+        text.set_value(&mut graph, "the news".to_string().into());
+
+        let n = template.input(&graph, 1).unwrap();
+        n.set_value(&mut graph, Value::USize(50));
+
+        Executor::execute(&mut graph, template.0).await.unwrap();
+
+        let output = template.output(&graph).unwrap();
+        match &graph[output.0] {
+            GraphNode::Store(value) => assert_eq!(
+                value,
+                &Value::String("Summarize the news in 50 words.".to_string())
+            ),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        let rendered = weight("{{\"name\": \"{name}\"}} }", &["name"])
+            .render(&["Bob".to_string().into()])
+            .unwrap();
+
+        assert_eq!(rendered, "{\"name\": \"Bob\"} }");
+    }
+
+    #[test]
+    fn test_unknown_placeholder() {
+        let template = weight("Hello {name}, {unknown}", &["name"]);
+        let values = ["Bob".to_string().into()];
+
+        assert!(matches!(
+            template.render(&values),
+            Err(NodeError::InternalError(_))
+        ));
+
+        let rendered = template
+            .with_unknown(UnknownPlaceholder::Literal)
+            .render(&values)
+            .unwrap();
+        assert_eq!(rendered, "Hello Bob, {unknown}");
+    }
+
+    #[test]
+    fn test_missing_input() {
+        let res = weight("{a} {b}", &["a", "b"]).render(&["a".to_string().into()]);
+        assert!(matches!(res, Err(NodeError::MissingInput(1))));
+    }
+}
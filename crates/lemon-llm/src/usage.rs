@@ -0,0 +1,97 @@
+use lemon_graph::Value;
+
+/// Token counts of a generation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// Prompt tokens read from the backend's prompt cache,
+    /// not counted in `prompt_tokens`.
+    pub cache_read_tokens: u32,
+    /// Prompt tokens written to the backend's prompt cache,
+    /// not counted in `prompt_tokens`.
+    pub cache_creation_tokens: u32,
+}
+
+impl Usage {
+    pub fn total_tokens(&self) -> u32 {
+        self.input_tokens() + self.completion_tokens
+    }
+
+    /// All prompt tokens, including those read from or written to the prompt cache.
+    pub fn input_tokens(&self) -> u32 {
+        self.prompt_tokens + self.cache_read_tokens + self.cache_creation_tokens
+    }
+
+    /// Converts to the values of [`LlmOutput::Usage`](crate::LlmOutput::Usage).
+    pub fn to_values(self) -> Vec<Value> {
+        vec![
+            Value::USize(self.prompt_tokens as usize),
+            Value::USize(self.completion_tokens as usize),
+        ]
+    }
+}
+
+impl std::ops::Add for Usage {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            prompt_tokens: self.prompt_tokens + rhs.prompt_tokens,
+            completion_tokens: self.completion_tokens + rhs.completion_tokens,
+            cache_read_tokens: self.cache_read_tokens + rhs.cache_read_tokens,
+            cache_creation_tokens: self.cache_creation_tokens + rhs.cache_creation_tokens,
+        }
+    }
+}
+
+impl std::ops::AddAssign for Usage {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// Per-token pricing of a hosted model, in dollars per 1000 tokens.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Pricing {
+    pub input_per_1k: f64,
+    pub output_per_1k: f64,
+}
+
+impl Pricing {
+    /// Estimated cost of the usage, in dollars.
+    /// Cached prompt tokens are priced as input tokens.
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        (usage.input_tokens() as f64 * self.input_per_1k
+            + usage.completion_tokens as f64 * self.output_per_1k)
+            / 1000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost() {
+        let pricing = Pricing {
+            input_per_1k: 0.5,
+            output_per_1k: 1.5,
+        };
+
+        let mut usage = Usage {
+            prompt_tokens: 2000,
+            completion_tokens: 500,
+            ..Default::default()
+        };
+        assert_eq!(pricing.cost(&usage), 1.75);
+
+        usage += usage;
+        assert_eq!(usage.total_tokens(), 5000);
+        assert_eq!(pricing.cost(&usage), 3.5);
+
+        usage.cache_read_tokens = 1000;
+        assert_eq!(usage.total_tokens(), 6000);
+        assert_eq!(pricing.cost(&usage), 4.0);
+    }
+}